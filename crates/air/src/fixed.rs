@@ -0,0 +1,234 @@
+use luminair_utils::LuminairError;
+use numerair::Fixed;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use stwo_prover::core::fields::m31::P;
+
+use crate::DEFAULT_FP_SCALE;
+
+/// Largest magnitude a raw fixed-point value can have without wrapping in M31.
+///
+/// Signed values are mapped to `[0, P)`, so anything beyond `(P - 1) / 2` aliases
+/// a value of the opposite sign.
+pub const MAX_FIXED_MAGNITUDE: i64 = (P as i64 - 1) / 2;
+
+/// Number of elements above which tensor conversions are split across threads.
+///
+/// Work runs on the current rayon thread pool, bounded by `RAYON_NUM_THREADS` or
+/// a custom `rayon::ThreadPool::install`.
+pub const PARALLEL_CONVERSION_THRESHOLD: usize = 1 << 16;
+
+/// Rounding applied when converting real values to fixed point.
+///
+/// Conversions only scale by powers of two and use IEEE 754 `floor` and `round`,
+/// which are exact and don't go through libm, so results are bit-exact on every platform.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round to the nearest representable value, ties away from zero.
+    ///
+    /// This is the conversion applied to `f32` graph inputs.
+    #[default]
+    Nearest,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round up with probability equal to the fractional part.
+    ///
+    /// Randomness is derived from the seed and the element index, so a given
+    /// seed always yields the same values.
+    Stochastic {
+        /// Seed of the rounding noise.
+        seed: u64,
+    },
+}
+
+/// Converts `value` to fixed point, `index` being its position in the tensor.
+///
+/// `index` only matters for `RoundingMode::Stochastic`.
+pub fn to_fixed(value: f64, mode: RoundingMode, index: usize) -> Fixed<DEFAULT_FP_SCALE> {
+    let scaled = value * (1u64 << DEFAULT_FP_SCALE) as f64;
+    match mode {
+        RoundingMode::Nearest => Fixed(scaled.round() as i64),
+        RoundingMode::Floor => Fixed(scaled.floor() as i64),
+        RoundingMode::Stochastic { seed } => {
+            let floor = scaled.floor();
+            let round_up = uniform(seed, index) < scaled - floor;
+            Fixed(floor as i64 + round_up as i64)
+        }
+    }
+}
+
+/// Converts a tensor of `f32` values to fixed point.
+///
+/// Tensors larger than `PARALLEL_CONVERSION_THRESHOLD` are converted in parallel,
+/// with the same result.
+pub fn f32_to_fixed(values: &[f32], mode: RoundingMode) -> Vec<Fixed<DEFAULT_FP_SCALE>> {
+    if values.len() > PARALLEL_CONVERSION_THRESHOLD {
+        values
+            .par_iter()
+            .enumerate()
+            .map(|(i, &v)| to_fixed(v as f64, mode, i))
+            .collect()
+    } else {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| to_fixed(v as f64, mode, i))
+            .collect()
+    }
+}
+
+/// Converts a tensor of fixed-point values back to `f32`.
+pub fn fixed_to_f32(values: &[Fixed<DEFAULT_FP_SCALE>]) -> Vec<f32> {
+    let to_f32 =
+        |v: &Fixed<DEFAULT_FP_SCALE>| (v.0 as f64 / (1u64 << DEFAULT_FP_SCALE) as f64) as f32;
+    if values.len() > PARALLEL_CONVERSION_THRESHOLD {
+        values.par_iter().map(to_f32).collect()
+    } else {
+        values.iter().map(to_f32).collect()
+    }
+}
+
+/// Numerators of the conformance inputs, in units of a quarter of the fixed-point resolution.
+const CONFORMANCE_INPUTS: [i64; 14] = [-9, -6, -5, -2, -1, 0, 1, 2, 3, 5, 6, 7, 10, 4099];
+/// Seed used for the stochastic conformance vector.
+const CONFORMANCE_SEED: u64 = 0x4c55_4d49;
+/// Expected stochastic rounding of `CONFORMANCE_INPUTS` with `CONFORMANCE_SEED`.
+const CONFORMANCE_STOCHASTIC: [i64; 14] = [-3, -1, -1, 0, 0, 0, 0, 0, 1, 1, 1, 2, 3, 1025];
+
+/// Checks that conversions on this machine match the reference results.
+///
+/// Inputs sit exactly on quarters of the fixed-point resolution, so the expected
+/// nearest and floor results are computed with integer arithmetic, and the
+/// stochastic results are compared with a fixed reference vector.
+pub fn conformance_self_test() -> Result<(), LuminairError> {
+    let values = CONFORMANCE_INPUTS
+        .iter()
+        .map(|&n| (n as f64 / (1u64 << (DEFAULT_FP_SCALE + 2)) as f64) as f32)
+        .collect::<Vec<_>>();
+
+    let nearest = CONFORMANCE_INPUTS
+        .iter()
+        .map(|&n| n.signum() * ((n.abs() + 2) / 4))
+        .collect::<Vec<_>>();
+    let floor = CONFORMANCE_INPUTS
+        .iter()
+        .map(|&n| n.div_euclid(4))
+        .collect::<Vec<_>>();
+
+    for (mode, expected) in [
+        (RoundingMode::Nearest, nearest.as_slice()),
+        (RoundingMode::Floor, floor.as_slice()),
+        (
+            RoundingMode::Stochastic {
+                seed: CONFORMANCE_SEED,
+            },
+            CONFORMANCE_STOCHASTIC.as_slice(),
+        ),
+    ] {
+        let fixed = f32_to_fixed(&values, mode);
+        if let Some(i) = (0..fixed.len()).find(|&i| fixed[i].0 != expected[i]) {
+            return Err(LuminairError::ConformanceError(format!(
+                "{:?} rounding of input {} gave {}, expected {}",
+                mode, i, fixed[i].0, expected[i]
+            )));
+        }
+
+        let resolution = (1u64 << DEFAULT_FP_SCALE) as f32;
+        let back = fixed_to_f32(&fixed);
+        if let Some(i) = (0..fixed.len()).find(|&i| back[i] != expected[i] as f32 / resolution) {
+            return Err(LuminairError::ConformanceError(format!(
+                "Conversion of {} back to f32 is not exact",
+                fixed[i].0
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the index of the first value that doesn't fit in M31, if any.
+pub fn find_overflow(values: &[Fixed<DEFAULT_FP_SCALE>]) -> Option<usize> {
+    values.iter().position(|v| v.0.abs() > MAX_FIXED_MAGNITUDE)
+}
+
+/// Returns a uniform value in `[0, 1)` derived from `seed` and `index` (SplitMix64).
+fn uniform(seed: u64, index: usize) -> f64 {
+    let mut z = seed.wrapping_add((index as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: i64 = 1 << DEFAULT_FP_SCALE;
+
+    #[test]
+    fn test_nearest_and_floor() {
+        let quarter_ulp = 0.25 / ONE as f64;
+
+        assert_eq!(to_fixed(1.0 + quarter_ulp, RoundingMode::Nearest, 0).0, ONE);
+        assert_eq!(to_fixed(1.0 - quarter_ulp, RoundingMode::Nearest, 0).0, ONE);
+        assert_eq!(
+            to_fixed(1.0 - quarter_ulp, RoundingMode::Floor, 0).0,
+            ONE - 1
+        );
+        assert_eq!(to_fixed(-quarter_ulp, RoundingMode::Floor, 0).0, -1);
+    }
+
+    #[test]
+    fn test_stochastic_is_reproducible_and_unbiased() {
+        let mode = RoundingMode::Stochastic { seed: 42 };
+        let values = [0.25 / ONE as f32; 10_000];
+        let fixed = f32_to_fixed(&values, mode);
+        let again = f32_to_fixed(&values, mode);
+
+        assert!(fixed.iter().zip(again.iter()).all(|(a, b)| a.0 == b.0));
+        assert!(fixed.iter().all(|v| v.0 == 0 || v.0 == 1));
+        let ups = fixed.iter().filter(|v| v.0 == 1).count();
+        assert!((2_000..3_000).contains(&ups));
+    }
+
+    #[test]
+    fn test_conformance_self_test() {
+        conformance_self_test().unwrap();
+    }
+
+    #[test]
+    fn test_find_overflow() {
+        let values = [
+            Fixed(0),
+            Fixed(-MAX_FIXED_MAGNITUDE),
+            Fixed(MAX_FIXED_MAGNITUDE + 1),
+        ];
+        assert_eq!(find_overflow(&values[..2]), None);
+        assert_eq!(find_overflow(&values), Some(2));
+    }
+
+    #[test]
+    fn test_exact_values_round_trip() {
+        let values = [0.5, -1.25, 3.0, 0.0];
+        for mode in [
+            RoundingMode::Nearest,
+            RoundingMode::Floor,
+            RoundingMode::Stochastic { seed: 7 },
+        ] {
+            assert_eq!(fixed_to_f32(&f32_to_fixed(&values, mode)), values);
+        }
+    }
+
+    #[test]
+    fn test_parallel_conversion_matches_elementwise() {
+        let mode = RoundingMode::Stochastic { seed: 3 };
+        let values = (0..PARALLEL_CONVERSION_THRESHOLD + 1)
+            .map(|i| i as f32 / 7.0)
+            .collect::<Vec<_>>();
+        let fixed = f32_to_fixed(&values, mode);
+        for (i, (&v, f)) in values.iter().zip(&fixed).enumerate() {
+            assert_eq!(f.0, to_fixed(v as f64, mode, i).0);
+        }
+        assert_eq!(fixed_to_f32(&fixed).len(), values.len());
+    }
+}
@@ -0,0 +1,134 @@
+use luminair_utils::LuminairError;
+use numerair::Fixed;
+use stwo_prover::core::vcs::blake2_hash::{Blake2sHash, Blake2sHasher};
+
+use crate::{
+    fixed::{f32_to_fixed, RoundingMode},
+    DEFAULT_FP_SCALE,
+};
+
+/// Domain separator for single tensor hashes.
+const TENSOR_DOMAIN: &[u8] = b"luminair.tensor.v1";
+/// Domain separator for hashes of named tensor collections.
+const TENSORS_DOMAIN: &[u8] = b"luminair.tensors.v1";
+/// Tag of the fixed-point element type.
+const FIXED_DTYPE: &[u8] = b"fixed";
+
+/// Computes the canonical Blake2s hash of a fixed-point tensor.
+///
+/// The hash covers the element type and fixed-point scale, the shape and the raw
+/// fixed-point values in row-major order, so two tensors hash equally only if
+/// they are identical on the circuit side.
+///
+/// Fails if the number of values doesn't match the shape.
+pub fn hash_tensor(
+    shape: &[usize],
+    data: &[Fixed<DEFAULT_FP_SCALE>],
+) -> Result<Blake2sHash, LuminairError> {
+    let size = shape
+        .iter()
+        .try_fold(1usize, |size, &d| size.checked_mul(d));
+    if size != Some(data.len()) {
+        return Err(LuminairError::InvalidTensorData(format!(
+            "{} values don't match shape {:?}",
+            data.len(),
+            shape
+        )));
+    }
+
+    let mut hasher = Blake2sHasher::new();
+    hasher.update(TENSOR_DOMAIN);
+    hasher.update(&(FIXED_DTYPE.len() as u64).to_le_bytes());
+    hasher.update(FIXED_DTYPE);
+    hasher.update(&DEFAULT_FP_SCALE.to_le_bytes());
+    hasher.update(&(shape.len() as u64).to_le_bytes());
+    for dim in shape {
+        hasher.update(&(*dim as u64).to_le_bytes());
+    }
+    for value in data {
+        hasher.update(&value.0.to_le_bytes());
+    }
+    Ok(hasher.finalize())
+}
+
+/// Computes the canonical hash of an `f32` tensor once converted to fixed point.
+///
+/// Values are rounded to nearest, as graph inputs set from `f32` data are.
+pub fn hash_f32_tensor(shape: &[usize], data: &[f32]) -> Result<Blake2sHash, LuminairError> {
+    hash_tensor(shape, &f32_to_fixed(data, RoundingMode::Nearest))
+}
+
+/// Computes the canonical hash of a collection of named fixed-point tensors.
+///
+/// Tensors are hashed in name order, so the result doesn't depend on iteration order.
+pub fn hash_tensors<'a>(
+    tensors: impl IntoIterator<Item = (&'a str, &'a [usize], &'a [Fixed<DEFAULT_FP_SCALE>])>,
+) -> Result<Blake2sHash, LuminairError> {
+    let mut hashes = tensors
+        .into_iter()
+        .map(|(name, shape, data)| Ok((name, hash_tensor(shape, data)?)))
+        .collect::<Result<Vec<_>, LuminairError>>()?;
+    hashes.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+    let mut hasher = Blake2sHasher::new();
+    hasher.update(TENSORS_DOMAIN);
+    hasher.update(&(hashes.len() as u64).to_le_bytes());
+    for (name, hash) in hashes {
+        hasher.update(&(name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update(&hash.0);
+    }
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::to_fixed;
+
+    #[test]
+    fn test_hash_tensor_is_canonical() {
+        let data = [1.0, -0.5, 0.25, 2.0];
+        let hash = hash_f32_tensor(&[2, 2], &data).unwrap();
+
+        assert_eq!(hash, hash_f32_tensor(&[2, 2], &data).unwrap());
+        assert_ne!(hash, hash_f32_tensor(&[4], &data).unwrap());
+        assert_ne!(
+            hash,
+            hash_f32_tensor(&[2, 2], &[1.0, -0.5, 0.25, 2.5]).unwrap()
+        );
+
+        // Hashes the values inputs are converted to, rounded to nearest
+        let value = -0.7f32;
+        assert_eq!(
+            hash_f32_tensor(&[1], &[value]).unwrap(),
+            hash_tensor(&[1], &[to_fixed(value as f64, RoundingMode::Nearest, 0)]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hash_tensor_checks_shape() {
+        let data = [1.0, 2.0, 3.0];
+        assert!(matches!(
+            hash_f32_tensor(&[2, 2], &data),
+            Err(LuminairError::InvalidTensorData(_))
+        ));
+        assert!(hash_f32_tensor(&[usize::MAX, 2], &data).is_err());
+    }
+
+    #[test]
+    fn test_hash_tensors_ignores_order() {
+        let a = [Fixed::from_f64(1.0)];
+        let b = [Fixed::from_f64(2.0)];
+        let shape = [1];
+
+        assert_eq!(
+            hash_tensors([("a", &shape[..], &a[..]), ("b", &shape[..], &b[..])]).unwrap(),
+            hash_tensors([("b", &shape[..], &b[..]), ("a", &shape[..], &a[..])]).unwrap()
+        );
+        assert_ne!(
+            hash_tensors([("a", &shape[..], &a[..]), ("b", &shape[..], &b[..])]).unwrap(),
+            hash_tensors([("a", &shape[..], &b[..]), ("b", &shape[..], &a[..])]).unwrap()
+        );
+    }
+}
@@ -8,13 +8,54 @@ use components::{
 use stwo_prover::core::{channel::Channel, pcs::TreeVec};
 
 pub mod components;
+pub mod fixed;
+pub mod hash;
 pub mod pie;
 pub mod preprocessed;
 pub mod settings;
 pub mod utils;
 
-// TODO (@raphaelDkhn): We should parametizing the fixed pointscale.
-pub const DEFAULT_FP_SCALE: u32 = 12;
+/// Number of fractional bits used by the fixed-point representation.
+///
+/// Defaults to 12 and can be overridden at build time with the `LUMINAIR_FP_SCALE`
+/// environment variable (e.g. `LUMINAIR_FP_SCALE=14 cargo build`).
+/// Every component, lookup table and serialized `CircuitSettings` share this value,
+/// so provers and verifiers must be built with the same scale.
+pub const DEFAULT_FP_SCALE: u32 = parse_fp_scale(option_env!("LUMINAIR_FP_SCALE"));
+
+/// Smallest supported number of fractional bits.
+pub const MIN_FP_SCALE: u32 = 1;
+/// Largest supported number of fractional bits, 15.
+///
+/// Fixed-point products must remain representable in M31 before rescaling: the product
+/// of two values below 1 in magnitude has `2 * scale` fractional bits, and must not
+/// exceed `MAX_FIXED_MAGNITUDE`, just under 2^30.
+pub const MAX_FP_SCALE: u32 = (fixed::MAX_FIXED_MAGNITUDE + 1).ilog2() / 2;
+
+/// Parses the `LUMINAIR_FP_SCALE` build-time value, falling back to 12 when unset.
+const fn parse_fp_scale(value: Option<&str>) -> u32 {
+    let bytes = match value {
+        Some(v) => v.as_bytes(),
+        None => return 12,
+    };
+    assert!(!bytes.is_empty(), "LUMINAIR_FP_SCALE must not be empty");
+
+    let mut scale = 0u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = bytes[i];
+        assert!(
+            digit.is_ascii_digit(),
+            "LUMINAIR_FP_SCALE must be an unsigned integer"
+        );
+        scale = scale * 10 + (digit - b'0') as u32;
+        i += 1;
+    }
+    assert!(scale >= MIN_FP_SCALE, "LUMINAIR_FP_SCALE is too small");
+    assert!(scale <= MAX_FP_SCALE, "LUMINAIR_FP_SCALE is too large");
+
+    scale
+}
 
 /// Container for claims related to the main execution trace of LuminAIR components.
 ///
@@ -39,6 +80,9 @@ pub struct LuminairClaim {
     pub max_reduce: Option<MaxReduceClaim>,
     /// Claim for the Sqrt component's trace.
     pub sqrt: Option<SqrtClaim>,
+    /// Operators executed on the CPU, whose outputs the proof does not attest to.
+    #[serde(default)]
+    pub unproven_ops: Vec<String>,
 }
 
 impl LuminairClaim {
@@ -70,6 +114,23 @@ impl LuminairClaim {
         if let Some(ref claim) = self.sqrt {
             claim.mix_into(channel);
         }
+        // Bound to the proof, so they can't be stripped to pass it off as fully proven
+        if !self.unproven_ops.is_empty() {
+            channel.mix_u64(self.unproven_ops.len() as u64);
+            for op in &self.unproven_ops {
+                channel.mix_u64(op.len() as u64);
+                for chunk in op.as_bytes().chunks(8) {
+                    let mut word = [0u8; 8];
+                    word[..chunk.len()].copy_from_slice(chunk);
+                    channel.mix_u64(u64::from_le_bytes(word));
+                }
+            }
+        }
+    }
+
+    /// Returns true if every operator of the graph is attested to by the proof.
+    pub fn is_fully_proven(&self) -> bool {
+        self.unproven_ops.is_empty()
     }
 
     /// Aggregates the log-sizes (dimensions) of all present component trace segments.
@@ -1,3 +1,10 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use luminair_utils::LuminairError;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -83,6 +90,79 @@ pub struct LuminairPie {
     pub execution_resources: ExecutionResources,
 }
 
+impl LuminairPie {
+    // --- Serde Binary ---
+    pub fn to_bincode(&self) -> Result<Vec<u8>, LuminairError> {
+        bincode::serialize(self).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to serialize PIE to bincode: {}", e))
+        })
+    }
+
+    pub fn from_bincode(data: &[u8]) -> Result<Self, LuminairError> {
+        bincode::deserialize(data).map_err(|e| {
+            LuminairError::SerializationError(format!(
+                "Failed to deserialize PIE from bincode: {}",
+                e
+            ))
+        })
+    }
+
+    pub fn to_bincode_file<P: AsRef<Path>>(&self, path: P) -> Result<(), LuminairError> {
+        let data = self.to_bincode()?;
+        std::fs::write(path, data).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to write bincode file: {}", e))
+        })
+    }
+
+    pub fn from_bincode_file<P: AsRef<Path>>(path: P) -> Result<Self, LuminairError> {
+        let data = std::fs::read(path).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to read bincode file: {}", e))
+        })?;
+        Self::from_bincode(&data)
+    }
+
+    // --- Serde JSON ---
+    pub fn to_json(&self) -> Result<String, LuminairError> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to serialize PIE to JSON: {}", e))
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, LuminairError> {
+        serde_json::from_str(json).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to deserialize PIE from JSON: {}", e))
+        })
+    }
+
+    pub fn to_json_file<P: AsRef<Path>>(&self, path: P) -> Result<(), LuminairError> {
+        let file = File::create(path).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to create file: {}", e))
+        })?;
+        let mut writer = BufWriter::new(file);
+
+        serde_json::to_writer_pretty(&mut writer, self).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to write PIE to JSON file: {}", e))
+        })?;
+
+        writer.flush().map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to flush writer: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self, LuminairError> {
+        let file = File::open(path).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to open file: {}", e))
+        })?;
+        let reader = BufReader::new(file);
+
+        serde_json::from_reader(reader).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to read PIE from JSON file: {}", e))
+        })
+    }
+}
+
 /// Struct for all LUT multiplicities
 #[derive(Serialize, Deserialize, Debug)]
 pub struct LUTMultiplicities {
@@ -93,16 +173,37 @@ pub struct LUTMultiplicities {
 ///
 /// This includes the maximum trace log-size required across all components
 /// and counts of different operation types.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct ExecutionResources {
     /// Counts of each AIR component operation executed.
     pub op_counter: OpCounter,
     /// The maximum log2 size needed for any trace segment (determines STARK domain size).
     pub max_log_size: u32,
+    /// Operators executed on luminal's CPU backend, which are not covered by the proof.
+    #[serde(default)]
+    pub unproven_ops: Vec<String>,
+    /// Time spent executing each node, in execution order.
+    ///
+    /// Only kept in memory: timings differ between runs and are not part of the serialized PIE.
+    #[serde(skip)]
+    pub node_timings: Vec<NodeTiming>,
+}
+
+/// Wall-clock time spent executing a node and filling its trace rows.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeTiming {
+    /// The unique ID of the node.
+    pub node: u32,
+    /// Description of the operator.
+    pub op: String,
+    /// Start time, in microseconds since the start of trace generation.
+    pub start_us: u64,
+    /// Duration, in microseconds.
+    pub duration_us: u64,
 }
 
 /// Counts the occurrences of each specific AIR operation type during graph execution.
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct OpCounter {
     /// Number of Add operations.
     pub add: usize,
@@ -148,8 +249,74 @@ pub struct NodeInfo {
     pub inputs: Vec<InputInfo>,
     /// Information about the node's output.
     pub output: OutputInfo,
-    /// The number of nodes that consume the output of this node.
+    /// The number of times an element of the output is read by the consumers.
+    ///
+    /// For broadcast or sliced reads, the maximum over the elements.
     pub num_consumers: u32,
+    /// The number of times each output element is read by the consumers.
+    ///
+    /// Empty if every element is read `num_consumers` times.
+    #[serde(default)]
+    pub consumer_counts: Vec<u32>,
     /// The unique ID of this node.
     pub id: u32,
 }
+
+impl NodeInfo {
+    /// Returns the number of times output element `index` is read by the consumers.
+    pub fn consumers_of(&self, index: usize) -> u32 {
+        if self.consumer_counts.is_empty() {
+            self.num_consumers
+        } else {
+            self.consumer_counts.get(index).copied().unwrap_or(0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pie() -> LuminairPie {
+        LuminairPie {
+            trace_tables: vec![],
+            execution_resources: ExecutionResources {
+                op_counter: OpCounter {
+                    add: 2,
+                    sin: 1,
+                    ..Default::default()
+                },
+                max_log_size: 5,
+                unproven_ops: vec!["Exp2".to_string()],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_pie_json_round_trip() {
+        let json = pie().to_json().unwrap();
+        let decoded = LuminairPie::from_json(&json).unwrap();
+        assert_eq!(decoded.execution_resources, pie().execution_resources);
+    }
+
+    #[test]
+    fn test_pie_bincode_round_trip() {
+        let bytes = pie().to_bincode().unwrap();
+        let decoded = LuminairPie::from_bincode(&bytes).unwrap();
+        assert_eq!(decoded.execution_resources, pie().execution_resources);
+    }
+
+    #[test]
+    fn test_pie_skips_node_timings() {
+        let mut timed = pie();
+        timed.execution_resources.node_timings = vec![NodeTiming {
+            node: 0,
+            op: "LuminairAdd".to_string(),
+            start_us: 1,
+            duration_us: 2,
+        }];
+        assert_eq!(timed.to_bincode().unwrap(), pie().to_bincode().unwrap());
+        assert!(!timed.to_json().unwrap().contains("node_timings"));
+    }
+}
@@ -4,7 +4,7 @@ use std::{
     path::Path,
 };
 
-use crate::lookups::Lookups;
+use crate::{lookups::Lookups, DEFAULT_FP_SCALE};
 use luminair_utils::LuminairError;
 use serde::{Deserialize, Serialize};
 
@@ -16,9 +16,41 @@ use serde::{Deserialize, Serialize};
 pub struct CircuitSettings {
     /// Lookup table configurations required by the circuit.
     pub lookups: Lookups,
+    /// Number of fractional bits of the fixed-point representation used to build the circuit.
+    ///
+    /// Settings written before the scale was recorded were built with 12 bits.
+    #[serde(default = "legacy_fp_scale")]
+    pub fp_scale: u32,
+}
+
+/// Scale of settings files predating `CircuitSettings::fp_scale`.
+fn legacy_fp_scale() -> u32 {
+    12
+}
+
+/// Layout of settings files predating `CircuitSettings::fp_scale`.
+///
+/// bincode isn't self-describing, so the missing field can't be defaulted by serde.
+#[derive(Deserialize)]
+struct LegacyCircuitSettings {
+    lookups: Lookups,
 }
 
 impl CircuitSettings {
+    /// Ensures these settings were generated with the fixed-point scale this binary was built with.
+    ///
+    /// Constraints and lookup tables depend on the scale, so a mismatch would make
+    /// proving or verification fail in a much less explicit way.
+    pub fn check_fp_scale(&self) -> Result<(), LuminairError> {
+        if self.fp_scale != DEFAULT_FP_SCALE {
+            return Err(LuminairError::FixedPointScaleMismatch {
+                expected: DEFAULT_FP_SCALE,
+                found: self.fp_scale,
+            });
+        }
+        Ok(())
+    }
+
     // --- Serde Binary ---
     pub fn to_bincode(&self) -> Result<Vec<u8>, LuminairError> {
         bincode::serialize(self).map_err(|e| {
@@ -29,13 +61,23 @@ impl CircuitSettings {
         })
     }
 
+    /// Also reads settings files predating `fp_scale`, as built with the legacy scale.
     pub fn from_bincode(data: &[u8]) -> Result<Self, LuminairError> {
-        bincode::deserialize(data).map_err(|e| {
-            LuminairError::SerializationError(format!(
-                "Failed to deserialize proof from bincode: {}",
-                e
-            ))
-        })
+        bincode::deserialize::<Self>(data)
+            .or_else(|e| {
+                bincode::deserialize::<LegacyCircuitSettings>(data)
+                    .map(|legacy| CircuitSettings {
+                        lookups: legacy.lookups,
+                        fp_scale: legacy_fp_scale(),
+                    })
+                    .map_err(|_| e)
+            })
+            .map_err(|e| {
+                LuminairError::SerializationError(format!(
+                    "Failed to deserialize proof from bincode: {}",
+                    e
+                ))
+            })
     }
 
     pub fn to_bincode_file<P: AsRef<Path>>(&self, path: P) -> Result<(), LuminairError> {
@@ -96,3 +138,35 @@ impl CircuitSettings {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Legacy {
+        lookups: Lookups,
+    }
+
+    #[test]
+    fn test_legacy_settings_load() {
+        let legacy = Legacy {
+            lookups: Lookups { sin: None },
+        };
+        let bytes = bincode::serialize(&legacy).unwrap();
+        assert_eq!(CircuitSettings::from_bincode(&bytes).unwrap().fp_scale, 12);
+
+        let json = serde_json::to_string(&legacy).unwrap();
+        assert_eq!(CircuitSettings::from_json(&json).unwrap().fp_scale, 12);
+    }
+
+    #[test]
+    fn test_settings_bincode_round_trip() {
+        let settings = CircuitSettings {
+            lookups: Lookups { sin: None },
+            fp_scale: DEFAULT_FP_SCALE,
+        };
+        let decoded = CircuitSettings::from_bincode(&settings.to_bincode().unwrap()).unwrap();
+        assert_eq!(decoded.fp_scale, DEFAULT_FP_SCALE);
+    }
+}
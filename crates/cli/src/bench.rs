@@ -0,0 +1,181 @@
+use luminair_graph::{
+    bench::{bench_graph, BenchResult},
+    StwoCompiler,
+};
+use luminair_utils::LuminairError;
+use luminal::prelude::*;
+
+/// A built-in operator benchmark, mirroring `crates/graph/benches/ops.rs`.
+pub struct OpBench {
+    /// Name of the benchmark, used to match baseline entries.
+    pub name: &'static str,
+    /// Number of operands.
+    arity: usize,
+    /// True if the operands must be strictly positive.
+    nonzero: bool,
+    op: fn(&[GraphTensor]) -> GraphTensor,
+}
+
+pub const OP_BENCHES: &[OpBench] = &[
+    OpBench {
+        name: "add",
+        arity: 2,
+        nonzero: false,
+        op: |t| t[0] + t[1],
+    },
+    OpBench {
+        name: "mul",
+        arity: 2,
+        nonzero: false,
+        op: |t| t[0] * t[1],
+    },
+    OpBench {
+        name: "recip",
+        arity: 1,
+        nonzero: true,
+        op: |t| t[0].recip(),
+    },
+    OpBench {
+        name: "sum_reduce",
+        arity: 1,
+        nonzero: true,
+        op: |t| t[0].sum_reduce(0),
+    },
+    OpBench {
+        name: "max_reduce",
+        arity: 1,
+        nonzero: true,
+        op: |t| t[0].max_reduce(0),
+    },
+    OpBench {
+        name: "sin",
+        arity: 1,
+        nonzero: true,
+        op: |t| t[0].sin(),
+    },
+    OpBench {
+        name: "sqrt",
+        arity: 1,
+        nonzero: true,
+        op: |t| t[0].sqrt(),
+    },
+];
+
+impl OpBench {
+    /// Benchmarks the operator on `(rows, cols)` operands.
+    pub fn run(
+        &self,
+        (rows, cols): (usize, usize),
+        iterations: usize,
+    ) -> Result<BenchResult, LuminairError> {
+        bench_graph(
+            &format!("{} ({}x{})", self.name, rows, cols),
+            iterations,
+            || {
+                let mut cx = Graph::new();
+                let operands = (0..self.arity)
+                    .map(|i| {
+                        let data = bench_data(rows * cols, i, self.nonzero);
+                        cx.tensor((rows, cols)).set(data)
+                    })
+                    .collect::<Vec<_>>();
+                let mut out = (self.op)(&operands).retrieve();
+                cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut out);
+                cx
+            },
+        )
+    }
+}
+
+/// Deterministic operand values in `[-0.5, 0.5)`, or in `[0.001, 0.5)` if `nonzero`.
+fn bench_data(len: usize, seed: usize, nonzero: bool) -> Vec<f32> {
+    (0..len)
+        .map(|i| {
+            let t = ((i + seed * len) * 7919 % 1000) as f32 / 1000.0;
+            if nonzero {
+                0.001 + t * 0.499
+            } else {
+                t - 0.5
+            }
+        })
+        .collect()
+}
+
+/// Compares `current` against `baseline` and describes each slowdown above `threshold`.
+///
+/// `threshold` is a relative increase, e.g. `0.1` for 10%. Benchmarks missing from
+/// the baseline are skipped.
+pub fn regressions(
+    current: &[BenchResult],
+    baseline: &[BenchResult],
+    threshold: f64,
+) -> Vec<String> {
+    let mut regressions = Vec::new();
+    for result in current {
+        let Some(base) = baseline.iter().find(|b| b.name == result.name) else {
+            continue;
+        };
+        for (stage, now, before) in [
+            (
+                "trace generation",
+                result.trace_generation_ms,
+                base.trace_generation_ms,
+            ),
+            ("proving", result.proving_ms, base.proving_ms),
+            ("verification", result.verification_ms, base.verification_ms),
+        ] {
+            if before > 0.0 && now > before * (1.0 + threshold) {
+                regressions.push(format!(
+                    "{}: {} took {:.2} ms, baseline {:.2} ms (+{:.1}%)",
+                    result.name,
+                    stage,
+                    now,
+                    before,
+                    (now / before - 1.0) * 100.0
+                ));
+            }
+        }
+    }
+    regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luminair_graph::cost::CostEstimate;
+
+    fn result(name: &str, proving_ms: f64) -> BenchResult {
+        BenchResult {
+            name: name.to_string(),
+            iterations: 1,
+            trace_generation_ms: 1.0,
+            proving_ms,
+            verification_ms: 1.0,
+            cost: CostEstimate::default(),
+        }
+    }
+
+    #[test]
+    fn test_regressions_above_threshold() {
+        let baseline = [result("add", 10.0), result("mul", 10.0)];
+        let current = [
+            result("add", 10.5),
+            result("mul", 12.0),
+            result("sin", 50.0),
+        ];
+
+        let regressions = regressions(&current, &baseline, 0.1);
+        assert_eq!(regressions.len(), 1);
+        assert!(regressions[0].starts_with("mul: proving"));
+    }
+
+    #[test]
+    fn test_bench_data_range() {
+        assert!(bench_data(1024, 1, true)
+            .iter()
+            .all(|v| (0.001..0.5).contains(v)));
+        assert!(bench_data(1024, 0, false)
+            .iter()
+            .all(|v| (-0.5..0.5).contains(v)));
+    }
+}
@@ -0,0 +1,222 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use luminair_air::pie::NodeTiming;
+use luminair_graph::{cost::CostEstimate, export::GraphExport};
+
+/// A compiled graph annotated with its trace rows and, if recorded, its node timings.
+///
+/// Timings are matched by node ID, so they must come from a trace of the same
+/// model compiled the same way.
+pub struct Inspection {
+    export: GraphExport,
+    rows: BTreeMap<u32, usize>,
+    timings: BTreeMap<u32, u64>,
+}
+
+impl Inspection {
+    pub fn new(export: GraphExport, cost: &CostEstimate) -> Self {
+        Self {
+            export,
+            rows: cost.ops.iter().map(|op| (op.node, op.rows)).collect(),
+            timings: BTreeMap::new(),
+        }
+    }
+
+    /// Attaches the node timings of a recorded execution.
+    pub fn with_timings(mut self, timings: &[NodeTiming]) -> Self {
+        self.timings = timings.iter().map(|t| (t.node, t.duration_us)).collect();
+        self
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    ///
+    /// Uses the colors of `GraphExport::to_dot`, with trace rows and timings added to the labels.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph luminair {\n    node [shape=box, style=filled];\n");
+        for node in &self.export.nodes {
+            let mut label = format!("{}: {}", node.id, node.op);
+            let color = match (&node.component, node.on_cpu) {
+                (Some(component), _) => {
+                    write!(label, "\\n[{}]", component).unwrap();
+                    "palegreen"
+                }
+                (None, true) => {
+                    label.push_str("\\n[cpu]");
+                    "orange"
+                }
+                (None, false) => "white",
+            };
+            if let Some(rows) = self.rows.get(&node.id) {
+                write!(label, "\\n{} rows", rows).unwrap();
+            }
+            if let Some(us) = self.timings.get(&node.id) {
+                write!(label, "\\n{} µs", us).unwrap();
+            }
+            writeln!(
+                dot,
+                "    n{} [label=\"{}\", fillcolor={}];",
+                node.id,
+                label.replace('"', "\\\""),
+                color
+            )
+            .unwrap();
+        }
+        for edge in &self.export.edges {
+            writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"{}];",
+                edge.source,
+                edge.target,
+                edge.shape.replace('"', "\\\""),
+                if edge.broadcast { ", style=dashed" } else { "" }
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders a standalone HTML page with one table row per node.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>LuminAIR graph</title>\n\
+             <style>\n\
+             body { font-family: sans-serif; }\n\
+             table { border-collapse: collapse; }\n\
+             th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }\n\
+             .proven { background: #d9f7d9; }\n\
+             .cpu { background: #ffe0b3; }\n\
+             </style>\n</head>\n<body>\n",
+        );
+        let proven = self
+            .export
+            .nodes
+            .iter()
+            .filter(|n| n.component.is_some())
+            .count();
+        let on_cpu = self.export.nodes.iter().filter(|n| n.on_cpu).count();
+        writeln!(
+            html,
+            "<p>{} nodes, {} proven, {} on the CPU, {} trace rows.</p>",
+            self.export.nodes.len(),
+            proven,
+            on_cpu,
+            self.rows.values().sum::<usize>()
+        )
+        .unwrap();
+
+        html.push_str(
+            "<table>\n<tr><th>Node</th><th>Operator</th><th>Status</th><th>Inputs</th>\
+             <th>Trace rows</th><th>Time (µs)</th></tr>\n",
+        );
+        for node in &self.export.nodes {
+            let (class, status) = match (&node.component, node.on_cpu) {
+                (Some(component), _) => ("proven", format!("proven ({})", component)),
+                (None, true) => ("cpu", "cpu, not proven".to_string()),
+                (None, false) => ("", String::new()),
+            };
+            let inputs = self
+                .export
+                .edges
+                .iter()
+                .filter(|e| e.target == node.id)
+                .map(|e| {
+                    format!(
+                        "n{}: {}{}",
+                        e.source,
+                        escape(&e.shape),
+                        if e.broadcast { " (broadcast)" } else { "" }
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("<br>");
+            let cell = |v: Option<String>| v.unwrap_or_default();
+            writeln!(
+                html,
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                class,
+                node.id,
+                escape(&node.op),
+                status,
+                inputs,
+                cell(self.rows.get(&node.id).map(usize::to_string)),
+                cell(self.timings.get(&node.id).map(u64::to_string)),
+            )
+            .unwrap();
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luminair_graph::{
+        cost::OpCost,
+        export::{ExportedEdge, ExportedNode},
+    };
+
+    fn inspection() -> Inspection {
+        let export = GraphExport {
+            nodes: vec![
+                ExportedNode {
+                    id: 0,
+                    op: "Function<Vec<f32>>".to_string(),
+                    component: None,
+                    on_cpu: false,
+                },
+                ExportedNode {
+                    id: 1,
+                    op: "LuminairSin".to_string(),
+                    component: Some("sin".to_string()),
+                    on_cpu: false,
+                },
+            ],
+            edges: vec![ExportedEdge {
+                source: 0,
+                target: 1,
+                input: 0,
+                output: 0,
+                shape: "[4]".to_string(),
+                broadcast: false,
+            }],
+        };
+        let cost = CostEstimate {
+            ops: vec![OpCost {
+                node: 1,
+                component: "sin".to_string(),
+                rows: 4,
+            }],
+            components: vec![],
+        };
+        Inspection::new(export, &cost).with_timings(&[NodeTiming {
+            node: 1,
+            op: "LuminairSin".to_string(),
+            start_us: 0,
+            duration_us: 12,
+        }])
+    }
+
+    #[test]
+    fn test_dot_annotations() {
+        let dot = inspection().to_dot();
+        assert!(dot.contains(
+            "n1 [label=\"1: LuminairSin\\n[sin]\\n4 rows\\n12 µs\", fillcolor=palegreen];"
+        ));
+        assert!(dot.contains("n0 -> n1 [label=\"[4]\"];"));
+    }
+
+    #[test]
+    fn test_html_escapes_operators() {
+        let html = inspection().to_html();
+        assert!(html.contains("Function&lt;Vec&lt;f32&gt;&gt;"));
+        assert!(html.contains("2 nodes, 1 proven, 0 on the CPU, 4 trace rows."));
+    }
+}
@@ -0,0 +1,449 @@
+use std::{collections::BTreeMap, path::Path, process::ExitCode};
+
+use luminair_air::settings::CircuitSettings;
+use luminair_graph::{
+    bench::BenchResult,
+    config::ProjectConfig,
+    description::{Model, ModelDescription},
+    doctor::diagnose,
+    export::GraphExport,
+    graph::LuminairGraph,
+    npy::{read_npz, write_npz, NpyArray},
+    weights::SafetensorsWeights,
+};
+use luminair_prover::LuminairProof;
+use luminair_verifier::verifier::verify;
+use luminal::prelude::*;
+
+use crate::{
+    bench::{regressions, OP_BENCHES},
+    inspect::Inspection,
+};
+
+mod bench;
+mod inspect;
+mod repl;
+
+type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+const USAGE: &str = "\
+Usage: luminair <command> [options]
+
+Commands:
+  compile <model.json>  Compile a model and report how it is lowered
+      --export <file>       Write the compiled graph (.dot or .json)
+      --settings <file>     Write circuit settings (requires --inputs or --weights)
+  run <model.json>      Execute a model without generating a trace
+  prove <model.json>    Execute a model and prove its execution
+      --settings <file>     Settings output path [default: settings.bin]
+      --proof <file>        Proof output path [default: proof.bin]
+      --trace <file>        Also record the execution trace
+  verify                Verify a proof
+      --settings <file>     Circuit settings [default: settings.bin]
+      --proof <file>        Proof to verify [default: proof.bin]
+  proof inspect         Print what a proof attests to
+      --proof <file>        Proof to inspect [default: proof.bin]
+      --output <file>       Also write the summary as JSON
+  inspect <model.json>  Visualize a compiled model, timing its nodes if inputs are given
+      --output <file>       Output path (.dot or .html) [default: graph.html]
+  bench                 Benchmark the built-in operators
+      --ops <a,b,...>       Operators to benchmark [default: all]
+      --size <rows>x<cols>  Operand shape [default: 32x32]
+      --iterations <n>      Runs per operator [default: 5]
+      --output <file>       Write the results as a JSON baseline
+      --baseline <file>     Fail if slower than a previous baseline
+      --threshold <pct>     Allowed slowdown, in percent [default: 10]
+  repl                  Define tensors and operators interactively, run and prove them
+  doctor                Check this installation can prove and verify
+      --trace-dir <dir>     Directory for traces and proofs [default: .]
+      --settings <file>     Also check circuit settings
+  serve <model.json>    Serve the model over HTTP, executing and proving submitted inputs
+      --addr <host:port>    Listen address [default: 127.0.0.1:8080]
+      --workers <n>         Jobs executed in parallel [default: 1]
+      --max-batch <n>       Jobs executed by a worker on one compiled graph [default: 8]
+      --weights <file>      Weights in .safetensors format
+
+Input options (compile, run, prove, inspect):
+  --inputs <file.npz>   Input arrays, by name
+  --weights <file>      Weights in .safetensors format
+  --outputs <file.npz>  Write the output arrays (run, prove)
+";
+
+/// Parsed command-line options, `--name value` pairs after the positional arguments.
+struct Args {
+    positional: Vec<String>,
+    options: BTreeMap<String, String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = BTreeMap::new();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for --{}", name))?;
+                    options.insert(name.to_string(), value);
+                }
+                None => positional.push(arg),
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn option_or<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.option(name).unwrap_or(default)
+    }
+
+    fn model(&self) -> Result<ModelDescription, Box<dyn std::error::Error>> {
+        let path = self
+            .positional
+            .get(1)
+            .ok_or("Missing model description path")?;
+        Ok(ModelDescription::from_file(path)?)
+    }
+
+    fn check_options(&self, allowed: &[&str]) -> Result<(), String> {
+        match self.options.keys().find(|k| !allowed.contains(&k.as_str())) {
+            Some(name) => Err(format!(
+                "Unknown option --{} for '{}'",
+                name, self.positional[0]
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match args.positional.first().map(String::as_str) {
+        Some("compile") => compile(&args),
+        Some("run") => run(&args),
+        Some("prove") => prove_model(&args),
+        Some("verify") => verify_proof(&args),
+        Some("proof") => match args.positional.get(1).map(String::as_str) {
+            Some("inspect") => inspect_proof(&args),
+            _ => Err(format!("Expected 'proof inspect'\n\n{}", USAGE).into()),
+        },
+        Some("inspect") => inspect(&args),
+        Some("bench") => bench(&args),
+        Some("doctor") => doctor(&args),
+        Some("repl") => repl::run_interactive(),
+        Some("serve") => serve(&args),
+        Some("help") | None => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Some(command) => Err(format!("Unknown command '{}'\n\n{}", command, USAGE).into()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn compile(args: &Args) -> CliResult {
+    args.check_options(&["export", "settings", "inputs", "weights"])?;
+    let description = args.model()?;
+    let config = ProjectConfig::load()?;
+    let mut cx = Graph::new();
+    let mut model = description.build(&mut cx)?;
+    let with_data = args.option("inputs").is_some() || args.option("weights").is_some();
+    if with_data {
+        bind_inputs(args, &description, &model)?;
+    }
+    model.compile(&mut cx, &config);
+
+    for (node, op) in cx.unsupported_ops() {
+        println!(
+            "No AIR component, tracing fails unless ops.cpu_fallback is set: {} (node {})",
+            op,
+            node.index()
+        );
+    }
+    let cost = cx.estimate_cost();
+    println!(
+        "{} proven operators, max log size {}, {} trace cells",
+        cost.ops.len(),
+        cost.max_log_size(),
+        cost.total_cells()
+    );
+    config.check_cost(&cost)?;
+
+    if let Some(path) = args.option("export") {
+        let export = GraphExport::new(&cx);
+        let rendered = match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("json") => export.to_json()?,
+            _ => export.to_dot(),
+        };
+        std::fs::write(path, rendered)?;
+        println!("Graph written to {}", path);
+    }
+
+    if let Some(path) = args.option("settings") {
+        if !with_data {
+            return Err("--settings requires --inputs or --weights to infer lookup ranges".into());
+        }
+        cx.gen_circuit_settings().to_bincode_file(path)?;
+        println!("Settings written to {}", path);
+    }
+    Ok(())
+}
+
+fn run(args: &Args) -> CliResult {
+    args.check_options(&["inputs", "weights", "outputs"])?;
+    let description = args.model()?;
+    let (arrays, weights) = read_inputs(args)?;
+    let mut cx = Graph::new();
+    let model = description.compile(&mut cx, &arrays, weights.as_ref(), &ProjectConfig::load()?)?;
+
+    write_outputs(args, &model.run(&mut cx)?)
+}
+
+fn prove_model(args: &Args) -> CliResult {
+    args.check_options(&["inputs", "weights", "outputs", "settings", "proof", "trace"])?;
+    let description = args.model()?;
+    let (arrays, weights) = read_inputs(args)?;
+    let mut cx = Graph::new();
+    let model = description.compile(&mut cx, &arrays, weights.as_ref(), &ProjectConfig::load()?)?;
+
+    let trace = model.trace(&mut cx, None)?;
+    write_outputs(args, &trace.outputs)?;
+    if let Some(path) = args.option("trace") {
+        trace.pie.to_bincode_file(path)?;
+        println!("Trace written to {}", path);
+    }
+
+    let proven = trace.prove(None)?;
+    let (settings_path, proof_path) = (
+        args.option_or("settings", "settings.bin"),
+        args.option_or("proof", "proof.bin"),
+    );
+    proven.settings.to_bincode_file(settings_path)?;
+    proven.proof.to_bincode_file(proof_path)?;
+    println!(
+        "Proof written to {}, settings to {}",
+        proof_path, settings_path
+    );
+    Ok(())
+}
+
+fn verify_proof(args: &Args) -> CliResult {
+    args.check_options(&["settings", "proof"])?;
+    let settings = CircuitSettings::from_bincode_file(args.option_or("settings", "settings.bin"))?;
+    let proof = LuminairProof::from_bincode_file(args.option_or("proof", "proof.bin"))?;
+
+    verify(proof, settings)?;
+    println!("Proof verified successfully.");
+    Ok(())
+}
+
+fn inspect_proof(args: &Args) -> CliResult {
+    args.check_options(&["proof", "output"])?;
+    let proof = LuminairProof::from_bincode_file(args.option_or("proof", "proof.bin"))?;
+
+    let summary = proof.summary()?;
+    print!("{}", summary);
+    if let Some(path) = args.option("output") {
+        std::fs::write(path, summary.to_json()?)?;
+        println!("Summary written to {}", path);
+    }
+    Ok(())
+}
+
+fn inspect(args: &Args) -> CliResult {
+    args.check_options(&["output", "inputs", "weights"])?;
+    let description = args.model()?;
+    let mut cx = Graph::new();
+    let mut model = description.build(&mut cx)?;
+    let bound = args.option("inputs").is_some() || args.option("weights").is_some();
+    if bound {
+        bind_inputs(args, &description, &model)?;
+    }
+    model.compile(&mut cx, &ProjectConfig::load()?);
+
+    let mut inspection = Inspection::new(GraphExport::new(&cx), &cx.estimate_cost());
+    if bound {
+        // Node timings are not serialized with traces, so the model is executed here
+        let mut settings = cx.gen_circuit_settings();
+        let pie = cx.gen_trace(&mut settings)?;
+        inspection = inspection.with_timings(&pie.execution_resources.node_timings);
+    }
+
+    let path = args.option_or("output", "graph.html");
+    let rendered = match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("dot") => inspection.to_dot(),
+        _ => inspection.to_html(),
+    };
+    std::fs::write(path, rendered)?;
+    println!("Graph written to {}", path);
+    Ok(())
+}
+
+fn bench(args: &Args) -> CliResult {
+    args.check_options(&[
+        "ops",
+        "size",
+        "iterations",
+        "output",
+        "baseline",
+        "threshold",
+    ])?;
+    let size = args.option_or("size", "32x32");
+    let size = size
+        .split_once('x')
+        .and_then(|(r, c)| Some((r.parse().ok()?, c.parse().ok()?)))
+        .ok_or_else(|| format!("Invalid --size '{}', expected <rows>x<cols>", size))?;
+    let iterations = args.option_or("iterations", "5").parse::<usize>()?;
+    let threshold = args.option_or("threshold", "10").parse::<f64>()? / 100.0;
+    let selected = args
+        .option("ops")
+        .map(|ops| ops.split(',').collect::<Vec<_>>());
+    if let Some(op) = selected
+        .iter()
+        .flatten()
+        .find(|op| OP_BENCHES.iter().all(|b| b.name != **op))
+    {
+        return Err(format!("Unknown benchmark '{}'", op).into());
+    }
+
+    let mut results = Vec::new();
+    for op in OP_BENCHES
+        .iter()
+        .filter(|b| selected.as_ref().is_none_or(|ops| ops.contains(&b.name)))
+    {
+        let result = op.run(size, iterations)?;
+        println!(
+            "{}: trace {:.2} ms, proving {:.2} ms, verification {:.2} ms",
+            result.name, result.trace_generation_ms, result.proving_ms, result.verification_ms
+        );
+        results.push(result);
+    }
+
+    if let Some(path) = args.option("output") {
+        std::fs::write(path, serde_json::to_string_pretty(&results)?)?;
+        println!("Results written to {}", path);
+    }
+
+    if let Some(path) = args.option("baseline") {
+        let baseline: Vec<BenchResult> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let regressions = regressions(&results, &baseline, threshold);
+        for regression in &regressions {
+            println!("Regression: {}", regression);
+        }
+        if !regressions.is_empty() {
+            return Err(format!(
+                "{} regressions above {}% against {}",
+                regressions.len(),
+                threshold * 100.0,
+                path
+            )
+            .into());
+        }
+        println!("No regressions against {}", path);
+    }
+    Ok(())
+}
+
+fn doctor(args: &Args) -> CliResult {
+    args.check_options(&["trace-dir", "settings"])?;
+    let settings = args
+        .option("settings")
+        .map(CircuitSettings::from_bincode_file)
+        .transpose()?;
+
+    let report = diagnose(
+        Path::new(args.option_or("trace-dir", ".")),
+        settings.as_ref(),
+    );
+    for diagnostic in &report.diagnostics {
+        println!("{}", diagnostic);
+    }
+    if !report.is_healthy() {
+        return Err("Some checks failed".into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serve")]
+fn serve(args: &Args) -> CliResult {
+    use luminair_graph::serve::{Service, ServiceConfig};
+
+    args.check_options(&["addr", "workers", "max-batch", "weights"])?;
+    let config = ServiceConfig {
+        workers: args.option_or("workers", "1").parse::<usize>()?,
+        max_batch_size: args.option_or("max-batch", "8").parse::<usize>()?.max(1),
+        ..ServiceConfig::default()
+    };
+    let weights = args
+        .option("weights")
+        .map(SafetensorsWeights::from_file)
+        .transpose()?;
+    let service = Service::new(args.model()?, weights, config)?;
+
+    let addr = args.option_or("addr", "127.0.0.1:8080");
+    println!("Serving on http://{}", addr);
+    Ok(service.serve(addr)?)
+}
+
+#[cfg(not(feature = "serve"))]
+fn serve(_: &Args) -> CliResult {
+    Err("luminair was built without the HTTP service, rebuild with --features serve".into())
+}
+
+/// Reads the `--inputs` arrays and `--weights` tensors.
+fn read_inputs(
+    args: &Args,
+) -> Result<(BTreeMap<String, NpyArray>, Option<SafetensorsWeights>), Box<dyn std::error::Error>> {
+    let arrays = match args.option("inputs") {
+        Some(path) => read_npz(path)?,
+        None => BTreeMap::new(),
+    };
+    let weights = args
+        .option("weights")
+        .map(SafetensorsWeights::from_file)
+        .transpose()?;
+    Ok((arrays, weights))
+}
+
+/// Binds the `--inputs` arrays and `--weights` tensors to the model's inputs.
+fn bind_inputs(args: &Args, description: &ModelDescription, model: &Model) -> CliResult {
+    let (arrays, weights) = read_inputs(args)?;
+    model.bind_inputs(description, &arrays, weights.as_ref())?;
+    Ok(())
+}
+
+/// Prints the outputs, and writes them to `--outputs` if given.
+fn write_outputs(args: &Args, outputs: &BTreeMap<String, NpyArray>) -> CliResult {
+    for (name, array) in outputs {
+        println!("{} {:?}: {:?}", name, array.shape, array.data);
+    }
+    if let Some(path) = args.option("outputs") {
+        write_npz(
+            path,
+            outputs.iter().map(|(name, array)| (name.as_str(), array)),
+        )?;
+        println!("Outputs written to {}", path);
+    }
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{BufRead, Write},
+};
+
+use luminair_graph::{
+    config::ProjectConfig,
+    description::{InputSpec, Model, ModelDescription, NodeSpec},
+    graph::LuminairGraph,
+    npy::NpyArray,
+};
+use luminair_verifier::verifier::verify;
+use luminal::prelude::*;
+
+use crate::CliResult;
+
+type EvalResult = Result<String, Box<dyn std::error::Error>>;
+
+const HELP: &str = "\
+  <name> = tensor <shape> <values...>    Define a tensor, e.g. a = tensor 2x2 1 2 3 4
+  <name> = <op> <operands...> [axes...]  Apply an operator, e.g. b = mul a a, c = sum_reduce b 1
+  run                                    Execute the session and print every result
+  prove                                  Prove and verify the session
+  list                                   Show the definitions
+  save <model.json>                      Save the session as a model description
+  reset                                  Clear the session
+  quit                                   Exit
+
+Operators are the ones of model descriptions: add, sub, mul, div, lt, matmul, neg, abs,
+recip, sqrt, sin, cos, exp, exp2, ln, log2, relu, sigmoid, sum_reduce, max_reduce and mean_reduce.
+";
+
+/// An interactive session, building a model description one definition at a time.
+///
+/// Every definition is checked by adding the model to a scratch graph, so the session
+/// always describes a valid model.
+#[derive(Default)]
+pub struct Repl {
+    description: ModelDescription,
+}
+
+impl Repl {
+    /// Evaluates a line, returning the text to print.
+    pub fn eval(&mut self, line: &str) -> EvalResult {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            [] => Ok(String::new()),
+            ["help"] => Ok(HELP.to_string()),
+            ["list"] => Ok(self.list()),
+            ["run"] => self.run(),
+            ["prove"] => self.prove(),
+            ["reset"] => {
+                self.description = ModelDescription::default();
+                Ok("Session cleared.\n".to_string())
+            }
+            ["save", path] => {
+                let mut description = self.description.clone();
+                description.outputs = self
+                    .description
+                    .nodes
+                    .last()
+                    .map(|n| n.name.clone())
+                    .into_iter()
+                    .collect();
+                std::fs::write(path, serde_json::to_string_pretty(&description)?)?;
+                Ok(format!("Model written to {}\n", path))
+            }
+            [name, "=", op, args @ ..] => self.define(name, op, args),
+            _ => Err(format!(
+                "Unrecognized input '{}', type 'help' for the syntax",
+                line.trim()
+            )
+            .into()),
+        }
+    }
+
+    /// Adds a tensor or operator definition and returns its shape.
+    fn define(&mut self, name: &str, op: &str, args: &[&str]) -> EvalResult {
+        if self.names().any(|n| n == name) {
+            return Err(format!("'{}' is already defined", name).into());
+        }
+
+        let mut description = self.description.clone();
+        if op == "tensor" {
+            let (shape, values) = args
+                .split_first()
+                .ok_or("Expected a shape and values, e.g. a = tensor 2x2 1 2 3 4")?;
+            let shape = shape
+                .split('x')
+                .map(str::parse)
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|_| format!("Invalid shape '{}', expected e.g. 2x3", shape))?;
+            let data = values
+                .iter()
+                .map(|v| {
+                    v.parse::<f32>()
+                        .map_err(|_| format!("Invalid value '{}'", v))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            description.inputs.push(InputSpec {
+                name: name.to_string(),
+                shape,
+                data: Some(data),
+            });
+        } else {
+            // Tensor names can't be numbers, so numeric arguments are reduced axes
+            let (axes, inputs): (Vec<&str>, Vec<&str>) = args
+                .iter()
+                .copied()
+                .partition(|a| a.parse::<usize>().is_ok());
+            description.nodes.push(NodeSpec {
+                name: name.to_string(),
+                op: op.to_string(),
+                inputs: inputs.into_iter().map(String::from).collect(),
+                axes: axes.into_iter().map(|a| a.parse().unwrap()).collect(),
+            });
+        }
+
+        description.outputs = vec![name.to_string()];
+        let mut scratch = Graph::new();
+        let model = description.build(&mut scratch)?;
+        let shape = dims(&model.outputs[0].1);
+        description.outputs.clear();
+        self.description = description;
+        Ok(format!("{}: {:?}\n", name, shape))
+    }
+
+    fn list(&self) -> String {
+        let mut out = String::new();
+        for input in &self.description.inputs {
+            writeln!(out, "{} = tensor {:?}", input.name, input.shape).unwrap();
+        }
+        for node in &self.description.nodes {
+            write!(out, "{} = {} {}", node.name, node.op, node.inputs.join(" ")).unwrap();
+            for axis in &node.axes {
+                write!(out, " {}", axis).unwrap();
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn run(&self) -> EvalResult {
+        let (mut cx, model) = self.session()?;
+        outputs(&model.run(&mut cx)?)
+    }
+
+    fn prove(&self) -> EvalResult {
+        let (mut cx, model) = self.session()?;
+        let mut out = String::new();
+        for (node, op) in cx.unsupported_ops() {
+            writeln!(
+                out,
+                "No AIR component, tracing fails unless ops.cpu_fallback is set: {} (node {})",
+                op,
+                node.index()
+            )?;
+        }
+
+        let proven = model.prove(&mut cx, None)?;
+        out.push_str(&outputs(&proven.outputs)?);
+        let size = proven.proof.to_bincode()?.len();
+        verify(proven.proof, proven.settings)?;
+        writeln!(out, "Proof verified, {} bytes.", size)?;
+        Ok(out)
+    }
+
+    /// Builds and compiles the session, retrieving every operator's result.
+    fn session(&self) -> Result<(Graph, Model), Box<dyn std::error::Error>> {
+        if self.description.nodes.is_empty() {
+            return Err("Nothing to execute, apply an operator first".into());
+        }
+        let mut description = self.description.clone();
+        description.outputs = description.nodes.iter().map(|n| n.name.clone()).collect();
+
+        let mut cx = Graph::new();
+        let mut model = description.build(&mut cx)?;
+        model.compile(&mut cx, &ProjectConfig::load()?);
+        Ok((cx, model))
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        let description = &self.description;
+        description
+            .inputs
+            .iter()
+            .map(|i| i.name.as_str())
+            .chain(description.nodes.iter().map(|n| n.name.as_str()))
+    }
+}
+
+/// Reads commands from standard input until `quit` or the end of input.
+pub fn run_interactive() -> CliResult {
+    println!("LuminAIR REPL, type 'help' for the commands.");
+    let mut repl = Repl::default();
+    let mut stdin = std::io::stdin().lock();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 || matches!(line.trim(), "quit" | "exit") {
+            return Ok(());
+        }
+        match repl.eval(&line) {
+            Ok(out) => print!("{}", out),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+}
+
+fn outputs(arrays: &BTreeMap<String, NpyArray>) -> EvalResult {
+    let mut out = String::new();
+    for (name, array) in arrays {
+        writeln!(out, "{} {:?}: {:?}", name, array.shape, array.data)?;
+    }
+    Ok(out)
+}
+
+fn dims(tensor: &GraphTensor) -> Vec<usize> {
+    tensor
+        .shape
+        .dims()
+        .iter()
+        .map(|d| d.to_usize().unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session() {
+        let mut repl = Repl::default();
+        assert_eq!(repl.eval("a = tensor 2x2 1 2 3 4").unwrap(), "a: [2, 2]\n");
+        assert_eq!(repl.eval("b = add a a").unwrap(), "b: [2, 2]\n");
+        assert_eq!(repl.eval("c = sum_reduce b 1").unwrap(), "c: [2]\n");
+        assert_eq!(
+            repl.list(),
+            "a = tensor [2, 2]\nb = add a a\nc = sum_reduce b 1\n"
+        );
+
+        assert!(repl.eval("run").unwrap().contains("c [2]: [6.0, 14.0]"));
+        assert!(repl.eval("prove").unwrap().ends_with("bytes.\n"));
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let mut repl = Repl::default();
+        assert!(repl.eval("run").is_err());
+        assert!(repl.eval("a = tensor 2x2 1 2").is_err());
+        repl.eval("a = tensor 2 1 2").unwrap();
+        assert!(repl.eval("a = sin a").is_err());
+        assert!(repl.eval("b = add a x").is_err());
+        assert!(repl.eval("b = unknown a").is_err());
+        assert_eq!(repl.list(), "a = tensor [2]\n");
+    }
+}
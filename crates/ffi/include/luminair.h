@@ -0,0 +1,93 @@
+/*
+ * C API of LuminAIR: compile, run, prove and verify models given as JSON model
+ * descriptions (see `luminair_graph::description`).
+ *
+ * Every function returns a LuminairStatus. On failure, luminair_last_error()
+ * describes the error. Handles are not thread-safe: use each one from a single
+ * thread at a time.
+ */
+#ifndef LUMINAIR_H
+#define LUMINAIR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum LuminairStatus {
+    LUMINAIR_OK = 0,
+    /* A pointer is null or a string is not valid UTF-8. */
+    LUMINAIR_INVALID_ARGUMENT = 1,
+    /* The model description can't be parsed or built. */
+    LUMINAIR_INVALID_MODEL = 2,
+    /* An input is unknown, has the wrong length or is missing. */
+    LUMINAIR_INVALID_INPUT = 3,
+    /* luminair.toml is invalid or the model exceeds its limits. */
+    LUMINAIR_CONFIG_ERROR = 4,
+    /* Execution or trace generation failed. */
+    LUMINAIR_EXECUTION_ERROR = 5,
+    LUMINAIR_PROVING_ERROR = 6,
+    /* The proof is invalid. */
+    LUMINAIR_VERIFICATION_FAILED = 7,
+    /* A proof or settings can't be encoded or decoded. */
+    LUMINAIR_SERIALIZATION_ERROR = 8,
+    /* LuminAIR panicked; the handles used by the call must not be used again. */
+    LUMINAIR_PANIC = 9,
+} LuminairStatus;
+
+/* A model, its inputs and the outputs of its last execution. */
+typedef struct LuminairModel LuminairModel;
+
+/* A serialized proof and the settings needed to verify it. */
+typedef struct LuminairProof LuminairProof;
+
+/* Parses and checks a JSON model description. Free with luminair_model_free. */
+LuminairStatus luminair_compile(const char *model_json, LuminairModel **out_model);
+
+/* Sets the values of an input, in row-major order. Values are kept across runs. */
+LuminairStatus luminair_set_input(LuminairModel *model, const char *name,
+                                  const float *data, size_t len);
+
+/* Executes the model on its inputs, without proving it. */
+LuminairStatus luminair_run(LuminairModel *model);
+
+/* Executes and proves the model. Free the proof with luminair_proof_free. */
+LuminairStatus luminair_prove(LuminairModel *model, LuminairProof **out_proof);
+
+/* Points to the values of an output of the last execution, owned by the model
+ * and valid until it is executed again or freed. */
+LuminairStatus luminair_get_output(const LuminairModel *model, const char *name,
+                                   const float **data, size_t *len);
+
+/* Verifies a proof. Returns LUMINAIR_VERIFICATION_FAILED if it is invalid. */
+LuminairStatus luminair_verify(const LuminairProof *proof);
+
+/* Number of operators executed on the CPU, whose outputs the proof does not
+ * attest to. A valid proof only covers every output if it is 0. */
+LuminairStatus luminair_proof_unproven_ops(const LuminairProof *proof, size_t *count);
+
+/* Points to the serialized proof and settings, owned by the proof handle. */
+LuminairStatus luminair_proof_bytes(const LuminairProof *proof,
+                                    const uint8_t **proof_data, size_t *proof_len,
+                                    const uint8_t **settings_data, size_t *settings_len);
+
+/* Decodes a serialized proof and its settings. Free with luminair_proof_free. */
+LuminairStatus luminair_proof_from_bytes(const uint8_t *proof_data, size_t proof_len,
+                                         const uint8_t *settings_data, size_t settings_len,
+                                         LuminairProof **out_proof);
+
+/* Describes the last error of the calling thread, valid until the next failing
+ * call on the same thread. */
+const char *luminair_last_error(void);
+
+/* Free handles. NULL is ignored. */
+void luminair_model_free(LuminairModel *model);
+void luminair_proof_free(LuminairProof *proof);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LUMINAIR_H */
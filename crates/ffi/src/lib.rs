@@ -0,0 +1,558 @@
+//! C API for embedding LuminAIR in other languages.
+//!
+//! Models are given as JSON model descriptions and referred to by opaque handles.
+//! Every function returns a `LuminairStatus`; on failure, `luminair_last_error` describes
+//! the error. See `include/luminair.h` for the declarations.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use luminair_air::settings::CircuitSettings;
+use luminair_graph::{
+    config::ProjectConfig,
+    description::{Model, ModelDescription},
+    npy::NpyArray,
+};
+use luminair_prover::LuminairProof;
+use luminair_utils::LuminairError;
+use luminair_verifier::verifier::verify;
+use luminal::prelude::*;
+use stwo_prover::core::vcs::blake2_merkle::Blake2sMerkleHasher;
+
+/// Result of a C API call.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LuminairStatus {
+    Ok = 0,
+    /// A pointer is null or a string is not valid UTF-8.
+    InvalidArgument = 1,
+    /// The model description can't be parsed or built.
+    InvalidModel = 2,
+    /// An input is unknown, has the wrong length or is missing.
+    InvalidInput = 3,
+    /// `luminair.toml` is invalid or the model exceeds its limits.
+    ConfigError = 4,
+    /// Execution or trace generation failed.
+    ExecutionError = 5,
+    ProvingError = 6,
+    /// The proof is invalid.
+    VerificationFailed = 7,
+    /// A proof or settings can't be encoded or decoded.
+    SerializationError = 8,
+    /// LuminAIR panicked; the handles used by the call must not be used again.
+    Panic = 9,
+}
+
+/// A model, its inputs and the outputs of its last execution.
+pub struct ModelHandle {
+    description: ModelDescription,
+    inputs: BTreeMap<String, NpyArray>,
+    outputs: BTreeMap<String, NpyArray>,
+    config: ProjectConfig,
+}
+
+/// A serialized proof and the settings needed to verify it.
+pub struct ProofHandle {
+    proof: Vec<u8>,
+    settings: Vec<u8>,
+}
+
+struct Error {
+    status: LuminairStatus,
+    message: String,
+}
+
+type FfiResult<T = ()> = Result<T, Error>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Wraps a `LuminairError` in the status of the stage it happened in.
+fn fail(status: LuminairStatus) -> impl FnOnce(LuminairError) -> Error {
+    move |e| Error {
+        status,
+        message: e.to_string(),
+    }
+}
+
+fn invalid_argument(message: &str) -> Error {
+    Error {
+        status: LuminairStatus::InvalidArgument,
+        message: message.to_string(),
+    }
+}
+
+/// Runs an API call, recording its error and turning panics into `LuminairStatus::Panic`.
+fn call(f: impl FnOnce() -> FfiResult) -> LuminairStatus {
+    let error = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return LuminairStatus::Ok,
+        Ok(Err(e)) => e,
+        Err(panic) => Error {
+            status: LuminairStatus::Panic,
+            message: panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "LuminAIR panicked".to_string()),
+        },
+    };
+    let message = CString::new(error.message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    error.status
+}
+
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> FfiResult<&'a str> {
+    if s.is_null() {
+        return Err(invalid_argument(&format!("{} is null", name)));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| invalid_argument(&format!("{} is not valid UTF-8", name)))
+}
+
+unsafe fn slice_arg<'a, T>(data: *const T, len: usize, name: &str) -> FfiResult<&'a [T]> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(invalid_argument(&format!("{} is null", name)));
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+unsafe fn ref_arg<'a, T>(handle: *const T, name: &str) -> FfiResult<&'a T> {
+    handle
+        .as_ref()
+        .ok_or_else(|| invalid_argument(&format!("{} is null", name)))
+}
+
+unsafe fn mut_arg<'a, T>(handle: *mut T, name: &str) -> FfiResult<&'a mut T> {
+    handle
+        .as_mut()
+        .ok_or_else(|| invalid_argument(&format!("{} is null", name)))
+}
+
+unsafe fn out_arg<T>(out: *mut T, value: T) -> FfiResult {
+    if out.is_null() {
+        return Err(invalid_argument("Output pointer is null"));
+    }
+    out.write(value);
+    Ok(())
+}
+
+impl ModelHandle {
+    /// Builds and compiles the model with its inputs bound.
+    fn compile(&self) -> FfiResult<(Graph, Model)> {
+        let mut cx = Graph::new();
+        let mut model = self
+            .description
+            .build(&mut cx)
+            .map_err(fail(LuminairStatus::InvalidModel))?;
+        model
+            .bind_inputs(&self.description, &self.inputs, None)
+            .map_err(fail(LuminairStatus::InvalidInput))?;
+        model.compile(&mut cx, &self.config);
+        model
+            .check_cost(&cx, &self.config)
+            .map_err(fail(LuminairStatus::ConfigError))?;
+        Ok((cx, model))
+    }
+}
+
+/// Parses and checks a JSON model description, returning a model handle in `out_model`.
+///
+/// The project configuration is loaded once, here. Free the handle with `luminair_model_free`.
+///
+/// # Safety
+///
+/// `model_json` must be a null-terminated string and `out_model` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_compile(
+    model_json: *const c_char,
+    out_model: *mut *mut ModelHandle,
+) -> LuminairStatus {
+    call(|| {
+        let json = str_arg(model_json, "model_json")?;
+        let description =
+            ModelDescription::from_json(json).map_err(fail(LuminairStatus::InvalidModel))?;
+        description
+            .build(&mut Graph::new())
+            .map_err(fail(LuminairStatus::InvalidModel))?;
+        let config = ProjectConfig::load().map_err(fail(LuminairStatus::ConfigError))?;
+
+        let handle = Box::new(ModelHandle {
+            description,
+            inputs: BTreeMap::new(),
+            outputs: BTreeMap::new(),
+            config,
+        });
+        out_arg(out_model, Box::into_raw(handle))
+    })
+}
+
+/// Sets the values of an input, in row-major order.
+///
+/// Inputs given values by the description can't be set. Values are kept across runs.
+///
+/// # Safety
+///
+/// `model` must be a handle from `luminair_compile`, `name` a null-terminated string and
+/// `data` point to `len` floats.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_set_input(
+    model: *mut ModelHandle,
+    name: *const c_char,
+    data: *const f32,
+    len: usize,
+) -> LuminairStatus {
+    call(|| {
+        let model = mut_arg(model, "model")?;
+        let name = str_arg(name, "name")?;
+        let data = slice_arg(data, len, "data")?;
+
+        let spec = model
+            .description
+            .inputs
+            .iter()
+            .find(|s| s.name == name && s.data.is_none())
+            .ok_or_else(|| Error {
+                status: LuminairStatus::InvalidInput,
+                message: format!("'{}' is not an input to set", name),
+            })?;
+        let array = NpyArray::new(spec.shape.clone(), data.to_vec())
+            .map_err(fail(LuminairStatus::InvalidInput))?;
+        model.inputs.insert(name.to_string(), array);
+        Ok(())
+    })
+}
+
+/// Executes the model on its inputs, without proving it.
+///
+/// Read the results with `luminair_get_output`.
+///
+/// # Safety
+///
+/// `model` must be a handle from `luminair_compile`.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_run(model: *mut ModelHandle) -> LuminairStatus {
+    call(|| {
+        let model = mut_arg(model, "model")?;
+        let (mut cx, compiled) = model.compile()?;
+        model.outputs = compiled
+            .run(&mut cx)
+            .map_err(fail(LuminairStatus::ExecutionError))?;
+        Ok(())
+    })
+}
+
+/// Executes and proves the model, returning a proof handle in `out_proof`.
+///
+/// The outputs are available with `luminair_get_output`, as after `luminair_run`.
+/// Free the proof with `luminair_proof_free`.
+///
+/// # Safety
+///
+/// `model` must be a handle from `luminair_compile` and `out_proof` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_prove(
+    model: *mut ModelHandle,
+    out_proof: *mut *mut ProofHandle,
+) -> LuminairStatus {
+    call(|| {
+        let model = mut_arg(model, "model")?;
+        let (mut cx, compiled) = model.compile()?;
+
+        let trace = compiled
+            .trace(&mut cx, None)
+            .map_err(fail(LuminairStatus::ExecutionError))?;
+        let proven = trace
+            .prove(None)
+            .map_err(fail(LuminairStatus::ProvingError))?;
+        let (proof, settings) = proven
+            .to_bincode()
+            .map_err(fail(LuminairStatus::SerializationError))?;
+        model.outputs = proven.outputs;
+
+        let handle = Box::new(ProofHandle { proof, settings });
+        out_arg(out_proof, Box::into_raw(handle))
+    })
+}
+
+/// Points `data` and `len` to the values of an output of the last execution.
+///
+/// The values are owned by the model, and valid until it is executed again or freed.
+///
+/// # Safety
+///
+/// `model` must be a handle from `luminair_compile`, `name` a null-terminated string, and
+/// `data` and `len` valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_get_output(
+    model: *const ModelHandle,
+    name: *const c_char,
+    data: *mut *const f32,
+    len: *mut usize,
+) -> LuminairStatus {
+    call(|| {
+        let model = ref_arg(model, "model")?;
+        let name = str_arg(name, "name")?;
+        let array = model.outputs.get(name).ok_or_else(|| Error {
+            status: LuminairStatus::InvalidInput,
+            message: format!("No output '{}', run the model first", name),
+        })?;
+        out_arg(data, array.data.as_ptr())?;
+        out_arg(len, array.data.len())
+    })
+}
+
+/// Verifies a proof against the settings it was generated with.
+///
+/// Returns `LUMINAIR_VERIFICATION_FAILED` if the proof is invalid.
+///
+/// # Safety
+///
+/// `proof` must be a handle from `luminair_prove` or `luminair_proof_from_bytes`.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_verify(proof: *const ProofHandle) -> LuminairStatus {
+    call(|| {
+        let handle = ref_arg(proof, "proof")?;
+        let proof = LuminairProof::<Blake2sMerkleHasher>::from_bincode(&handle.proof)
+            .map_err(fail(LuminairStatus::SerializationError))?;
+        let settings = CircuitSettings::from_bincode(&handle.settings)
+            .map_err(fail(LuminairStatus::SerializationError))?;
+        verify(proof, settings).map_err(fail(LuminairStatus::VerificationFailed))
+    })
+}
+
+/// Sets `count` to the number of operators executed on the CPU, whose outputs the proof
+/// does not attest to. A valid proof only covers every output if it is 0.
+///
+/// # Safety
+///
+/// `proof` must be a handle from `luminair_prove` or `luminair_proof_from_bytes`, and `count`
+/// a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_proof_unproven_ops(
+    proof: *const ProofHandle,
+    count: *mut usize,
+) -> LuminairStatus {
+    call(|| {
+        let handle = ref_arg(proof, "proof")?;
+        let proof = LuminairProof::<Blake2sMerkleHasher>::from_bincode(&handle.proof)
+            .map_err(fail(LuminairStatus::SerializationError))?;
+        out_arg(count, proof.claim.unproven_ops.len())
+    })
+}
+
+/// Points to the serialized proof and settings, to send or store them.
+///
+/// Both use the format of the Rust crates and the command line. The bytes are owned by the
+/// proof handle and valid until it is freed.
+///
+/// # Safety
+///
+/// `proof` must be a proof handle and the other arguments valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_proof_bytes(
+    proof: *const ProofHandle,
+    proof_data: *mut *const u8,
+    proof_len: *mut usize,
+    settings_data: *mut *const u8,
+    settings_len: *mut usize,
+) -> LuminairStatus {
+    call(|| {
+        let handle = ref_arg(proof, "proof")?;
+        out_arg(proof_data, handle.proof.as_ptr())?;
+        out_arg(proof_len, handle.proof.len())?;
+        out_arg(settings_data, handle.settings.as_ptr())?;
+        out_arg(settings_len, handle.settings.len())
+    })
+}
+
+/// Decodes a serialized proof and its settings, returning a proof handle in `out_proof`.
+///
+/// # Safety
+///
+/// `proof_data` and `settings_data` must point to `proof_len` and `settings_len` bytes, and
+/// `out_proof` be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_proof_from_bytes(
+    proof_data: *const u8,
+    proof_len: usize,
+    settings_data: *const u8,
+    settings_len: usize,
+    out_proof: *mut *mut ProofHandle,
+) -> LuminairStatus {
+    call(|| {
+        let proof = slice_arg(proof_data, proof_len, "proof_data")?;
+        let settings = slice_arg(settings_data, settings_len, "settings_data")?;
+        LuminairProof::<Blake2sMerkleHasher>::from_bincode(proof)
+            .map_err(fail(LuminairStatus::SerializationError))?;
+        CircuitSettings::from_bincode(settings)
+            .map_err(fail(LuminairStatus::SerializationError))?;
+
+        let handle = Box::new(ProofHandle {
+            proof: proof.to_vec(),
+            settings: settings.to_vec(),
+        });
+        out_arg(out_proof, Box::into_raw(handle))
+    })
+}
+
+/// Describes the last error of the calling thread.
+///
+/// The string is valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn luminair_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Frees a model handle. Null is ignored.
+///
+/// # Safety
+///
+/// `model` must be null or a handle from `luminair_compile`, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_model_free(model: *mut ModelHandle) {
+    if !model.is_null() {
+        drop(Box::from_raw(model));
+    }
+}
+
+/// Frees a proof handle. Null is ignored.
+///
+/// # Safety
+///
+/// `proof` must be null or a proof handle, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn luminair_proof_free(proof: *mut ProofHandle) {
+    if !proof.is_null() {
+        drop(Box::from_raw(proof));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &CStr = cr#"{
+        "inputs": [{ "name": "a", "shape": [2, 2] }],
+        "nodes": [
+            { "name": "b", "op": "mul", "inputs": ["a", "a"] },
+            { "name": "c", "op": "sum_reduce", "inputs": ["b"], "axes": [1] }
+        ],
+        "outputs": ["c"]
+    }"#;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(luminair_last_error()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        unsafe {
+            let mut model = ptr::null_mut();
+            assert_eq!(
+                luminair_compile(MODEL.as_ptr(), &mut model),
+                LuminairStatus::Ok
+            );
+            let input = [1.0f32, 2.0, 3.0, 4.0];
+            assert_eq!(
+                luminair_set_input(model, c"a".as_ptr(), input.as_ptr(), input.len()),
+                LuminairStatus::Ok
+            );
+
+            let mut proof = ptr::null_mut();
+            assert_eq!(luminair_prove(model, &mut proof), LuminairStatus::Ok);
+            let (mut data, mut len) = (ptr::null(), 0);
+            assert_eq!(
+                luminair_get_output(model, c"c".as_ptr(), &mut data, &mut len),
+                LuminairStatus::Ok
+            );
+            assert_eq!(slice::from_raw_parts(data, len), [5.0, 25.0]);
+
+            // Round trip through bytes, as when sending the proof to another service
+            let (mut proof_data, mut proof_len) = (ptr::null(), 0);
+            let (mut settings_data, mut settings_len) = (ptr::null(), 0);
+            assert_eq!(
+                luminair_proof_bytes(
+                    proof,
+                    &mut proof_data,
+                    &mut proof_len,
+                    &mut settings_data,
+                    &mut settings_len
+                ),
+                LuminairStatus::Ok
+            );
+            let mut decoded = ptr::null_mut();
+            assert_eq!(
+                luminair_proof_from_bytes(
+                    proof_data,
+                    proof_len,
+                    settings_data,
+                    settings_len,
+                    &mut decoded
+                ),
+                LuminairStatus::Ok
+            );
+            assert_eq!(luminair_verify(decoded), LuminairStatus::Ok);
+            let mut unproven = usize::MAX;
+            assert_eq!(
+                luminair_proof_unproven_ops(decoded, &mut unproven),
+                LuminairStatus::Ok
+            );
+            assert_eq!(unproven, 0);
+
+            luminair_proof_free(decoded);
+            luminair_proof_free(proof);
+            luminair_model_free(model);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        unsafe {
+            let mut model = ptr::null_mut();
+            assert_eq!(
+                luminair_compile(c"{}}".as_ptr(), &mut model),
+                LuminairStatus::InvalidModel
+            );
+            assert_eq!(
+                luminair_compile(ptr::null(), &mut model),
+                LuminairStatus::InvalidArgument
+            );
+            assert_eq!(last_error(), "model_json is null");
+
+            assert_eq!(
+                luminair_compile(MODEL.as_ptr(), &mut model),
+                LuminairStatus::Ok
+            );
+            assert_eq!(luminair_run(model), LuminairStatus::InvalidInput);
+            assert!(last_error().contains("No data for input 'a'"));
+            let input = [1.0f32, 2.0];
+            assert_eq!(
+                luminair_set_input(model, c"a".as_ptr(), input.as_ptr(), input.len()),
+                LuminairStatus::InvalidInput
+            );
+            assert_eq!(
+                luminair_set_input(model, c"b".as_ptr(), input.as_ptr(), input.len()),
+                LuminairStatus::InvalidInput
+            );
+
+            let mut proof = ptr::null_mut();
+            assert_eq!(
+                luminair_proof_from_bytes(input.as_ptr().cast(), 8, ptr::null(), 0, &mut proof),
+                LuminairStatus::SerializationError
+            );
+            luminair_model_free(model);
+        }
+    }
+}
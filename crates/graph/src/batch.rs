@@ -0,0 +1,64 @@
+use luminair_air::{pie::LuminairPie, settings::CircuitSettings};
+use luminair_utils::LuminairError;
+use luminal::prelude::*;
+
+use crate::graph::LuminairGraph;
+
+/// Trace and outputs of one sample of a batch.
+#[derive(Debug)]
+pub struct BatchRun {
+    /// Circuit settings of the run, to prove and verify it with.
+    pub settings: CircuitSettings,
+    /// Execution trace of the run.
+    pub pie: LuminairPie,
+    /// Data of each output, in the order they were requested.
+    pub outputs: Vec<Vec<f32>>,
+}
+
+/// Generates the trace of a compiled graph for each sample, setting `inputs` to the
+/// sample's tensors and reading back `outputs`.
+///
+/// The graph is compiled once and reused across the batch, along with everything not
+/// listed in `inputs`, such as weights preconverted by `WeightsCompiler`. `inputs` and
+/// `outputs` should be remapped by `compile` so that they follow graph rewrites.
+pub fn run_batch(
+    graph: &mut Graph,
+    inputs: &[GraphTensor],
+    samples: Vec<Vec<Vec<f32>>>,
+    outputs: &[GraphTensor],
+) -> Result<Vec<BatchRun>, LuminairError> {
+    let mut runs = Vec::with_capacity(samples.len());
+    for (i, sample) in samples.into_iter().enumerate() {
+        if sample.len() != inputs.len() {
+            return Err(LuminairError::InvalidGraph(format!(
+                "sample {} has {} tensors, expected {}",
+                i,
+                sample.len(),
+                inputs.len()
+            )));
+        }
+        for (input, data) in inputs.iter().zip(sample) {
+            input.set(data);
+        }
+
+        // Outputs of the previous run would be kept, and their nodes skipped
+        clear_outputs(graph, outputs);
+        let mut settings = graph.gen_circuit_settings();
+        clear_outputs(graph, outputs);
+        let pie = graph.gen_trace(&mut settings)?;
+
+        runs.push(BatchRun {
+            settings,
+            pie,
+            outputs: outputs.iter().map(|o| o.data()).collect(),
+        });
+    }
+    Ok(runs)
+}
+
+/// Drops the data of `outputs`, so that the next execution computes them again.
+pub(crate) fn clear_outputs(graph: &mut Graph, outputs: &[GraphTensor]) {
+    for output in outputs {
+        graph.tensors.remove(&(output.id, 0));
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::{Duration, Instant};
+
+use luminair_prover::prover::prove;
+use luminair_utils::LuminairError;
+use luminair_verifier::verifier::verify;
+use luminal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{cost::CostEstimate, graph::LuminairGraph};
+
+/// Mean timings and predicted cost of a benchmarked graph.
+///
+/// Generated by `bench_graph`, and serializable with `to_json` for tracking
+/// results across changes.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BenchResult {
+    /// Name of the benchmark.
+    pub name: String,
+    /// Number of measured runs.
+    pub iterations: usize,
+    /// Mean trace generation time, in milliseconds.
+    pub trace_generation_ms: f64,
+    /// Mean proving time, in milliseconds.
+    pub proving_ms: f64,
+    /// Mean verification time, in milliseconds.
+    pub verification_ms: f64,
+    /// Trace rows of each operator and component.
+    pub cost: CostEstimate,
+    /// Mean execution time of each node during trace generation, in execution order.
+    #[serde(default)]
+    pub nodes: Vec<NodeBench>,
+}
+
+/// Mean time spent executing a node and filling its trace rows.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NodeBench {
+    /// The unique ID of the node.
+    pub node: u32,
+    /// Description of the operator.
+    pub op: String,
+    /// Mean duration, in milliseconds.
+    pub mean_ms: f64,
+}
+
+impl BenchResult {
+    pub fn to_json(&self) -> Result<String, LuminairError> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            LuminairError::SerializationError(format!(
+                "Failed to serialize benchmark to JSON: {}",
+                e
+            ))
+        })
+    }
+}
+
+/// Generates the trace of, proves and verifies the graph returned by `build`, `iterations` times.
+///
+/// Also times each node during trace generation, from `ExecutionResources::node_timings`.
+///
+/// `build` must return a compiled graph with its inputs set. It is called once per run
+/// because trace generation consumes the graph's tensors, and is not timed.
+pub fn bench_graph(
+    name: &str,
+    iterations: usize,
+    mut build: impl FnMut() -> Graph,
+) -> Result<BenchResult, LuminairError> {
+    let (mut trace, mut proving, mut verification) =
+        (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+    let mut cost = CostEstimate::default();
+    let mut nodes: Vec<(NodeBench, Duration)> = Vec::new();
+
+    for _ in 0..iterations {
+        let mut graph = build();
+        let mut settings = graph.gen_circuit_settings();
+        cost = graph.estimate_cost();
+
+        let start = Instant::now();
+        let pie = graph.gen_trace(&mut settings)?;
+        trace += start.elapsed();
+        for timing in &pie.execution_resources.node_timings {
+            let duration = Duration::from_micros(timing.duration_us);
+            match nodes.iter_mut().find(|(n, _)| n.node == timing.node) {
+                Some((_, total)) => *total += duration,
+                None => nodes.push((
+                    NodeBench {
+                        node: timing.node,
+                        op: timing.op.clone(),
+                        mean_ms: 0.0,
+                    },
+                    duration,
+                )),
+            }
+        }
+
+        let start = Instant::now();
+        let proof = prove(pie, settings.clone())?;
+        proving += start.elapsed();
+
+        let start = Instant::now();
+        verify(proof, settings)?;
+        verification += start.elapsed();
+    }
+
+    let mean_ms = |total: Duration| total.as_secs_f64() * 1e3 / iterations.max(1) as f64;
+    Ok(BenchResult {
+        name: name.to_string(),
+        iterations,
+        trace_generation_ms: mean_ms(trace),
+        proving_ms: mean_ms(proving),
+        verification_ms: mean_ms(verification),
+        cost,
+        nodes: nodes
+            .into_iter()
+            .map(|(node, total)| NodeBench {
+                mean_ms: mean_ms(total),
+                ..node
+            })
+            .collect(),
+    })
+}
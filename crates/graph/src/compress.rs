@@ -0,0 +1,215 @@
+use luminair_utils::LuminairError;
+use luminal::prelude::*;
+
+/// Run-length compressed `f32` tensor data that can be fed to a LuminAIR graph.
+///
+/// The encoding is a sequence of `(zero_run, literal_count, literals...)` groups,
+/// where both counts are LEB128 varints and literals are little-endian `f32`s.
+/// Weights with long runs of zeros (pruned layers, padding) shrink accordingly.
+/// The tensor is decompressed when it enters the circuit (in `CopyToStwo`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressedData {
+    /// Number of elements of the decompressed tensor.
+    pub len: usize,
+    /// Encoded bytes.
+    pub bytes: Vec<u8>,
+}
+
+impl CompressedData {
+    /// Compresses `data`.
+    pub fn encode(data: &[f32]) -> Self {
+        let mut bytes = Vec::new();
+        let mut i = 0;
+
+        while i < data.len() {
+            let zeros_start = i;
+            while i < data.len() && data[i] == 0.0 {
+                i += 1;
+            }
+            let literals_start = i;
+            while i < data.len() && data[i] != 0.0 {
+                i += 1;
+            }
+
+            write_varint(&mut bytes, literals_start - zeros_start);
+            write_varint(&mut bytes, i - literals_start);
+            for v in &data[literals_start..i] {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+
+        Self {
+            len: data.len(),
+            bytes,
+        }
+    }
+
+    /// Returns the number of elements of the decompressed tensor.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the decompressed tensor has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decompresses the tensor into a `Vec<f32>`.
+    ///
+    /// Fails if the encoded bytes are malformed or don't decode to `len` elements.
+    pub fn decode(&self) -> Result<Vec<f32>, LuminairError> {
+        let mut data = Vec::with_capacity(self.len);
+        self.walk(|zeros, literals| {
+            data.resize(data.len() + zeros, 0.0);
+            data.extend(
+                literals
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap())),
+            );
+        })?;
+        Ok(data)
+    }
+
+    /// Checks the encoded bytes decode to `len` elements, without decompressing them.
+    pub fn validate(&self) -> Result<(), LuminairError> {
+        self.walk(|_, _| {})
+    }
+
+    /// Calls `group` with the zero run and literal bytes of each group, in order.
+    ///
+    /// Counts are checked against `len` before `group` is called, so a malformed
+    /// tensor can't make decoding allocate more than `len` elements.
+    fn walk(&self, mut group: impl FnMut(usize, &[u8])) -> Result<(), LuminairError> {
+        let mut decoded = 0usize;
+        let mut pos = 0;
+
+        while pos < self.bytes.len() {
+            let zeros = read_varint(&self.bytes, &mut pos)?;
+            let literals = read_varint(&self.bytes, &mut pos)?;
+            decoded = decoded
+                .checked_add(zeros)
+                .and_then(|n| n.checked_add(literals))
+                .filter(|&n| n <= self.len)
+                .ok_or_else(|| malformed("more elements than its length"))?;
+            let bytes = literals
+                .checked_mul(4)
+                .and_then(|n| self.bytes.get(pos..pos.checked_add(n)?))
+                .ok_or_else(|| malformed("truncated literals"))?;
+            pos += bytes.len();
+            group(zeros, bytes);
+        }
+
+        if decoded != self.len {
+            return Err(malformed("fewer elements than its length"));
+        }
+        Ok(())
+    }
+}
+
+impl Data for CompressedData {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Appends `value` as an unsigned LEB128 varint.
+fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Reads an unsigned LEB128 varint starting at `pos`, advancing it.
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<usize, LuminairError> {
+    let mut value = 0usize;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| malformed("truncated varint"))?;
+        *pos += 1;
+        let bits = (byte & 0x7f) as usize;
+        if shift >= usize::BITS || (bits << shift) >> shift != bits {
+            return Err(malformed("varint overflows usize"));
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+/// Returns the error reported for a malformed compressed tensor.
+fn malformed(reason: &str) -> LuminairError {
+    LuminairError::InvalidTensorData(format!("Compressed tensor has {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let data = [0.0, 0.0, 1.5, -2.0, 0.0, 3.25, 0.0, 0.0];
+        let compressed = CompressedData::encode(&data);
+
+        assert_eq!(compressed.len(), data.len());
+        assert_eq!(compressed.decode().unwrap(), data);
+    }
+
+    #[test]
+    fn test_long_zero_runs() {
+        let mut data = vec![0.0; 10_000];
+        data[5_000] = 1.0;
+        let compressed = CompressedData::encode(&data);
+
+        assert!(compressed.bytes.len() < 16);
+        assert_eq!(compressed.decode().unwrap(), data);
+    }
+
+    #[test]
+    fn test_empty() {
+        let compressed = CompressedData::encode(&[]);
+        assert!(compressed.is_empty());
+        assert!(compressed.decode().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed() {
+        let truncated = CompressedData {
+            len: 2,
+            bytes: vec![0, 2, 0, 0, 0x80],
+        };
+        assert!(truncated.decode().is_err());
+
+        let overlong = CompressedData {
+            len: 1,
+            bytes: [vec![0xff; 10], vec![0x7f, 0]].concat(),
+        };
+        assert!(overlong.validate().is_err());
+
+        let too_long = CompressedData {
+            len: 4,
+            bytes: vec![0x80, 0x80, 0x80, 0x01, 0],
+        };
+        assert!(matches!(
+            too_long.decode(),
+            Err(LuminairError::InvalidTensorData(_))
+        ));
+
+        let too_short = CompressedData::encode(&[1.0, 0.0]);
+        assert!(CompressedData {
+            len: 3,
+            ..too_short
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+
+use luminair_air::DEFAULT_FP_SCALE;
+use luminair_utils::LuminairError;
+use serde::{Deserialize, Serialize};
+
+use crate::cost::CostEstimate;
+
+/// Name of the project configuration file.
+pub const CONFIG_FILE: &str = "luminair.toml";
+
+/// Environment variable pointing to a configuration file, overriding the lookup.
+pub const CONFIG_ENV: &str = "LUMINAIR_CONFIG";
+
+/// AIR components whose operators can be kept on the CPU with `ops.cpu`.
+pub const CONFIGURABLE_OPS: [&str; 7] = [
+    "add",
+    "mul",
+    "recip",
+    "sin",
+    "sum_reduce",
+    "max_reduce",
+    "sqrt",
+];
+
+/// Project-level configuration, read from `luminair.toml`.
+///
+/// ```toml
+/// [precision]
+/// fp_scale = 12           # fails if this binary was built with another scale
+///
+/// [limits]
+/// max_log_size = 20       # largest trace to prove, as a log2 number of rows
+///
+/// [ops]
+/// cpu = ["sin"]           # components whose operators are executed unproven
+/// cpu_fallback = true     # run operators without an AIR component unproven, instead of failing
+/// ```
+///
+/// Operators and their AIR components are compiled into the binary and proven with
+/// Stwo, so there are no artifact paths or backends to configure. Unknown keys are
+/// rejected rather than silently ignored.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub precision: PrecisionConfig,
+    pub limits: LimitsConfig,
+    pub ops: OpsConfig,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrecisionConfig {
+    /// Expected number of fractional bits of the fixed-point representation.
+    pub fp_scale: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Largest component trace allowed, as a log2 number of rows.
+    pub max_log_size: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct OpsConfig {
+    /// Components, from `CONFIGURABLE_OPS`, whose operators are left on the CPU.
+    pub cpu: Vec<String>,
+    /// Whether operators without an AIR component (e.g. `relu`, `exp`) run unproven on the
+    /// CPU. Off by default: traces of graphs using them fail with `UnsupportedOps`.
+    pub cpu_fallback: bool,
+}
+
+impl ProjectConfig {
+    /// Loads the project configuration.
+    ///
+    /// Reads the file named by `LUMINAIR_CONFIG` if set, or else the first `luminair.toml`
+    /// found in the current directory and its ancestors. Returns the default
+    /// configuration if there is none.
+    pub fn load() -> Result<Self, LuminairError> {
+        match Self::find() {
+            Some(path) => Self::from_file(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Path of the configuration file `load` reads, if any.
+    pub fn find() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV) {
+            return Some(path.into());
+        }
+        let cwd = std::env::current_dir().ok()?;
+        cwd.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, LuminairError> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path).map_err(|e| {
+            LuminairError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Self::from_toml(&toml)
+            .map_err(|e| LuminairError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parses and validates a configuration.
+    pub fn from_toml(toml: &str) -> Result<Self, LuminairError> {
+        let config: Self = toml::from_str(toml)
+            .map_err(|e| LuminairError::ConfigError(format!("Invalid configuration: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the configuration applies to this build.
+    pub fn validate(&self) -> Result<(), LuminairError> {
+        if let Some(fp_scale) = self.precision.fp_scale {
+            if fp_scale != DEFAULT_FP_SCALE {
+                return Err(LuminairError::FixedPointScaleMismatch {
+                    expected: DEFAULT_FP_SCALE,
+                    found: fp_scale,
+                });
+            }
+        }
+        if let Some(op) = self
+            .ops
+            .cpu
+            .iter()
+            .find(|op| !CONFIGURABLE_OPS.contains(&op.as_str()))
+        {
+            return Err(LuminairError::ConfigError(format!(
+                "Unknown operator '{}' in ops.cpu, expected one of {}",
+                op,
+                CONFIGURABLE_OPS.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks a graph's predicted cost, from `LuminairGraph::estimate_cost`, is within the limits.
+    pub fn check_cost(&self, cost: &CostEstimate) -> Result<(), LuminairError> {
+        match self.limits.max_log_size {
+            Some(max) if cost.max_log_size() > max => Err(LuminairError::ConfigError(format!(
+                "Trace log size {} exceeds limits.max_log_size = {}",
+                cost.max_log_size(),
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether operators proven by `component` are kept on the CPU.
+    pub fn runs_on_cpu(&self, component: &str) -> bool {
+        self.ops.cpu.iter().any(|op| op == component)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let config = ProjectConfig::from_toml(
+            "[limits]\nmax_log_size = 4\n\n[ops]\ncpu = [\"sin\", \"sqrt\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.limits.max_log_size, Some(4));
+        assert!(config.runs_on_cpu("sin") && !config.runs_on_cpu("add"));
+        assert_eq!(
+            ProjectConfig::from_toml("").unwrap(),
+            ProjectConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(ProjectConfig::from_toml("[ops]\ncpu = [\"exp\"]\n").is_err());
+        assert!(ProjectConfig::from_toml("[artifacts]\npath = \"out\"\n").is_err());
+        assert!(matches!(
+            ProjectConfig::from_toml(&format!(
+                "[precision]\nfp_scale = {}\n",
+                DEFAULT_FP_SCALE + 1
+            )),
+            Err(LuminairError::FixedPointScaleMismatch { .. })
+        ));
+    }
+}
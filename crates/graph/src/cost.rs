@@ -0,0 +1,184 @@
+use luminair_air::{
+    components::{
+        add::table::{AddColumn, AddTraceTable},
+        lookups::sin::SinLookup,
+        max_reduce::table::{MaxReduceColumn, MaxReduceTraceTable},
+        mul::table::{MulColumn, MulTraceTable},
+        recip::table::{RecipColumn, RecipTraceTable},
+        sin::table::{SinColumn, SinTraceTable},
+        sqrt::table::{SqrtColumn, SqrtTraceTable},
+        sum_reduce::table::{SumReduceColumn, SumReduceTraceTable},
+        TraceColumn,
+    },
+    utils::calculate_log_size,
+};
+use luminal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::op::HasProcessTrace;
+
+/// Predicted trace rows of a single operator.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OpCost {
+    /// The unique ID of the node.
+    pub node: u32,
+    /// Name of the AIR component proving the operator.
+    pub component: String,
+    /// Number of trace rows the operator adds to its component.
+    pub rows: usize,
+}
+
+/// Predicted trace size of an AIR component.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ComponentCost {
+    /// Name of the AIR component.
+    pub component: String,
+    /// Total number of rows, across all operators proven by the component.
+    pub rows: usize,
+    /// Log2 size of the padded trace.
+    pub log_size: u32,
+    /// Number of main and interaction trace columns.
+    pub columns: (usize, usize),
+}
+
+impl ComponentCost {
+    /// Number of trace cells to commit to, padding included.
+    pub fn cells(&self) -> usize {
+        (1 << self.log_size) * (self.columns.0 + self.columns.1)
+    }
+}
+
+/// Proving cost of a graph, predicted from its shapes without executing it.
+///
+/// Generated by `LuminairGraph::estimate_cost`. The `sin_lookup` table is not included:
+/// its size is set by the range of values fed to `sin`, only known once the graph is
+/// executed by `gen_circuit_settings`, so graphs using `sin` may need a larger trace.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// Cost of each proven operator, in node order.
+    pub ops: Vec<OpCost>,
+    /// Cost of each AIR component used by the graph.
+    pub components: Vec<ComponentCost>,
+}
+
+impl CostEstimate {
+    /// Predicted maximum log2 trace size, as reported by `ExecutionResources::max_log_size`.
+    ///
+    /// A lower bound for graphs using `sin`, whose lookup table is not estimated.
+    pub fn max_log_size(&self) -> u32 {
+        self.components
+            .iter()
+            .map(|c| c.log_size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Total number of trace cells, a proxy for proving time.
+    pub fn total_cells(&self) -> usize {
+        self.components.iter().map(ComponentCost::cells).sum()
+    }
+}
+
+/// Returns the name and column counts of the AIR component proving `node`, if any.
+pub(crate) fn component_of(
+    graph: &Graph,
+    node: NodeIndex,
+) -> Option<(&'static str, (usize, usize))> {
+    let op = graph.graph.node_weight(node).unwrap();
+    if <Box<dyn Operator> as HasProcessTrace<
+        AddColumn,
+        AddTraceTable,
+        (),
+    >>::has_process_trace(op)
+    {
+        Some(("add", AddColumn::count()))
+    } else if <Box<dyn Operator> as HasProcessTrace<
+        MulColumn,
+        MulTraceTable,
+        (),
+    >>::has_process_trace(op)
+    {
+        Some(("mul", MulColumn::count()))
+    } else if <Box<dyn Operator> as HasProcessTrace<
+        RecipColumn,
+        RecipTraceTable,
+        (),
+    >>::has_process_trace(op)
+    {
+        Some(("recip", RecipColumn::count()))
+    } else if <Box<dyn Operator> as HasProcessTrace<
+        SinColumn,
+        SinTraceTable,
+        SinLookup,
+    >>::has_process_trace(op)
+    {
+        Some(("sin", SinColumn::count()))
+    } else if <Box<dyn Operator> as HasProcessTrace<
+        SumReduceColumn,
+        SumReduceTraceTable,
+        (),
+    >>::has_process_trace(op)
+    {
+        Some(("sum_reduce", SumReduceColumn::count()))
+    } else if <Box<dyn Operator> as HasProcessTrace<
+        MaxReduceColumn,
+        MaxReduceTraceTable,
+        (),
+    >>::has_process_trace(op)
+    {
+        Some(("max_reduce", MaxReduceColumn::count()))
+    } else if <Box<dyn Operator> as HasProcessTrace<
+        SqrtColumn,
+        SqrtTraceTable,
+        (),
+    >>::has_process_trace(op)
+    {
+        Some(("sqrt", SqrtColumn::count()))
+    } else {
+        None
+    }
+}
+
+/// Builds the cost estimate of a compiled graph.
+///
+/// Every AIR component adds one row per element of its operator's first input:
+/// element-wise ops produce one row per output and reductions one row per reduced element.
+/// Lookup tables are left out, see `CostEstimate`.
+pub(crate) fn estimate(graph: &Graph) -> CostEstimate {
+    let mut ops = Vec::new();
+    let mut components: Vec<ComponentCost> = Vec::new();
+
+    let mut nodes = graph.node_indices().collect::<Vec<_>>();
+    nodes.sort_unstable();
+    for node in nodes {
+        let Some((component, columns)) = component_of(graph, node) else {
+            continue;
+        };
+        let rows = graph
+            .get_sources(node)
+            .first()
+            .and_then(|(_, _, st)| st.n_elements().exec(&graph.dyn_map))
+            .unwrap_or(0);
+
+        ops.push(OpCost {
+            node: node.index() as u32,
+            component: component.to_string(),
+            rows,
+        });
+        match components.iter_mut().find(|c| c.component == component) {
+            Some(c) => c.rows += rows,
+            None => components.push(ComponentCost {
+                component: component.to_string(),
+                rows,
+                log_size: 0,
+                columns,
+            }),
+        }
+    }
+
+    for c in &mut components {
+        c.log_size = calculate_log_size(c.rows);
+    }
+
+    CostEstimate { ops, components }
+}
@@ -1,5 +1,16 @@
-use luminair_air::DEFAULT_FP_SCALE;
-use luminal::prelude::*;
+use crate::{
+    compress::CompressedData, op::prim::CopyFromStwo, quant::QuantizedData, sparse::SparseData,
+};
+use half::{bf16, f16};
+use luminair_air::{
+    fixed::{f32_to_fixed, fixed_to_f32, to_fixed, RoundingMode},
+    DEFAULT_FP_SCALE,
+};
+use luminair_utils::LuminairError;
+use luminal::{
+    op::{Function, LessThan},
+    prelude::*,
+};
 use num_traits::Zero;
 use numerair::Fixed;
 use std::sync::Arc;
@@ -13,17 +24,75 @@ pub(crate) struct StwoData(pub(crate) Arc<Vec<Fixed<DEFAULT_FP_SCALE>>>);
 impl StwoData {
     /// Creates a new `StwoData` instance by converting a slice of `f32` values to `Fixed` point.
     pub(crate) fn from_f32(data: &[f32]) -> Self {
+        Self::from_f32_rounded(data, RoundingMode::Nearest)
+    }
+
+    /// Creates a new `StwoData` instance from `f32` values with the given rounding mode.
+    pub(crate) fn from_f32_rounded(data: &[f32], mode: RoundingMode) -> Self {
+        StwoData(Arc::new(f32_to_fixed(data, mode)))
+    }
+
+    /// Creates a new `StwoData` instance from integer values.
+    ///
+    /// Integers are scaled by the fixed-point scale directly, so no precision is lost
+    /// going through floating point. Values beyond `MAX_FIXED_MAGNITUDE` saturate instead
+    /// of wrapping into range, so they are reported as a `FixedPointOverflow`.
+    pub(crate) fn from_integers(data: &IntegerData) -> Self {
+        let scale = |v: i64| Fixed(v.saturating_mul(1 << DEFAULT_FP_SCALE));
+        let fixed_data = match data {
+            IntegerData::I32(values) => values.iter().map(|&v| scale(v as i64)).collect::<Vec<_>>(),
+            IntegerData::I64(values) => values.iter().map(|&v| scale(v)).collect::<Vec<_>>(),
+        };
+
+        StwoData(Arc::new(fixed_data))
+    }
+
+    /// Creates a new `StwoData` instance from half-precision values.
+    pub(crate) fn from_half(data: &HalfData) -> Self {
+        let fixed_data = match data {
+            HalfData::F16(values) => values
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| to_fixed(v.to_f64(), RoundingMode::Nearest, i))
+                .collect::<Vec<_>>(),
+            HalfData::BF16(values) => values
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| to_fixed(v.to_f64(), RoundingMode::Nearest, i))
+                .collect::<Vec<_>>(),
+        };
+
+        StwoData(Arc::new(fixed_data))
+    }
+
+    /// Creates a new `StwoData` instance by dequantizing INT8 data to `Fixed` point.
+    pub(crate) fn from_quantized(data: &QuantizedData) -> Self {
         let fixed_data = data
+            .values
             .iter()
-            .map(|&d| Fixed::from_f64(d as f64))
+            .enumerate()
+            .map(|(i, &q)| to_fixed(q as f64 * data.scale_at(i) as f64, RoundingMode::Nearest, i))
             .collect::<Vec<_>>();
 
         StwoData(Arc::new(fixed_data))
     }
 
+    /// Creates a new `StwoData` instance by densifying sparse data.
+    ///
+    /// Only stored entries are converted; the rest stay at zero.
+    pub(crate) fn from_sparse(data: &SparseData) -> Result<Self, LuminairError> {
+        data.validate()?;
+        let mut fixed_data = vec![Fixed::zero(); data.len()];
+        for (i, v) in data.entries() {
+            fixed_data[i] = to_fixed(v as f64, RoundingMode::Nearest, i);
+        }
+
+        Ok(StwoData(Arc::new(fixed_data)))
+    }
+
     /// Converts the internal `Fixed` point data back to a vector of `f32` values.
     pub(crate) fn to_f32(&self) -> Vec<f32> {
-        self.0.iter().map(|&d| d.to_f64() as f32).collect()
+        fixed_to_f32(&self.0)
     }
 
     /// Finds the minimum and maximum `Fixed` point values within the data.
@@ -52,3 +121,315 @@ impl Data for StwoData {
         self
     }
 }
+
+/// Integer tensor data that can be fed to a LuminAIR graph.
+///
+/// Luminal only carries `Vec<f32>` for graph inputs; this container lets integer tensors
+/// (e.g. token IDs) reach `CopyToStwo` without going through floats first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntegerData {
+    /// 32-bit signed integers.
+    I32(Vec<i32>),
+    /// 64-bit signed integers.
+    I64(Vec<i64>),
+}
+
+impl IntegerData {
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        match self {
+            IntegerData::I32(values) => values.len(),
+            IntegerData::I64(values) => values.len(),
+        }
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Vec<i32>> for IntegerData {
+    fn from(values: Vec<i32>) -> Self {
+        IntegerData::I32(values)
+    }
+}
+
+impl From<Vec<i64>> for IntegerData {
+    fn from(values: Vec<i64>) -> Self {
+        IntegerData::I64(values)
+    }
+}
+
+impl Data for IntegerData {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Half-precision tensor data that can be fed to a LuminAIR graph.
+///
+/// Values are converted to the circuit's fixed-point representation by `CopyToStwo`,
+/// so half-precision weights don't have to be up-cast to `f32` on the host first.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HalfData {
+    /// IEEE 754 half-precision floats.
+    F16(Vec<f16>),
+    /// Brain floating point (bfloat16) values.
+    BF16(Vec<bf16>),
+}
+
+impl HalfData {
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        match self {
+            HalfData::F16(values) => values.len(),
+            HalfData::BF16(values) => values.len(),
+        }
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Vec<f16>> for HalfData {
+    fn from(values: Vec<f16>) -> Self {
+        HalfData::F16(values)
+    }
+}
+
+impl From<Vec<bf16>> for HalfData {
+    fn from(values: Vec<bf16>) -> Self {
+        HalfData::BF16(values)
+    }
+}
+
+impl Data for HalfData {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// A user-defined host data type that can be fed to and read back from a LuminAIR graph.
+///
+/// The circuit only operates on `Fixed` values, so a custom dtype is defined by its
+/// conversion rules to and from fixed point. Implementors are bound to input tensors
+/// with `SetTensorData::set_custom` and read back with `GetTensorData::data_custom`.
+pub trait CustomDtype: std::fmt::Debug + Send + Sync + 'static {
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Converts the values to fixed point, in row-major order.
+    fn to_fixed(&self) -> Vec<Fixed<DEFAULT_FP_SCALE>>;
+
+    /// Builds the dtype back from fixed-point values.
+    fn from_fixed(values: &[Fixed<DEFAULT_FP_SCALE>]) -> Self
+    where
+        Self: Sized;
+}
+
+/// Type-erased container for `CustomDtype` data, converted by `CopyToStwo`.
+#[derive(Clone, Debug)]
+pub struct CustomData(pub(crate) Arc<dyn CustomDtype>);
+
+impl Data for CustomData {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Extension trait to set non-`f32` data on graph input tensors.
+pub trait SetTensorData {
+    /// Sets the value of an input tensor from integer data.
+    fn set_integers(self, data: impl Into<IntegerData>) -> Self;
+
+    /// Sets the value of an input tensor from half-precision data.
+    fn set_half(self, data: impl Into<HalfData>) -> Self;
+
+    /// Sets the value of an input tensor from INT8 quantized data, dequantized to fixed point.
+    fn set_quantized(self, data: QuantizedData) -> Self;
+
+    /// Sets the value of an input tensor from `f32` data converted with the given rounding mode.
+    fn set_rounded(self, data: Vec<f32>, mode: RoundingMode) -> Self;
+
+    /// Sets the value of an input tensor from a user-defined dtype.
+    fn set_custom(self, data: impl CustomDtype) -> Self;
+
+    /// Sets the value of an input tensor from sparse (COO or CSR) data.
+    ///
+    /// Panics if the entries are invalid, see `SparseData::validate`.
+    fn set_sparse(self, data: SparseData) -> Self;
+
+    /// Sets the value of an input tensor from run-length compressed data.
+    ///
+    /// Panics if the encoded bytes are malformed, see `CompressedData::validate`.
+    fn set_compressed(self, data: CompressedData) -> Self;
+}
+
+impl SetTensorData for GraphTensor {
+    fn set_integers(self, data: impl Into<IntegerData>) -> Self {
+        let data = data.into();
+        set_input_data(self, data.len(), data)
+    }
+
+    fn set_half(self, data: impl Into<HalfData>) -> Self {
+        let data = data.into();
+        set_input_data(self, data.len(), data)
+    }
+
+    fn set_quantized(self, data: QuantizedData) -> Self {
+        set_input_data(self, data.len(), data)
+    }
+
+    fn set_rounded(self, data: Vec<f32>, mode: RoundingMode) -> Self {
+        let len = data.len();
+        set_input_data(self, len, StwoData::from_f32_rounded(&data, mode))
+    }
+
+    fn set_custom(self, data: impl CustomDtype) -> Self {
+        let len = data.len();
+        set_input_data(self, len, CustomData(Arc::new(data)))
+    }
+
+    fn set_sparse(self, data: SparseData) -> Self {
+        if let Err(e) = data.validate() {
+            panic!("{}", e);
+        }
+        set_input_data(self, data.len(), data)
+    }
+
+    fn set_compressed(self, data: CompressedData) -> Self {
+        if let Err(e) = data.validate() {
+            panic!("{}", e);
+        }
+        set_input_data(self, data.len(), data)
+    }
+}
+
+/// Replaces the loader of an input tensor's `Function` node with one returning `data`.
+fn set_input_data<D: Data + Clone>(tensor: GraphTensor, len: usize, data: D) -> GraphTensor {
+    assert_eq!(
+        len,
+        tensor.shape.n_elements().to_usize().unwrap(),
+        "Data length doesn't match the tensor shape"
+    );
+    tensor.graph().get_op_mut::<Function>(tensor.id).1 =
+        Box::new(move |_| vec![Tensor::new(data.clone())]);
+    tensor
+}
+
+/// Element type of a graph output, inferred from the operator producing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputKind {
+    /// Real values.
+    F32,
+    /// Integer values (e.g. indices).
+    I64,
+    /// Boolean masks (e.g. comparison results).
+    Bool,
+}
+
+/// Output tensor data with its element type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputData {
+    /// Real values.
+    F32(Vec<f32>),
+    /// Integer values.
+    I64(Vec<i64>),
+    /// Boolean masks.
+    Bool(Vec<bool>),
+}
+
+/// Extension trait to read graph outputs with a Rust type other than `f32`.
+///
+/// Outputs always come back from the circuit as fixed-point values, so integers and
+/// booleans are recovered by rounding and by comparing against zero respectively.
+pub trait GetTensorData {
+    /// Returns the output values rounded to the nearest integer.
+    fn data_i64(&self) -> Vec<i64>;
+
+    /// Returns the output values as a boolean mask (non-zero is `true`).
+    fn data_bool(&self) -> Vec<bool>;
+
+    /// Infers the element type of the output from the operator that produced it.
+    ///
+    /// Comparisons yield `Bool`. No primitive produces indices yet, so `I64` is
+    /// only reached through `data_i64`.
+    fn output_kind(&self) -> OutputKind;
+
+    /// Returns the output values converted according to `output_kind`.
+    fn data_typed(&self) -> OutputData;
+
+    /// Returns the output values as a user-defined dtype.
+    fn data_custom<D: CustomDtype>(&self) -> D;
+}
+
+impl GetTensorData for GraphTensor {
+    fn data_i64(&self) -> Vec<i64> {
+        self.data().iter().map(|&v| v.round() as i64).collect()
+    }
+
+    fn data_bool(&self) -> Vec<bool> {
+        self.data().iter().map(|&v| v != 0.0).collect()
+    }
+
+    fn output_kind(&self) -> OutputKind {
+        let graph = self.graph();
+        let mut node = self.id;
+        // Look through the copy back to CPU to find the producing operator
+        while graph.check_node_type::<CopyFromStwo>(node) {
+            node = graph.get_sources(node)[0].0;
+        }
+
+        if graph.check_node_type::<LessThan>(node) {
+            OutputKind::Bool
+        } else {
+            OutputKind::F32
+        }
+    }
+
+    fn data_typed(&self) -> OutputData {
+        match self.output_kind() {
+            OutputKind::F32 => OutputData::F32(self.data()),
+            OutputKind::I64 => OutputData::I64(self.data_i64()),
+            OutputKind::Bool => OutputData::Bool(self.data_bool()),
+        }
+    }
+
+    fn data_custom<D: CustomDtype>(&self) -> D {
+        let graph = self.graph();
+        let mut node = self.id;
+        // Read the fixed-point values kept by the producer of the copy back to CPU
+        while graph.check_node_type::<CopyFromStwo>(node) {
+            node = graph.get_sources(node)[0].0;
+        }
+        if let Some(data) = graph
+            .get_tensor_ref(node, 0)
+            .and_then(|t| t.downcast_ref::<StwoData>())
+        {
+            return D::from_fixed(&data.0);
+        }
+
+        // Outputs that never reached the STWO backend are plain f32
+        D::from_fixed(&f32_to_fixed(&self.data(), RoundingMode::Nearest))
+    }
+}
@@ -0,0 +1,550 @@
+use std::{collections::BTreeMap, path::Path};
+
+use luminair_air::{pie::LuminairPie, settings::CircuitSettings};
+use luminair_prover::{prover::prove, LuminairProof};
+use luminair_utils::LuminairError;
+use luminal::prelude::*;
+use serde::{Deserialize, Serialize};
+use stwo_prover::core::vcs::blake2_merkle::Blake2sMerkleHasher;
+
+use crate::{
+    batch::clear_outputs, config::ProjectConfig, graph::LuminairGraph, metrics::Metrics,
+    npy::NpyArray, stwo_compiler, weights::SafetensorsWeights,
+};
+
+/// A graph input, fed from an input file or from the description itself.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct InputSpec {
+    /// Name of the input, matched against the `.npz` entries and the weights.
+    pub name: String,
+    /// Dimensions of the input.
+    pub shape: Vec<usize>,
+    /// Constant values, in row-major order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<f32>>,
+}
+
+/// An operator applied to previously defined tensors.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeSpec {
+    /// Name of the resulting tensor.
+    pub name: String,
+    /// Operator name, e.g. `add`, `sin` or `sum_reduce`.
+    pub op: String,
+    /// Names of the operands.
+    pub inputs: Vec<String>,
+    /// Axes reduced by `sum_reduce`, `max_reduce` and `mean_reduce`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub axes: Vec<usize>,
+}
+
+/// A model described in JSON, for driving LuminAIR without writing Rust.
+///
+/// ```json
+/// {
+///   "inputs": [{ "name": "a", "shape": [2, 2] }, { "name": "b", "shape": [2, 2] }],
+///   "nodes": [{ "name": "c", "op": "mul", "inputs": ["a", "b"] }],
+///   "outputs": ["c"]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelDescription {
+    /// Graph inputs.
+    pub inputs: Vec<InputSpec>,
+    /// Operators, in definition order.
+    pub nodes: Vec<NodeSpec>,
+    /// Names of the tensors retrieved after execution.
+    pub outputs: Vec<String>,
+}
+
+/// Tensors of a model added to a graph.
+///
+/// A model is built with `ModelDescription::build`, then bound to its inputs, compiled and
+/// checked against the project's limits, and finally executed with `run`, `trace` or `prove`.
+/// `ModelDescription::compile` runs the first steps at once.
+pub struct Model {
+    /// Input tensors, by name.
+    pub inputs: BTreeMap<String, GraphTensor>,
+    /// Retrieved output tensors, in description order.
+    pub outputs: Vec<(String, GraphTensor)>,
+}
+
+/// Outputs and trace of a model's execution, from `Model::trace`.
+#[derive(Debug)]
+pub struct ModelTrace {
+    pub outputs: BTreeMap<String, NpyArray>,
+    pub settings: CircuitSettings,
+    pub pie: LuminairPie,
+}
+
+/// Outputs and proof of a model's execution, from `Model::prove` or `ModelTrace::prove`.
+pub struct ModelProof {
+    pub outputs: BTreeMap<String, NpyArray>,
+    pub settings: CircuitSettings,
+    pub proof: LuminairProof<Blake2sMerkleHasher>,
+}
+
+impl ModelDescription {
+    pub fn from_json(json: &str) -> Result<Self, LuminairError> {
+        serde_json::from_str(json).map_err(|e| {
+            LuminairError::InvalidGraph(format!("Failed to parse model description: {}", e))
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, LuminairError> {
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            LuminairError::InvalidGraph(format!("Failed to read model description: {}", e))
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// Adds the model's tensors to `cx` and marks its outputs as retrieved.
+    pub fn build(&self, cx: &mut Graph) -> Result<Model, LuminairError> {
+        let mut tensors = BTreeMap::new();
+        let mut inputs = BTreeMap::new();
+
+        for input in &self.inputs {
+            let shape = input
+                .shape
+                .iter()
+                .map(|&d| Expression::from(d))
+                .collect::<Vec<_>>();
+            let mut tensor = cx.named_tensor(&input.name, shape);
+            if let Some(data) = &input.data {
+                check_len(&input.name, &input.shape, data.len())?;
+                tensor = tensor.set(data.clone());
+            }
+            define(&mut tensors, &input.name, tensor)?;
+            inputs.insert(input.name.clone(), tensor);
+        }
+
+        for node in &self.nodes {
+            let operands = node
+                .inputs
+                .iter()
+                .map(|name| {
+                    tensors.get(name).copied().ok_or_else(|| {
+                        invalid(format!(
+                            "Node '{}' reads undefined tensor '{}'",
+                            node.name, name
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let tensor = apply(node, &operands)?;
+            define(&mut tensors, &node.name, tensor)?;
+        }
+
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|name| {
+                tensors
+                    .get(name)
+                    .map(|t| (name.clone(), t.retrieve()))
+                    .ok_or_else(|| invalid(format!("Output '{}' is not defined", name)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Model { inputs, outputs })
+    }
+
+    /// Builds the model in `cx`, binds its inputs and compiles it, then checks its cost.
+    pub fn compile(
+        &self,
+        cx: &mut Graph,
+        arrays: &BTreeMap<String, NpyArray>,
+        weights: Option<&SafetensorsWeights>,
+        config: &ProjectConfig,
+    ) -> Result<Model, LuminairError> {
+        let mut model = self.build(cx)?;
+        model.bind_inputs(self, arrays, weights)?;
+        model.compile(cx, config);
+        model.check_cost(cx, config)?;
+        Ok(model)
+    }
+}
+
+impl Model {
+    /// Sets the inputs not given a value by the description.
+    ///
+    /// Each input is looked up in `arrays` first, then in `weights`.
+    pub fn bind_inputs(
+        &self,
+        description: &ModelDescription,
+        arrays: &BTreeMap<String, NpyArray>,
+        weights: Option<&SafetensorsWeights>,
+    ) -> Result<(), LuminairError> {
+        for spec in description.inputs.iter().filter(|s| s.data.is_none()) {
+            let tensor = self.inputs[&spec.name];
+            if let Some(array) = arrays.get(&spec.name) {
+                if array.shape != spec.shape {
+                    return Err(invalid(format!(
+                        "Input '{}' has shape {:?}, model expects {:?}",
+                        spec.name, array.shape, spec.shape
+                    )));
+                }
+                tensor.set(array.data.clone());
+            } else if let Some(weights) = weights.filter(|w| w.shape(&spec.name).is_some()) {
+                weights.bind(&spec.name, tensor)?;
+            } else {
+                return Err(invalid(format!("No data for input '{}'", spec.name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles `cx` with the `StwoCompiler` pipeline for `config`, keeping track of the inputs
+    /// and outputs.
+    ///
+    /// Inputs must be bound beforehand, and can be bound again to execute the compiled graph
+    /// on other values.
+    pub fn compile(&mut self, cx: &mut Graph, config: &ProjectConfig) {
+        let mut inputs = self.inputs.values().copied().collect::<Vec<_>>();
+        let mut outputs = self.outputs.iter().map(|(_, t)| *t).collect::<Vec<_>>();
+        cx.compile(
+            (GenericCompiler::default(), stwo_compiler(config)),
+            (&mut inputs, &mut outputs),
+        );
+        for (tensor, compiled) in self.inputs.values_mut().zip(inputs) {
+            *tensor = compiled;
+        }
+        for ((_, tensor), compiled) in self.outputs.iter_mut().zip(outputs) {
+            *tensor = compiled;
+        }
+    }
+
+    /// Checks the predicted cost of the compiled graph is within the limits of `config`.
+    pub fn check_cost(&self, cx: &Graph, config: &ProjectConfig) -> Result<(), LuminairError> {
+        config.check_cost(&cx.estimate_cost())
+    }
+
+    /// Executes the compiled graph without generating a trace, returning the outputs.
+    ///
+    /// Like `trace` and `prove`, it can be called again after binding other inputs.
+    pub fn run(&self, cx: &mut Graph) -> Result<BTreeMap<String, NpyArray>, LuminairError> {
+        self.clear_outputs(cx);
+        cx.execute();
+        self.output_arrays()
+    }
+
+    /// Executes the compiled graph, generating its circuit settings and trace.
+    ///
+    /// The execution is recorded in `metrics`, if given.
+    pub fn trace(
+        &self,
+        cx: &mut Graph,
+        metrics: Option<&Metrics>,
+    ) -> Result<ModelTrace, LuminairError> {
+        // `gen_circuit_settings` would panic on unsupported ops rather than return the error
+        cx.check_supported_ops()?;
+        self.clear_outputs(cx);
+        let mut settings = cx.gen_circuit_settings();
+        self.clear_outputs(cx);
+        let pie = match metrics {
+            Some(metrics) => metrics.gen_trace(cx, &mut settings)?,
+            None => cx.gen_trace(&mut settings)?,
+        };
+        Ok(ModelTrace {
+            outputs: self.output_arrays()?,
+            settings,
+            pie,
+        })
+    }
+
+    /// Executes and proves the compiled graph, see `trace` and `ModelTrace::prove`.
+    pub fn prove(
+        &self,
+        cx: &mut Graph,
+        metrics: Option<&Metrics>,
+    ) -> Result<ModelProof, LuminairError> {
+        self.trace(cx, metrics)?.prove(metrics)
+    }
+
+    /// Outputs of a previous execution would be kept, and their nodes skipped.
+    fn clear_outputs(&self, cx: &mut Graph) {
+        let outputs = self.outputs.iter().map(|(_, t)| *t).collect::<Vec<_>>();
+        clear_outputs(cx, &outputs);
+    }
+
+    /// Reads the values of the outputs, once the graph has been executed.
+    pub fn output_arrays(&self) -> Result<BTreeMap<String, NpyArray>, LuminairError> {
+        self.outputs
+            .iter()
+            .map(|(name, tensor)| {
+                let shape = tensor
+                    .shape
+                    .dims()
+                    .iter()
+                    .map(|d| d.to_usize().unwrap())
+                    .collect();
+                Ok((name.clone(), NpyArray::new(shape, tensor.data())?))
+            })
+            .collect()
+    }
+}
+
+impl ModelTrace {
+    /// Proves the execution, recording it in `metrics` if given.
+    pub fn prove(self, metrics: Option<&Metrics>) -> Result<ModelProof, LuminairError> {
+        let proof = match metrics {
+            Some(metrics) => metrics.prove(self.pie, self.settings.clone())?,
+            None => prove(self.pie, self.settings.clone())?,
+        };
+        Ok(ModelProof {
+            outputs: self.outputs,
+            settings: self.settings,
+            proof,
+        })
+    }
+}
+
+impl ModelProof {
+    /// Serializes the proof and the circuit settings to verify it with.
+    pub fn to_bincode(&self) -> Result<(Vec<u8>, Vec<u8>), LuminairError> {
+        Ok((self.proof.to_bincode()?, self.settings.to_bincode()?))
+    }
+}
+
+/// Applies the operator of `node` to its operands.
+fn apply(node: &NodeSpec, operands: &[GraphTensor]) -> Result<GraphTensor, LuminairError> {
+    let arity = match node.op.as_str() {
+        "add" | "sub" | "mul" | "div" | "lt" | "matmul" => 2,
+        _ => 1,
+    };
+    if operands.len() != arity {
+        return Err(invalid(format!(
+            "Node '{}' ({}) takes {} inputs, got {}",
+            node.name,
+            node.op,
+            arity,
+            operands.len()
+        )));
+    }
+
+    if arity == 2 {
+        check_shapes(node, operands[0], operands[1])?;
+    }
+
+    let a = operands[0];
+    let tensor = match node.op.as_str() {
+        "add" => a + operands[1],
+        "sub" => a - operands[1],
+        "mul" => a * operands[1],
+        "div" => a / operands[1],
+        "lt" => a.lt(operands[1]),
+        "matmul" => a.matmul(operands[1]),
+        "neg" => -a,
+        "abs" => a.abs(),
+        "recip" => a.recip(),
+        "sqrt" => a.sqrt(),
+        "sin" => a.sin(),
+        "cos" => a.cos(),
+        "exp" => a.exp(),
+        "exp2" => a.exp2(),
+        "ln" => a.ln(),
+        "log2" => a.log2(),
+        "relu" => a.relu(),
+        "sigmoid" => a.sigmoid(),
+        "sum_reduce" | "max_reduce" | "mean_reduce" => reduce(node, a)?,
+        op => {
+            return Err(invalid(format!(
+                "Unknown operator '{}' in node '{}'",
+                op, node.name
+            )))
+        }
+    };
+    Ok(tensor)
+}
+
+/// Checks the operands of a binary node have shapes its operator accepts, since luminal
+/// panics on mismatched shapes.
+///
+/// Element-wise operators need identical shapes. `matmul` takes the shapes luminal
+/// implements: a vector or matrix times a matrix, a 3-d tensor times a matrix, or two
+/// tensors of 3 to 5 dimensions with the same batch dimensions; the last dimension of
+/// `a` must match the second to last of `b`.
+fn check_shapes(node: &NodeSpec, a: GraphTensor, b: GraphTensor) -> Result<(), LuminairError> {
+    let dims = |t: GraphTensor| {
+        t.shape
+            .dims()
+            .iter()
+            .map(|d| d.to_usize().unwrap())
+            .collect::<Vec<_>>()
+    };
+    let (a, b) = (dims(a), dims(b));
+    let valid = if node.op == "matmul" {
+        matches!((a.len(), b.len()), (1..=3, 2) | (3, 3) | (4, 4) | (5, 5))
+            && (b.len() == 2 || a[..a.len() - 2] == b[..b.len() - 2])
+            && a[a.len() - 1] == b[b.len() - 2]
+    } else {
+        a == b
+    };
+    if !valid {
+        return Err(invalid(format!(
+            "Node '{}' ({}) can't take operands of shapes {:?} and {:?}",
+            node.name, node.op, a, b
+        )));
+    }
+    Ok(())
+}
+
+/// Reduces `a` over the axes of `node`, highest axis first so lower indices stay valid.
+fn reduce(node: &NodeSpec, mut a: GraphTensor) -> Result<GraphTensor, LuminairError> {
+    let mut axes = node.axes.clone();
+    axes.sort_unstable();
+    axes.dedup();
+    if axes.is_empty() {
+        return Err(invalid(format!(
+            "Node '{}' has no axes to reduce",
+            node.name
+        )));
+    }
+    if let Some(&axis) = axes.iter().find(|&&ax| ax >= a.shape.len()) {
+        return Err(invalid(format!(
+            "Node '{}' reduces axis {} of a {}-d tensor",
+            node.name,
+            axis,
+            a.shape.len()
+        )));
+    }
+
+    for &axis in axes.iter().rev() {
+        a = match node.op.as_str() {
+            "sum_reduce" => a.sum_reduce(axis),
+            "max_reduce" => a.max_reduce(axis),
+            _ => a.mean_reduce(axis),
+        };
+    }
+    Ok(a)
+}
+
+fn define(
+    tensors: &mut BTreeMap<String, GraphTensor>,
+    name: &str,
+    tensor: GraphTensor,
+) -> Result<(), LuminairError> {
+    if tensors.insert(name.to_string(), tensor).is_some() {
+        return Err(invalid(format!("Tensor '{}' is defined twice", name)));
+    }
+    Ok(())
+}
+
+fn check_len(name: &str, shape: &[usize], len: usize) -> Result<(), LuminairError> {
+    if shape.iter().product::<usize>() != len {
+        return Err(invalid(format!(
+            "Input '{}' has {} values, shape {:?} expects {}",
+            name,
+            len,
+            shape,
+            shape.iter().product::<usize>()
+        )));
+    }
+    Ok(())
+}
+
+fn invalid(message: String) -> LuminairError {
+    LuminairError::InvalidGraph(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = r#"{
+        "inputs": [
+            { "name": "a", "shape": [2, 2] },
+            { "name": "w", "shape": [2, 2], "data": [-1.0, -1.0, -1.0, -1.0] }
+        ],
+        "nodes": [
+            { "name": "c", "op": "add", "inputs": ["a", "w"] },
+            { "name": "d", "op": "sum_reduce", "inputs": ["c"], "axes": [1] }
+        ],
+        "outputs": ["d"]
+    }"#;
+
+    #[test]
+    fn test_build_and_execute() {
+        let description = ModelDescription::from_json(MODEL).unwrap();
+        let mut cx = Graph::new();
+        let mut model = description.build(&mut cx).unwrap();
+
+        let arrays = BTreeMap::from([(
+            "a".to_string(),
+            NpyArray::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap(),
+        )]);
+        model.bind_inputs(&description, &arrays, None).unwrap();
+
+        model.compile(&mut cx, &ProjectConfig::default());
+        cx.execute();
+
+        let d = &model.output_arrays().unwrap()["d"];
+        assert_eq!(d.shape, [2]);
+        assert_eq!(d.data, [1.0, 5.0]);
+    }
+
+    #[test]
+    fn test_run_and_prove_again() {
+        let description = ModelDescription::from_json(MODEL).unwrap();
+        let config = ProjectConfig::default();
+        let mut cx = Graph::new();
+        let arrays = BTreeMap::from([(
+            "a".to_string(),
+            NpyArray::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap(),
+        )]);
+        let model = description
+            .compile(&mut cx, &arrays, None, &config)
+            .unwrap();
+        assert_eq!(model.run(&mut cx).unwrap()["d"].data, [1.0, 5.0]);
+
+        let arrays = BTreeMap::from([(
+            "a".to_string(),
+            NpyArray::new(vec![2, 2], vec![2.0, 2.0, 2.0, 2.0]).unwrap(),
+        )]);
+        model.bind_inputs(&description, &arrays, None).unwrap();
+        let proven = model.prove(&mut cx, None).unwrap();
+        assert_eq!(proven.outputs["d"].data, [2.0, 2.0]);
+        luminair_verifier::verifier::verify(proven.proof, proven.settings).unwrap();
+    }
+
+    #[test]
+    fn test_missing_input_data() {
+        let description = ModelDescription::from_json(MODEL).unwrap();
+        let mut cx = Graph::new();
+        let model = description.build(&mut cx).unwrap();
+
+        assert!(model
+            .bind_inputs(&description, &BTreeMap::new(), None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_undefined_operand() {
+        let mut description = ModelDescription::from_json(MODEL).unwrap();
+        description.nodes[0].inputs[1] = "x".to_string();
+
+        assert!(description.build(&mut Graph::new()).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_shapes() {
+        let mut description = ModelDescription::from_json(MODEL).unwrap();
+        description.inputs[1].shape = vec![4];
+        description.inputs[1].data = Some(vec![-1.0; 4]);
+        assert!(matches!(
+            description.build(&mut Graph::new()),
+            Err(LuminairError::InvalidGraph(_))
+        ));
+
+        let mut description = ModelDescription::from_json(MODEL).unwrap();
+        description.inputs[1].shape = vec![3, 2];
+        description.inputs[1].data = Some(vec![-1.0; 6]);
+        description.nodes[0].op = "matmul".to_string();
+        assert!(matches!(
+            description.build(&mut Graph::new()),
+            Err(LuminairError::InvalidGraph(_))
+        ));
+        description.inputs[1].shape = vec![2, 3];
+        assert!(description.build(&mut Graph::new()).is_ok());
+    }
+}
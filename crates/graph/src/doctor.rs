@@ -0,0 +1,253 @@
+use std::{fmt, path::Path, time::Instant};
+
+use luminair_air::{fixed::conformance_self_test, settings::CircuitSettings};
+use luminair_prover::prover::prove;
+use luminair_verifier::verifier::verify;
+use luminal::prelude::*;
+use serde::Serialize;
+
+use crate::{config::ProjectConfig, graph::LuminairGraph, StwoCompiler};
+
+/// Free space below which the trace directory check warns.
+pub const MIN_TRACE_SPACE: u64 = 1 << 30;
+
+/// Outcome of a single check.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Result of a single check, with a hint on how to fix it when it didn't pass.
+#[derive(Clone, Debug, Serialize)]
+pub struct Diagnostic {
+    pub check: String,
+    pub status: CheckStatus,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl Diagnostic {
+    fn ok(check: &str, message: impl Into<String>) -> Self {
+        Self {
+            check: check.to_string(),
+            status: CheckStatus::Ok,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn failed(
+        check: &str,
+        status: CheckStatus,
+        message: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self {
+            check: check.to_string(),
+            status,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Error => "error",
+        };
+        write!(f, "[{}] {}: {}", status, self.check, self.message)?;
+        if let Some(hint) = &self.hint {
+            write!(f, "\n      {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+/// Diagnostics of a LuminAIR installation, generated by `diagnose`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DoctorReport {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DoctorReport {
+    /// Whether no check failed. Warnings don't prevent proving.
+    pub fn is_healthy(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .all(|d| d.status != CheckStatus::Error)
+    }
+}
+
+/// Checks that this installation can generate and verify proofs.
+///
+/// Runs the fixed-point conformance test and a small end-to-end proof, checks the
+/// project's `luminair.toml`, that `trace_dir` is writable with enough free space for
+/// traces, and, if given, that `settings` were generated for this build.
+pub fn diagnose(trace_dir: &Path, settings: Option<&CircuitSettings>) -> DoctorReport {
+    let mut diagnostics = vec![
+        check_build(),
+        check_conformance(),
+        check_prover(),
+        check_threads(),
+        check_config(),
+    ];
+    diagnostics.extend(check_trace_dir(trace_dir));
+    if let Some(settings) = settings {
+        diagnostics.push(check_settings(settings));
+    }
+    DoctorReport { diagnostics }
+}
+
+fn check_build() -> Diagnostic {
+    if cfg!(debug_assertions) {
+        Diagnostic::failed(
+            "build",
+            CheckStatus::Warning,
+            "debug build",
+            "Build with --release, proving is much slower without optimizations.",
+        )
+    } else {
+        Diagnostic::ok("build", "release build")
+    }
+}
+
+fn check_conformance() -> Diagnostic {
+    match conformance_self_test() {
+        Ok(()) => Diagnostic::ok("fixed point", "conversions match the reference"),
+        Err(e) => Diagnostic::failed(
+            "fixed point",
+            CheckStatus::Error,
+            e.to_string(),
+            "Proofs generated on this machine won't match other machines. Check for \
+             non-default floating-point flags (e.g. -ffast-math) in the build.",
+        ),
+    }
+}
+
+fn check_prover() -> Diagnostic {
+    let start = Instant::now();
+    let result = (|| {
+        let mut cx = Graph::new();
+        let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+        let mut b = (a * a + a).retrieve();
+        cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+
+        let mut settings = cx.gen_circuit_settings();
+        let trace = cx.gen_trace(&mut settings)?;
+        let proof = prove(trace, settings.clone())?;
+        verify(proof, settings)
+    })();
+    match result {
+        Ok(()) => Diagnostic::ok(
+            "prover",
+            format!(
+                "proved and verified a test graph in {:.0} ms",
+                start.elapsed().as_secs_f64() * 1e3
+            ),
+        ),
+        Err(e) => Diagnostic::failed(
+            "prover",
+            CheckStatus::Error,
+            e.to_string(),
+            "The prover backend is broken. Rebuild from a clean checkout.",
+        ),
+    }
+}
+
+fn check_threads() -> Diagnostic {
+    match rayon::current_num_threads() {
+        1 => Diagnostic::failed(
+            "threads",
+            CheckStatus::Warning,
+            "proving on a single thread",
+            "Unset RAYON_NUM_THREADS, or raise it, to prove in parallel.",
+        ),
+        n => Diagnostic::ok("threads", format!("proving on {} threads", n)),
+    }
+}
+
+fn check_config() -> Diagnostic {
+    let Some(path) = ProjectConfig::find() else {
+        return Diagnostic::ok("config", "no luminair.toml, using defaults");
+    };
+    match ProjectConfig::from_file(&path) {
+        Ok(_) => Diagnostic::ok("config", format!("{} is valid", path.display())),
+        Err(e) => Diagnostic::failed(
+            "config",
+            CheckStatus::Error,
+            e.to_string(),
+            "Fix or remove the file, compilation fails while it is invalid.",
+        ),
+    }
+}
+
+fn check_trace_dir(dir: &Path) -> Vec<Diagnostic> {
+    let probe = dir.join(".luminair-doctor");
+    if let Err(e) = std::fs::write(&probe, b"").and_then(|_| std::fs::remove_file(&probe)) {
+        return vec![Diagnostic::failed(
+            "trace directory",
+            CheckStatus::Error,
+            format!("{} is not writable: {}", dir.display(), e),
+            "Create the directory or choose another one for traces and proofs.",
+        )];
+    }
+
+    let mut diagnostics = vec![Diagnostic::ok(
+        "trace directory",
+        format!("{} is writable", dir.display()),
+    )];
+    match free_space(dir) {
+        Some(free) if free < MIN_TRACE_SPACE => diagnostics.push(Diagnostic::failed(
+            "disk space",
+            CheckStatus::Warning,
+            format!("{} MiB free", free >> 20),
+            "Traces of large models take several GiB. Free up space or move the trace directory.",
+        )),
+        Some(free) => diagnostics.push(Diagnostic::ok(
+            "disk space",
+            format!("{} MiB free", free >> 20),
+        )),
+        None => {}
+    }
+    diagnostics
+}
+
+fn check_settings(settings: &CircuitSettings) -> Diagnostic {
+    match settings.check_fp_scale() {
+        Ok(()) => Diagnostic::ok(
+            "settings",
+            format!("fixed-point scale {} matches", settings.fp_scale),
+        ),
+        Err(e) => Diagnostic::failed(
+            "settings",
+            CheckStatus::Error,
+            e.to_string(),
+            "Regenerate the settings with this build, or use the build they were made with.",
+        ),
+    }
+}
+
+/// Free space available to the current user on the filesystem holding `dir`.
+#[cfg(unix)]
+fn free_space(dir: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stat` is only read after a successful call.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let stat = unsafe { stat.assume_init() };
+    Some(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+}
+
+#[cfg(not(unix))]
+fn free_space(_dir: &Path) -> Option<u64> {
+    None
+}
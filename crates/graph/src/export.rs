@@ -0,0 +1,130 @@
+use std::fmt::Write;
+
+use luminair_utils::LuminairError;
+use luminal::prelude::{petgraph::visit::EdgeRef, *};
+use serde::{Deserialize, Serialize};
+
+use crate::{cost::component_of, op::other::runs_on_cpu};
+
+/// A node of an exported graph.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExportedNode {
+    /// The unique ID of the node.
+    pub id: u32,
+    /// Description of the operator.
+    pub op: String,
+    /// Name of the AIR component proving the operator, if any.
+    pub component: Option<String>,
+    /// True if the operator is executed on luminal's CPU backend and not proven.
+    pub on_cpu: bool,
+}
+
+/// An edge of an exported graph.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExportedEdge {
+    /// ID of the producing node.
+    pub source: u32,
+    /// ID of the consuming node.
+    pub target: u32,
+    /// Position of the input in the consumer.
+    pub input: u8,
+    /// Output of the producer being read.
+    pub output: u8,
+    /// Shape the consumer reads the tensor with.
+    pub shape: String,
+    /// True if the tensor is broadcast to the consumer's shape.
+    ///
+    /// Broadcasts are not materialized: the consumer reads the producer's elements
+    /// several times, and proves one row per broadcast element.
+    pub broadcast: bool,
+}
+
+/// Snapshot of a compiled graph, for inspecting lowering decisions.
+///
+/// Generated by `GraphExport::new`, and rendered with `to_dot` or `to_json`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GraphExport {
+    /// Nodes, in ID order.
+    pub nodes: Vec<ExportedNode>,
+    /// Data edges, ordered by consumer then input.
+    pub edges: Vec<ExportedEdge>,
+}
+
+impl GraphExport {
+    /// Captures the nodes and data edges of `graph`.
+    pub fn new(graph: &Graph) -> Self {
+        let mut ids = graph.node_indices().collect::<Vec<_>>();
+        ids.sort_unstable();
+
+        let nodes = ids
+            .iter()
+            .map(|&n| ExportedNode {
+                id: n.index() as u32,
+                op: format!("{:?}", graph.node_weight(n).unwrap()),
+                component: component_of(graph, n).map(|(name, _)| name.to_string()),
+                on_cpu: runs_on_cpu(graph, n),
+            })
+            .collect();
+
+        let mut edges = graph
+            .graph
+            .edge_references()
+            .filter_map(|edge| {
+                let (input, output, shape) = edge.weight().as_data()?;
+                Some(ExportedEdge {
+                    source: edge.source().index() as u32,
+                    target: edge.target().index() as u32,
+                    input,
+                    output,
+                    shape: format!("{:?}", shape.shape()),
+                    broadcast: shape.fake.iter().any(|f| *f),
+                })
+            })
+            .collect::<Vec<_>>();
+        edges.sort_unstable_by_key(|e| (e.target, e.input));
+
+        Self { nodes, edges }
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    ///
+    /// Proven operators are filled green, CPU operators orange, and broadcast edges are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph luminair {\n    node [shape=box, style=filled];\n");
+        for node in &self.nodes {
+            let (label, color) = match (&node.component, node.on_cpu) {
+                (Some(component), _) => (format!("{}\\n[{}]", node.op, component), "palegreen"),
+                (None, true) => (format!("{}\\n[cpu]", node.op), "orange"),
+                (None, false) => (node.op.clone(), "white"),
+            };
+            writeln!(
+                dot,
+                "    n{} [label=\"{}: {}\", fillcolor={}];",
+                node.id,
+                node.id,
+                label.replace('"', "\\\""),
+                color
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"{}];",
+                edge.source,
+                edge.target,
+                edge.shape.replace('"', "\\\""),
+                if edge.broadcast { ", style=dashed" } else { "" }
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> Result<String, LuminairError> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to serialize graph to JSON: {}", e))
+        })
+    }
+}
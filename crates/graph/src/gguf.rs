@@ -0,0 +1,434 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use half::{bf16, f16};
+use luminair_utils::LuminairError;
+use luminal::{op::Function, prelude::*};
+use stwo_prover::core::vcs::blake2_hash::Blake2sHash;
+
+use crate::{
+    data::{HalfData, StwoData},
+    quant::{QuantGranularity, QuantizedData},
+    weights::{commit, decode},
+};
+
+const MAGIC: &[u8] = b"GGUF";
+const DEFAULT_ALIGNMENT: u64 = 32;
+/// Number of elements in a `Q8_0` or `Q4_0` block.
+const BLOCK_SIZE: usize = 32;
+
+// GGML tensor types.
+const GGML_F32: u32 = 0;
+const GGML_F16: u32 = 1;
+const GGML_Q4_0: u32 = 2;
+const GGML_Q8_0: u32 = 8;
+const GGML_BF16: u32 = 30;
+
+/// A metadata value of a GGUF file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GgufValue {
+    /// Any integer type.
+    Int(i64),
+    /// `f32` or `f64`.
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Array(Vec<GgufValue>),
+}
+
+/// Weights loaded from a GGUF file, already converted to fixed point.
+///
+/// `F32`, `F16` and `BF16` tensors are converted directly. `Q8_0` and `Q4_0` blocks
+/// are symmetric, so they are read as `QuantizedData` with one scale per block
+/// and dequantized exactly. Other GGML types, including the k-quants, are rejected.
+///
+/// GGUF lists dimensions from the fastest varying, so shapes are reversed to
+/// luminal's row-major order.
+#[derive(Clone, Debug)]
+pub struct GgufWeights {
+    tensors: HashMap<String, (Vec<usize>, StwoData)>,
+    metadata: BTreeMap<String, GgufValue>,
+    commitment: Blake2sHash,
+}
+
+impl GgufWeights {
+    /// Loads and converts the weights stored in a `.gguf` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, LuminairError> {
+        let data = std::fs::read(path)
+            .map_err(|e| gguf_error(format!("Failed to read GGUF file: {}", e)))?;
+        Self::from_bytes(&data)
+    }
+
+    /// Loads and converts weights from the bytes of a `.gguf` file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, LuminairError> {
+        let mut reader = Reader { data, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(gguf_error("Not a GGUF file".to_string()));
+        }
+        let version = reader.u32()?;
+        if !(2..=3).contains(&version) {
+            return Err(gguf_error(format!("Unsupported GGUF version {}", version)));
+        }
+        let tensor_count = reader.u64()?;
+        let metadata_count = reader.u64()?;
+
+        let mut metadata = BTreeMap::new();
+        for _ in 0..metadata_count {
+            let key = reader.string()?;
+            let value_type = reader.u32()?;
+            metadata.insert(key, reader.value(value_type)?);
+        }
+
+        let mut infos = Vec::new();
+        for _ in 0..tensor_count {
+            let name = reader.string()?;
+            let n_dims = reader.u32()?;
+            let mut shape = (0..n_dims)
+                .map(|_| {
+                    let dim = reader.u64()?;
+                    usize::try_from(dim).map_err(|_| too_large(&name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            shape.reverse();
+            let ggml_type = reader.u32()?;
+            let offset = reader.u64()?;
+            infos.push((name, shape, ggml_type, offset));
+        }
+
+        let alignment = match metadata.get("general.alignment") {
+            Some(GgufValue::Int(a)) if *a > 0 => *a as u64,
+            _ => DEFAULT_ALIGNMENT,
+        };
+        let data_start = (reader.pos as u64)
+            .div_ceil(alignment)
+            .checked_mul(alignment)
+            .ok_or_else(|| gguf_error(format!("Invalid GGUF alignment {}", alignment)))?;
+
+        let mut tensors = HashMap::new();
+        for (name, shape, ggml_type, offset) in infos {
+            let len = shape
+                .iter()
+                .try_fold(1usize, |len, &d| len.checked_mul(d))
+                .ok_or_else(|| too_large(&name))?;
+            let start = data_start
+                .checked_add(offset)
+                .and_then(|start| usize::try_from(start).ok())
+                .ok_or_else(|| too_large(&name))?;
+            let end = start
+                .checked_add(byte_size(&name, ggml_type, len)?)
+                .ok_or_else(|| too_large(&name))?;
+            let bytes = data
+                .get(start..end)
+                .ok_or_else(|| gguf_error(format!("Tensor '{}' is out of bounds", name)))?;
+            let stwo_data = match ggml_type {
+                GGML_F32 => StwoData::from_f32(&decode(bytes, f32::from_le_bytes)),
+                GGML_F16 => StwoData::from_half(&HalfData::F16(decode(bytes, f16::from_le_bytes))),
+                GGML_BF16 => {
+                    StwoData::from_half(&HalfData::BF16(decode(bytes, bf16::from_le_bytes)))
+                }
+                GGML_Q8_0 => StwoData::from_quantized(&dequantize_q8_0(bytes)),
+                _ => StwoData::from_quantized(&dequantize_q4_0(bytes)),
+            };
+            tensors.insert(name, (shape, stwo_data));
+        }
+
+        let commitment = commit(&tensors)?;
+        Ok(Self {
+            tensors,
+            metadata,
+            commitment,
+        })
+    }
+
+    /// Returns the commitment to the loaded weights.
+    pub fn commitment(&self) -> Blake2sHash {
+        self.commitment
+    }
+
+    /// Returns the metadata value of `key`, e.g. `general.architecture`.
+    pub fn metadata(&self, key: &str) -> Option<&GgufValue> {
+        self.metadata.get(key)
+    }
+
+    /// Returns the names of the loaded tensors, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.tensors.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Returns the shape of the tensor `name`, if loaded.
+    pub fn shape(&self, name: &str) -> Option<&[usize]> {
+        self.tensors.get(name).map(|(shape, _)| shape.as_slice())
+    }
+
+    /// Binds the weights of `name` to the input tensor `tensor`.
+    pub fn bind(&self, name: &str, tensor: GraphTensor) -> Result<GraphTensor, LuminairError> {
+        let (_, data) = self
+            .tensors
+            .get(name)
+            .ok_or_else(|| gguf_error(format!("Tensor '{}' not found in weights", name)))?;
+
+        let expected = tensor.shape.n_elements().to_usize().unwrap();
+        if data.0.len() != expected {
+            return Err(gguf_error(format!(
+                "Tensor '{}' has {} elements, graph tensor expects {}",
+                name,
+                data.0.len(),
+                expected
+            )));
+        }
+
+        let data = data.clone();
+        tensor.graph().get_op_mut::<Function>(tensor.id).1 =
+            Box::new(move |_| vec![Tensor::new(data.clone())]);
+        Ok(tensor)
+    }
+
+    /// Binds every `(name, tensor)` pair, stopping at the first error.
+    pub fn bind_all<'a>(
+        &self,
+        tensors: impl IntoIterator<Item = (&'a str, GraphTensor)>,
+    ) -> Result<(), LuminairError> {
+        for (name, tensor) in tensors {
+            self.bind(name, tensor)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sequential little-endian reader over the GGUF header.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], LuminairError> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| gguf_error("Truncated GGUF header".to_string()))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], LuminairError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> Result<u32, LuminairError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, LuminairError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self) -> Result<String, LuminairError> {
+        let len = self.u64()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| gguf_error("Invalid UTF-8 string in GGUF header".to_string()))
+    }
+
+    fn value(&mut self, value_type: u32) -> Result<GgufValue, LuminairError> {
+        Ok(match value_type {
+            0 => GgufValue::Int(self.array::<1>()?[0] as i64),
+            1 => GgufValue::Int(self.array::<1>()?[0] as i8 as i64),
+            2 => GgufValue::Int(u16::from_le_bytes(self.array()?) as i64),
+            3 => GgufValue::Int(i16::from_le_bytes(self.array()?) as i64),
+            4 => GgufValue::Int(self.u32()? as i64),
+            5 => GgufValue::Int(i32::from_le_bytes(self.array()?) as i64),
+            6 => GgufValue::Float(f32::from_le_bytes(self.array()?) as f64),
+            7 => GgufValue::Bool(self.array::<1>()?[0] != 0),
+            8 => GgufValue::String(self.string()?),
+            9 => {
+                let item_type = self.u32()?;
+                let len = self.u64()?;
+                GgufValue::Array(
+                    (0..len)
+                        .map(|_| self.value(item_type))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            10 => GgufValue::Int(self.u64()? as i64),
+            11 => GgufValue::Int(i64::from_le_bytes(self.array()?)),
+            12 => GgufValue::Float(f64::from_le_bytes(self.array()?)),
+            t => return Err(gguf_error(format!("Unknown GGUF value type {}", t))),
+        })
+    }
+}
+
+/// Returns the size in bytes of `len` elements of `ggml_type`.
+fn byte_size(name: &str, ggml_type: u32, len: usize) -> Result<usize, LuminairError> {
+    let (len, block_bytes) = match ggml_type {
+        GGML_F32 => (len, 4),
+        GGML_F16 | GGML_BF16 => (len, 2),
+        GGML_Q8_0 | GGML_Q4_0 if len % BLOCK_SIZE != 0 => {
+            return Err(gguf_error(format!(
+                "Tensor '{}' has {} elements, not a multiple of the block size",
+                name, len
+            )))
+        }
+        GGML_Q8_0 => (len / BLOCK_SIZE, 2 + BLOCK_SIZE),
+        GGML_Q4_0 => (len / BLOCK_SIZE, 2 + BLOCK_SIZE / 2),
+        t => {
+            return Err(gguf_error(format!(
+                "Unsupported GGML type {} for tensor '{}'",
+                t, name
+            )))
+        }
+    };
+    len.checked_mul(block_bytes).ok_or_else(|| too_large(name))
+}
+
+/// Reads `Q8_0` blocks: an `f16` scale followed by 32 `i8` values.
+fn dequantize_q8_0(bytes: &[u8]) -> QuantizedData {
+    let blocks = bytes.chunks_exact(2 + BLOCK_SIZE);
+    let mut data = QuantizedData {
+        values: Vec::with_capacity(blocks.len() * BLOCK_SIZE),
+        scales: Vec::with_capacity(blocks.len()),
+        granularity: QuantGranularity::PerBlock {
+            block_size: BLOCK_SIZE,
+        },
+    };
+    for block in blocks {
+        data.scales
+            .push(f16::from_le_bytes([block[0], block[1]]).to_f32());
+        data.values.extend(block[2..].iter().map(|&q| q as i8));
+    }
+    data
+}
+
+/// Reads `Q4_0` blocks: an `f16` scale followed by 16 bytes of 4-bit values offset by 8.
+///
+/// The low nibbles hold the first 16 values of the block, the high nibbles the last 16.
+fn dequantize_q4_0(bytes: &[u8]) -> QuantizedData {
+    let blocks = bytes.chunks_exact(2 + BLOCK_SIZE / 2);
+    let mut data = QuantizedData {
+        values: Vec::with_capacity(blocks.len() * BLOCK_SIZE),
+        scales: Vec::with_capacity(blocks.len()),
+        granularity: QuantGranularity::PerBlock {
+            block_size: BLOCK_SIZE,
+        },
+    };
+    for block in blocks {
+        data.scales
+            .push(f16::from_le_bytes([block[0], block[1]]).to_f32());
+        let qs = &block[2..];
+        data.values.extend(qs.iter().map(|&q| (q & 0x0f) as i8 - 8));
+        data.values.extend(qs.iter().map(|&q| (q >> 4) as i8 - 8));
+    }
+    data
+}
+
+fn gguf_error(msg: String) -> LuminairError {
+    LuminairError::WeightsError(msg)
+}
+
+fn too_large(name: &str) -> LuminairError {
+    gguf_error(format!("Tensor '{}' is too large", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serializes a GGUF v3 file with one metadata entry and the given tensors.
+    fn serialize(tensors: &[(&str, &[u64], u32, Vec<u8>)]) -> Vec<u8> {
+        let string = |out: &mut Vec<u8>, s: &str| {
+            out.extend_from_slice(&(s.len() as u64).to_le_bytes());
+            out.extend_from_slice(s.as_bytes());
+        };
+
+        let mut file = MAGIC.to_vec();
+        file.extend_from_slice(&3u32.to_le_bytes());
+        file.extend_from_slice(&(tensors.len() as u64).to_le_bytes());
+        file.extend_from_slice(&1u64.to_le_bytes());
+        string(&mut file, "general.architecture");
+        file.extend_from_slice(&8u32.to_le_bytes());
+        string(&mut file, "llama");
+
+        let mut offset = 0u64;
+        for (name, dims, ggml_type, data) in tensors {
+            string(&mut file, name);
+            file.extend_from_slice(&(dims.len() as u32).to_le_bytes());
+            dims.iter()
+                .for_each(|d| file.extend_from_slice(&d.to_le_bytes()));
+            file.extend_from_slice(&ggml_type.to_le_bytes());
+            file.extend_from_slice(&offset.to_le_bytes());
+            offset += (data.len() as u64).div_ceil(DEFAULT_ALIGNMENT) * DEFAULT_ALIGNMENT;
+        }
+        for (_, _, _, data) in tensors {
+            file.resize(file.len().div_ceil(32) * 32, 0);
+            file.extend_from_slice(data);
+        }
+        file
+    }
+
+    #[test]
+    fn test_load_quantized_blocks() {
+        let mut q8 = f16::from_f32(0.5).to_le_bytes().to_vec();
+        q8.extend((0..32).map(|i| (i as i8 - 16) as u8));
+        let mut q4 = f16::from_f32(0.25).to_le_bytes().to_vec();
+        q4.extend((0..16).map(|i| (i as u8) | (15 - i as u8) << 4));
+        let f32s = [1.0f32, -2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let file = serialize(&[
+            ("q8", &[16, 2], GGML_Q8_0, q8),
+            ("q4", &[32], GGML_Q4_0, q4),
+            ("bias", &[2], GGML_F32, f32s),
+        ]);
+        let weights = GgufWeights::from_bytes(&file).unwrap();
+
+        assert_eq!(weights.names(), ["bias", "q4", "q8"]);
+        assert_eq!(weights.shape("q8"), Some([2, 16].as_slice()));
+        assert_eq!(
+            weights.metadata("general.architecture"),
+            Some(&GgufValue::String("llama".to_string()))
+        );
+
+        let q8 = weights.tensors["q8"].1.to_f32();
+        assert_eq!(q8[0], -8.0);
+        assert_eq!(q8[31], 7.5);
+        let q4 = weights.tensors["q4"].1.to_f32();
+        assert_eq!(q4[0], -2.0);
+        assert_eq!(q4[16], 1.75);
+        assert_eq!(weights.tensors["bias"].1.to_f32(), [1.0, -2.0]);
+
+        let mut cx = Graph::new();
+        assert!(weights.bind("q8", cx.tensor((2, 16))).is_ok());
+        assert!(weights.bind("q8", cx.tensor((2, 2))).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        // Q4_K
+        let file = serialize(&[("w", &[256], 12, vec![0; 144])]);
+        assert!(GgufWeights::from_bytes(&file).is_err());
+    }
+
+    #[test]
+    fn test_oversized_tensor() {
+        let file = serialize(&[("w", &[1 << 62, 8], GGML_F32, vec![0; 32])]);
+        assert!(matches!(
+            GgufWeights::from_bytes(&file),
+            Err(LuminairError::WeightsError(_))
+        ));
+        let file = serialize(&[("w", &[u64::MAX / 2], GGML_F32, vec![0; 32])]);
+        assert!(matches!(
+            GgufWeights::from_bytes(&file),
+            Err(LuminairError::WeightsError(_))
+        ));
+    }
+
+    #[test]
+    fn test_not_gguf() {
+        assert!(GgufWeights::from_bytes(b"GGML\0\0\0\0").is_err());
+    }
+}
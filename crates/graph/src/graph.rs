@@ -1,9 +1,19 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    time::Instant,
+};
+
 use crate::{
+    cost::{component_of, estimate, CostEstimate},
+    data::StwoData,
     op::{
-        prim::{CopyFromStwo, CopyToStwo, LuminairConstant},
-        HasProcessTrace,
+        other::{outputs_f32, runs_on_cpu},
+        prim::{is_unsupported_op, CopyFromStwo, CopyToStwo, LuminairConstant},
+        HasProcessTrace, REFERENCE_KEY,
     },
-    utils::compute_padded_range_from_srcs,
+    precision::{OpPrecision, PrecisionReport},
+    utils::{compute_padded_range_from_srcs, op_name},
 };
 use luminair_air::{
     components::{
@@ -19,12 +29,15 @@ use luminair_air::{
         sqrt::table::{SqrtColumn, SqrtTraceTable},
         sum_reduce::table::{SumReduceColumn, SumReduceTraceTable},
     },
+    fixed::find_overflow,
     pie::{
-        ExecutionResources, InputInfo, LuminairPie, NodeInfo, OpCounter, OutputInfo, TraceTable,
+        ExecutionResources, InputInfo, LuminairPie, NodeInfo, NodeTiming, OpCounter, OutputInfo,
+        TraceTable,
     },
     preprocessed::{LookupLayout, Range},
     settings::CircuitSettings,
     utils::calculate_log_size,
+    DEFAULT_FP_SCALE,
 };
 use luminair_utils::LuminairError;
 use luminal::{
@@ -43,6 +56,22 @@ pub trait LuminairGraph {
 
     /// Generates an execution trace for the graph's computation.
     fn gen_trace(&mut self, settings: &mut CircuitSettings) -> Result<LuminairPie, LuminairError>;
+
+    /// Measures the fixed-point error of every operator against an `f64` reference execution.
+    fn validate_precision(&mut self) -> PrecisionReport;
+
+    /// Lists the nodes of the compiled graph that can't be executed with Stwo.
+    fn unsupported_ops(&self) -> Vec<(NodeIndex, String)>;
+
+    /// Fails with every unsupported node at once, before anything is executed.
+    ///
+    /// Run by `gen_trace`, and by `gen_circuit_settings` which panics instead.
+    fn check_supported_ops(&self) -> Result<(), LuminairError>;
+
+    /// Predicts the trace size of each AIR component from the graph's shapes.
+    ///
+    /// The `sin_lookup` table depends on the values fed to `sin` and is not included.
+    fn estimate_cost(&self) -> CostEstimate;
 }
 
 /// Implementation of `LuminairGraph` for the `luminal::Graph` struct.
@@ -52,11 +81,14 @@ impl LuminairGraph for Graph {
     /// Runs a pass over the graph to identify the range of values used
     /// by lookup-based operations (like `sin`).
     /// This information is crucial for constructing the preprocessed trace later.
+    ///
+    /// Panics with the list of unsupported operators if there are any, since they can't run.
     fn gen_circuit_settings(&mut self) -> CircuitSettings {
-        // Track the number of views pointing to each tensor so we know when to clear
-        if self.linearized_graph.is_none() {
-            self.toposort();
+        if let Err(e) = self.check_supported_ops() {
+            panic!("{}", e);
         }
+        // Track the number of views pointing to each tensor so we know when to clear
+        schedule(self);
         let mut consumers = self.consumers_map.as_ref().unwrap().clone();
         let mut dim_stack = Vec::new();
 
@@ -105,6 +137,7 @@ impl LuminairGraph for Graph {
 
         CircuitSettings {
             lookups: Lookups { sin: sin_lookup },
+            fp_scale: DEFAULT_FP_SCALE,
         }
     }
 
@@ -114,19 +147,25 @@ impl LuminairGraph for Graph {
     /// and intermediate values for each supported AIR operation (e.g., add, mul, sin).
     /// It populates specific trace tables for each operation type and gathers
     /// metadata about the graph structure and execution flow.
+    /// Operators left on the CPU, with `CpuPartitionCompiler`, `ops.cpu` or `ops.cpu_fallback`,
+    /// are listed in `ExecutionResources::unproven_ops`. Any other operator without an AIR
+    /// component fails the trace with `UnsupportedOps`, before anything is executed.
     ///
     /// Returns a `LuminairPie` containing all the trace tables and execution resources.
     fn gen_trace(&mut self, settings: &mut CircuitSettings) -> Result<LuminairPie, LuminairError> {
+        self.check_supported_ops()?;
+
         // Track the number of views pointing to each tensor so we know when to clear
-        if self.linearized_graph.is_none() {
-            self.toposort();
-        }
+        schedule(self);
 
         let mut consumers = self.consumers_map.as_ref().unwrap().clone();
         let mut dim_stack = Vec::new();
 
         // Initializes operator counter
         let mut op_counter = OpCounter::default();
+        let mut unproven_ops = Vec::new();
+        let mut node_timings = Vec::new();
+        let trace_start = Instant::now();
 
         // Initializes table for each operator
         let mut add_table = AddTraceTable::new();
@@ -196,36 +235,31 @@ impl LuminairGraph for Graph {
                             .is::<CopyFromStwo>()
                 });
 
-            // Calculate expansion-adjusted consumer count
+            // Count the reads of each output element by the proven consumers: broadcast
+            // elements are read several times, sliced or padded ones may not be read
             let base_consumers = *consumers.get(&(*node, 0)).unwrap_or(&0);
-            let mut expansion_adjusted_consumers = 0u32;
-
+            let mut consumer_counts = Vec::new();
             if base_consumers > 0 {
-                // Iterate through each consumer edge to calculate expansion factors
                 for edge in self
                     .graph
                     .edges_directed(*node, petgraph::Direction::Outgoing)
+                    .filter(|e| component_of(self, e.target()).is_some())
                 {
-                    if let Some((_, _, shape)) = edge.weight().as_data() {
-                        // Calculate expansion factor for this consumer based on fake dimensions
-                        let expansion_factor: u32 = (0..shape.len())
-                            .map(|i| {
-                                let dim_index = shape.indexes[i];
-                                if shape.fake[dim_index] {
-                                    // This dimension is fake (expanded), so count its size
-                                    shape.dims[dim_index].to_usize().unwrap_or(1) as u32
-                                } else {
-                                    // This dimension is real, contributes factor of 1
-                                    1
-                                }
-                            })
-                            .product();
-
-                        expansion_adjusted_consumers += expansion_factor;
+                    if let Some((_, _, mut shape)) = edge.weight().as_data() {
+                        shape.resolve_global_dyn_dims_stack(&self.dyn_map, &mut dim_stack);
+                        let (ind, val) = (shape.index_expression(), shape.valid_expression());
+                        for i in 0..shape.n_elements().to_usize().unwrap() {
+                            if val.exec_single_var_stack(i, &mut dim_stack) == 0 {
+                                continue;
+                            }
+                            let index = ind.exec_single_var_stack(i, &mut dim_stack);
+                            if index >= consumer_counts.len() {
+                                consumer_counts.resize(index + 1, 0);
+                            }
+                            consumer_counts[index] += 1;
+                        }
                     }
                 }
-            } else {
-                expansion_adjusted_consumers = base_consumers as u32;
             }
 
             let node_info = NodeInfo {
@@ -233,11 +267,13 @@ impl LuminairGraph for Graph {
                 output: OutputInfo {
                     is_final_output: is_direct_output || is_output_via_copy,
                 },
-                num_consumers: expansion_adjusted_consumers,
+                num_consumers: consumer_counts.iter().copied().max().unwrap_or(0),
+                consumer_counts,
                 id: node.index() as u32,
             };
 
             // Get operator and dispatch to appropriate process_trace handler
+            let node_start = Instant::now();
             let node_op = &mut *self.graph.node_weight_mut(*node).unwrap();
 
             let tensors =
@@ -353,6 +389,33 @@ impl LuminairGraph for Graph {
                     }
                     _ => node_op.process(srcs),
                 };
+            node_timings.push(NodeTiming {
+                node: node.index() as u32,
+                op: op_name(self.node_weight(*node).unwrap().as_ref()),
+                start_us: (node_start - trace_start).as_micros() as u64,
+                duration_us: node_start.elapsed().as_micros() as u64,
+            });
+
+            // Ops without an AIR component fall back to luminal's CPU backend
+            if runs_on_cpu(self, *node) {
+                let op = op_name(self.node_weight(*node).unwrap().as_ref());
+                tracing::warn!("{} is executed on the CPU and is not proven", op);
+                unproven_ops.push(op);
+            }
+
+            // Values beyond the M31 range would silently wrap in the trace
+            for tensor in &tensors {
+                if let Some(data) = tensor.downcast_ref::<StwoData>() {
+                    if let Some(index) = find_overflow(&data.0) {
+                        return Err(LuminairError::FixedPointOverflow {
+                            op: format!("{:?}", self.node_weight(*node).unwrap()),
+                            node: node.index() as u32,
+                            index,
+                            value: data.0[index].to_f64(),
+                        });
+                    }
+                }
+            }
 
             // Store output tensors
             for (i, tensor) in tensors.into_iter().enumerate() {
@@ -418,9 +481,153 @@ impl LuminairGraph for Graph {
             execution_resources: ExecutionResources {
                 op_counter,
                 max_log_size,
+                unproven_ops,
+                node_timings,
             },
         })
     }
+
+    /// Lists the Luminal primitives left in the graph that `PrimitiveCompiler` couldn't lower,
+    /// and that `BoundaryCompiler` didn't move to the CPU: those reading `StwoData`, directly
+    /// or through another unsupported primitive.
+    fn unsupported_ops(&self) -> Vec<(NodeIndex, String)> {
+        let mut unsupported = HashSet::new();
+        for n in petgraph::algo::toposort(&self.graph, None).unwrap_or_default() {
+            let op = self.graph.node_weight(n).unwrap();
+            if is_unsupported_op(op.as_ref())
+                && self
+                    .get_sources(n)
+                    .iter()
+                    .any(|(src, _, _)| !outputs_f32(self, *src) || unsupported.contains(src))
+            {
+                unsupported.insert(n);
+            }
+        }
+        let mut nodes = unsupported
+            .into_iter()
+            .map(|n| (n, format!("{:?}", self.graph.node_weight(n).unwrap())))
+            .collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|(n, _)| *n);
+        nodes
+    }
+
+    /// Returns `UnsupportedOps` listing all unsupported nodes, so they can be fixed in one go
+    /// instead of failing on the first one during execution.
+    fn check_supported_ops(&self) -> Result<(), LuminairError> {
+        let unsupported = self.unsupported_ops();
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+        Err(LuminairError::UnsupportedOps(
+            unsupported
+                .into_iter()
+                .map(|(n, op)| format!("{} (node {})", op, n.index()))
+                .collect(),
+        ))
+    }
+
+    /// Runs without executing the graph, so it can be used to compare candidate graphs
+    /// before paying for trace generation.
+    fn estimate_cost(&self) -> CostEstimate {
+        estimate(self)
+    }
+
+    /// Runs the graph while recomputing each operator in `f64` from reference inputs.
+    ///
+    /// Graph inputs are taken before fixed-point conversion, so the reported errors
+    /// include input rounding. Operators without a reference implementation are
+    /// skipped and their fixed-point output is used as reference downstream.
+    fn validate_precision(&mut self) -> PrecisionReport {
+        schedule(self);
+        let mut consumers = self.consumers_map.as_ref().unwrap().clone();
+        let mut dim_stack = Vec::new();
+
+        let mut references: HashMap<(NodeIndex, u8), Vec<f64>> = HashMap::new();
+        let mut report = PrecisionReport::default();
+
+        for (node, src_ids) in self.linearized_graph.as_ref().unwrap() {
+            if self.tensors.contains_key(&(*node, 0)) {
+                continue;
+            }
+
+            let mut srcs =
+                get_source_tensors(&self.no_delete, &mut self.tensors, src_ids, &consumers);
+
+            // Substitute in the dyn dims
+            for (_, st) in srcs.iter_mut() {
+                st.resolve_global_dyn_dims_stack(&self.dyn_map, &mut dim_stack);
+            }
+
+            // Reference inputs, falling back to the actual values when unknown
+            let reference_inputs = src_ids
+                .iter()
+                .zip(srcs.iter())
+                .map(|((id, ind, _), (tensor, st))| {
+                    let values = references
+                        .get(&(*id, *ind))
+                        .cloned()
+                        .or_else(|| tensor_values(tensor.borrowed()))
+                        .unwrap_or_default();
+                    (values, *st)
+                })
+                .collect::<Vec<_>>();
+
+            let node_op = &mut **self.graph.node_weight_mut(*node).unwrap();
+            let reference =
+                if node_op.as_any().is::<CopyToStwo>() || node_op.as_any().is::<CopyFromStwo>() {
+                    reference_inputs
+                        .into_iter()
+                        .next()
+                        .map(|(values, _)| values)
+                } else if let Some(constant) = node_op.as_any().downcast_ref::<LuminairConstant>() {
+                    match constant.value {
+                        ConstantValue::Float(f) => Some(vec![f as f64]),
+                        ConstantValue::Expression(_) => None,
+                    }
+                } else {
+                    node_op
+                        .custom(REFERENCE_KEY, Box::new(reference_inputs))
+                        .and_then(|out| out.downcast::<Vec<f64>>().ok())
+                        .map(|out| *out)
+                };
+
+            // Execute
+            let tensors = node_op.process(srcs);
+            let op = format!("{:?}", node_op);
+
+            if let Some(output) = tensors.first() {
+                let is_fixed = output.downcast_ref::<StwoData>().is_some();
+                let values = tensor_values(output).unwrap_or_default();
+                let reference = match reference {
+                    Some(reference) if reference.len() == values.len() => {
+                        if is_fixed {
+                            report.ops.push(OpPrecision::measure(
+                                node.index() as u32,
+                                op,
+                                &values,
+                                &reference,
+                            ));
+                        }
+                        reference
+                    }
+                    _ => values,
+                };
+                references.insert((*node, 0), reference);
+            }
+
+            for (i, tensor) in tensors.into_iter().enumerate() {
+                self.tensors.insert((*node, i as u8), tensor);
+            }
+
+            // Bookkeep remaining consumers
+            for (id, ind, _) in src_ids {
+                *consumers.get_mut(&(*id, *ind)).unwrap() -= 1;
+            }
+        }
+
+        self.reset();
+        report
+    }
 }
 
 /// Merges overlapping or adjacent ranges into a minimal set of disjoint ranges.
@@ -454,3 +661,61 @@ fn coalesce_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
     result.push(current_range);
     result
 }
+
+/// Reads the values of a `StwoData` or `Vec<f32>` tensor as `f64`.
+fn tensor_values(tensor: &Tensor) -> Option<Vec<f64>> {
+    if let Some(data) = tensor.downcast_ref::<StwoData>() {
+        Some(data.0.iter().map(|v| v.to_f64()).collect())
+    } else {
+        tensor
+            .downcast_ref::<Vec<f32>>()
+            .map(|data| data.iter().map(|&v| v as f64).collect())
+    }
+}
+
+/// Orders the execution of `graph` deterministically.
+///
+/// Among the nodes ready to run, the one with the lowest index always runs first, so the
+/// same graph always yields the same trace rows, commitments and proof, whatever order
+/// the topological sort returned.
+fn schedule(graph: &mut Graph) {
+    if graph.linearized_graph.is_none() {
+        graph.toposort();
+    }
+
+    let mut in_degree = graph
+        .node_indices()
+        .map(|n| {
+            let degree = graph
+                .edges_directed(n, petgraph::Direction::Incoming)
+                .count();
+            (n, degree)
+        })
+        .collect::<HashMap<_, _>>();
+    let mut ready = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(n, _)| Reverse(*n))
+        .collect::<BinaryHeap<_>>();
+
+    let mut position = HashMap::new();
+    while let Some(Reverse(node)) = ready.pop() {
+        position.insert(node, position.len());
+        for dest in graph
+            .edges_directed(node, petgraph::Direction::Outgoing)
+            .map(|e| e.target())
+        {
+            let degree = in_degree.get_mut(&dest).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push(Reverse(dest));
+            }
+        }
+    }
+
+    graph
+        .linearized_graph
+        .as_mut()
+        .unwrap()
+        .sort_by_key(|(n, _)| position[n]);
+}
@@ -1,15 +1,79 @@
+pub mod batch;
+pub mod bench;
+pub mod compress;
+pub mod config;
+pub mod cost;
 pub mod data;
+pub mod description;
+pub mod doctor;
+pub mod export;
+pub mod gguf;
 pub mod graph;
+pub mod metrics;
+pub mod models;
+pub mod npy;
+pub mod onnx;
 pub mod op;
+pub mod precision;
+pub mod profile;
+pub mod quant;
+pub mod report;
+#[cfg(feature = "serve")]
+pub mod serve;
 pub mod settings;
+pub mod sparse;
 pub mod utils;
+pub mod weights;
 
 #[cfg(test)]
 mod tests;
 
+pub use op::{
+    other::{
+        BoundaryCompiler, CSECompiler, ConstantFoldingCompiler, CopyCompiler, CpuPartitionCompiler,
+        DeadCodeCompiler, RematerializeCompiler, ValidationCompiler, ViewFusionCompiler,
+        WeightsCompiler,
+    },
+    prim::{PrimitiveCompiler, ReduceFusionCompiler},
+};
+
+/// Folding stage of the `StwoCompiler` pipeline.
+///
+/// Removes the operators not contributing to an output and evaluates constant subexpressions.
+pub type StwoFolding = (DeadCodeCompiler, ConstantFoldingCompiler);
+
+/// Lowering stage of the `StwoCompiler` pipeline.
+///
+/// Folds views into their consumers, replaces supported operators with their LuminAIR
+/// equivalents, and inserts copies around the operators left on the CPU.
+pub type StwoLowering = (ViewFusionCompiler, PrimitiveCompiler, BoundaryCompiler);
+
+/// Optimization stage of the `StwoCompiler` pipeline.
+///
+/// Fuses chained reductions, merges duplicated LuminAIR operators and removes redundant copies.
+pub type StwoOptimization = (ReduceFusionCompiler, CSECompiler, CopyCompiler);
+
 /// Type alias for the Stwo compiler used in LuminAIR.
 ///
 /// Represents the collection of compilers needed to transform a computation graph
 /// defined in LuminAIR into an AIR format compatible with the STWO prover.
-/// It bundles primitive operations and copy constraints compilers.
-pub type StwoCompiler = (op::prim::PrimitiveCompiler, op::other::CopyCompiler);
+/// It runs the `StwoFolding`, `StwoLowering` and `StwoOptimization` stages in order.
+///
+/// Custom passes can be inserted between the stages by compiling them as a tuple,
+/// e.g. `(StwoFolding, MyPass, StwoLowering, StwoOptimization, ValidationCompiler)`.
+/// `ValidationCompiler` checks the resulting graph is still executable.
+pub type StwoCompiler = (StwoFolding, StwoLowering, StwoOptimization);
+
+/// Builds the `StwoCompiler` pipeline for `config`, instead of the configuration
+/// `luminair.toml` holds.
+pub fn stwo_compiler(config: &ProjectConfig) -> StwoCompiler {
+    (
+        StwoFolding::default(),
+        (
+            ViewFusionCompiler::default(),
+            PrimitiveCompiler::new(config),
+            BoundaryCompiler::new(config),
+        ),
+        StwoOptimization::default(),
+    )
+}
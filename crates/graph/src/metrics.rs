@@ -0,0 +1,275 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use luminair_air::{
+    pie::{LuminairPie, TraceTable},
+    settings::CircuitSettings,
+};
+use luminair_prover::{prover::prove, LuminairProof};
+use luminair_utils::LuminairError;
+use luminair_verifier::verifier::verify;
+use luminal::prelude::*;
+use stwo_prover::core::vcs::blake2_merkle::Blake2sMerkleHasher;
+
+use crate::graph::LuminairGraph;
+
+/// Upper bounds of the duration histogram buckets, in seconds.
+pub const DURATION_BUCKETS: [f64; 10] = [0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// Prometheus metrics of the graphs executed, proven and verified by a long-lived process.
+///
+/// Nothing is recorded unless the pipeline runs through these methods, which time each
+/// stage and count its outcome. Render the metrics with `render`, e.g. from a `/metrics`
+/// endpoint. Thread-safe, so a single instance can be shared by all workers.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    executions: Counters,
+    operators: Counters,
+    trace_rows: Counters,
+    unproven_ops: u64,
+    proofs: Counters,
+    verifications: Counters,
+    trace_generation: Histogram,
+    proving: Histogram,
+    verification: Histogram,
+}
+
+/// Counters by label value.
+type Counters = BTreeMap<&'static str, u64>;
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generates the trace of `graph`, recording the execution, its duration and trace rows.
+    pub fn gen_trace(
+        &self,
+        graph: &mut Graph,
+        settings: &mut CircuitSettings,
+    ) -> Result<LuminairPie, LuminairError> {
+        let start = Instant::now();
+        let result = graph.gen_trace(settings);
+        let elapsed = start.elapsed();
+
+        let mut inner = self.inner.lock().unwrap();
+        *inner.executions.entry(outcome(&result)).or_default() += 1;
+        let Ok(pie) = &result else {
+            return result;
+        };
+        inner.trace_generation.observe(elapsed);
+
+        let counter = &pie.execution_resources.op_counter;
+        for (op, count) in [
+            ("add", counter.add),
+            ("mul", counter.mul),
+            ("recip", counter.recip),
+            ("sin", counter.sin),
+            ("sum_reduce", counter.sum_reduce),
+            ("max_reduce", counter.max_reduce),
+            ("sqrt", counter.sqrt),
+        ] {
+            *inner.operators.entry(op).or_default() += count as u64;
+        }
+        for table in &pie.trace_tables {
+            let (component, rows) = match table {
+                TraceTable::Add { table } => ("add", table.table.len()),
+                TraceTable::Mul { table } => ("mul", table.table.len()),
+                TraceTable::Recip { table } => ("recip", table.table.len()),
+                TraceTable::Sin { table } => ("sin", table.table.len()),
+                TraceTable::SinLookup { table } => ("sin_lookup", table.table.len()),
+                TraceTable::SumReduce { table } => ("sum_reduce", table.table.len()),
+                TraceTable::MaxReduce { table } => ("max_reduce", table.table.len()),
+                TraceTable::Sqrt { table } => ("sqrt", table.table.len()),
+            };
+            *inner.trace_rows.entry(component).or_default() += rows as u64;
+        }
+        inner.unproven_ops += pie.execution_resources.unproven_ops.len() as u64;
+        drop(inner);
+        result
+    }
+
+    /// Proves a trace, recording the outcome and proving time.
+    pub fn prove(
+        &self,
+        pie: LuminairPie,
+        settings: CircuitSettings,
+    ) -> Result<LuminairProof<Blake2sMerkleHasher>, LuminairError> {
+        let start = Instant::now();
+        let result = prove(pie, settings);
+        let elapsed = start.elapsed();
+
+        let mut inner = self.inner.lock().unwrap();
+        *inner.proofs.entry(outcome(&result)).or_default() += 1;
+        if result.is_ok() {
+            inner.proving.observe(elapsed);
+        }
+        result
+    }
+
+    /// Verifies a proof, recording the outcome and verification time.
+    pub fn verify(
+        &self,
+        proof: LuminairProof<Blake2sMerkleHasher>,
+        settings: CircuitSettings,
+    ) -> Result<(), LuminairError> {
+        let start = Instant::now();
+        let result = verify(proof, settings);
+        let elapsed = start.elapsed();
+
+        let mut inner = self.inner.lock().unwrap();
+        *inner.verifications.entry(outcome(&result)).or_default() += 1;
+        inner.verification.observe(elapsed);
+        result
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+        counters(
+            &mut out,
+            "luminair_executions_total",
+            "Trace generations, by result.",
+            "result",
+            &inner.executions,
+        );
+        counters(
+            &mut out,
+            "luminair_operators_total",
+            "Proven operators executed, by component.",
+            "component",
+            &inner.operators,
+        );
+        counters(
+            &mut out,
+            "luminair_trace_rows_total",
+            "Trace rows generated, by component.",
+            "component",
+            &inner.trace_rows,
+        );
+        writeln!(
+            out,
+            "# HELP luminair_unproven_ops_total Operators executed on the CPU without being proven.\n\
+             # TYPE luminair_unproven_ops_total counter\n\
+             luminair_unproven_ops_total {}",
+            inner.unproven_ops
+        )
+        .unwrap();
+        counters(
+            &mut out,
+            "luminair_proofs_total",
+            "Proof generations, by result.",
+            "result",
+            &inner.proofs,
+        );
+        counters(
+            &mut out,
+            "luminair_verifications_total",
+            "Proof verifications, by result.",
+            "result",
+            &inner.verifications,
+        );
+        histogram(
+            &mut out,
+            "luminair_trace_generation_seconds",
+            "Duration of successful trace generations.",
+            &inner.trace_generation,
+        );
+        histogram(
+            &mut out,
+            "luminair_proving_seconds",
+            "Duration of successful proof generations.",
+            &inner.proving,
+        );
+        histogram(
+            &mut out,
+            "luminair_verification_seconds",
+            "Duration of proof verifications.",
+            &inner.verification,
+        );
+        out
+    }
+}
+
+fn outcome<T>(result: &Result<T, LuminairError>) -> &'static str {
+    if result.is_ok() {
+        "ok"
+    } else {
+        "error"
+    }
+}
+
+fn counters(out: &mut String, name: &str, help: &str, label: &str, values: &Counters) {
+    writeln!(out, "# HELP {} {}\n# TYPE {} counter", name, help, name).unwrap();
+    for (value, count) in values {
+        writeln!(out, "{}{{{}=\"{}\"}} {}", name, label, value, count).unwrap();
+    }
+}
+
+fn histogram(out: &mut String, name: &str, help: &str, histogram: &Histogram) {
+    writeln!(out, "# HELP {} {}\n# TYPE {} histogram", name, help, name).unwrap();
+    for (bound, count) in DURATION_BUCKETS.iter().zip(histogram.buckets) {
+        writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count).unwrap();
+    }
+    writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count).unwrap();
+    writeln!(out, "{}_sum {}", name, histogram.sum).unwrap();
+    writeln!(out, "{}_count {}", name, histogram.count).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StwoCompiler;
+
+    #[test]
+    fn test_metrics_render() {
+        let metrics = Metrics::new();
+        let mut cx = Graph::new();
+        let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+        let mut b = (a * a + a).retrieve();
+        cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+
+        let mut settings = cx.gen_circuit_settings();
+        let pie = metrics.gen_trace(&mut cx, &mut settings).unwrap();
+        let proof = metrics.prove(pie, settings.clone()).unwrap();
+        metrics.verify(proof, settings).unwrap();
+
+        let rendered = metrics.render();
+        assert!(rendered.contains("luminair_executions_total{result=\"ok\"} 1\n"));
+        assert!(rendered.contains("luminair_operators_total{component=\"mul\"} 1\n"));
+        assert!(rendered.contains("luminair_trace_rows_total{component=\"add\"} 4\n"));
+        assert!(rendered.contains("luminair_proofs_total{result=\"ok\"} 1\n"));
+        assert!(rendered.contains("luminair_proving_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(rendered.contains("# TYPE luminair_verification_seconds histogram\n"));
+    }
+}
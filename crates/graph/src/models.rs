@@ -0,0 +1,273 @@
+use luminal::prelude::*;
+
+/// A ready-made model added to a graph.
+///
+/// All models use ReLU, whose `LessThan` comparison has no AIR component: it only runs,
+/// unproven on the CPU, with `ops.cpu_fallback` enabled, and is then listed in the proof's
+/// `unproven_ops`. Without it, trace generation fails with `UnsupportedOps`.
+///
+/// Weights are initialized deterministically so the model runs as is. Their names
+/// follow the usual `<layer>.weight` / `<layer>.bias` convention, so trained values can
+/// be bound with `SafetensorsWeights::bind_all(model.weights.iter().map(..))`.
+#[derive(Debug)]
+pub struct ExampleModel {
+    /// Input tensor, to be set before execution.
+    pub input: GraphTensor,
+    /// Retrieved output tensor.
+    pub output: GraphTensor,
+    /// Weight tensors, by name, in creation order.
+    pub weights: Vec<(String, GraphTensor)>,
+}
+
+/// Multi-layer perceptron classifier over `batch` samples.
+///
+/// `dims` lists the layer widths from input features to classes, e.g. `[784, 128, 10]`.
+/// Hidden layers use ReLU, whose comparison runs unproven; the output is the logits, of
+/// shape `(batch, classes)`.
+pub fn mlp_classifier(cx: &mut Graph, batch: usize, dims: &[usize]) -> ExampleModel {
+    assert!(
+        dims.len() >= 2,
+        "An MLP needs at least input and output widths"
+    );
+    let mut weights = Weights::default();
+
+    let input = cx.named_tensor("input", (batch, dims[0]));
+    let mut x = input;
+    for (i, w) in dims.windows(2).enumerate() {
+        x = weights.linear(cx, &format!("fc{}", i), x, batch, w[0], w[1]);
+        if i + 2 < dims.len() {
+            x = x.relu();
+        }
+    }
+
+    ExampleModel {
+        input,
+        output: x.retrieve(),
+        weights: weights.0,
+    }
+}
+
+/// Small convolutional classifier over a `(channels, height, width)` image.
+///
+/// A `kernel x kernel` valid convolution with `filters` output channels and ReLU, run
+/// unproven, then global average pooling and a linear layer to `classes` logits, of shape
+/// `(1, classes)`.
+/// The convolution is computed as a sum of shifted matrix products, one per kernel offset,
+/// with its weight stored as `(kernel * kernel, filters, channels)`.
+pub fn small_cnn(
+    cx: &mut Graph,
+    (channels, height, width): (usize, usize, usize),
+    filters: usize,
+    kernel: usize,
+    classes: usize,
+) -> ExampleModel {
+    assert!(
+        kernel <= height && kernel <= width,
+        "Kernel larger than the image"
+    );
+    let mut weights = Weights::default();
+    let (out_h, out_w) = (height - kernel + 1, width - kernel + 1);
+
+    let input = cx.named_tensor("input", (channels, height, width));
+    let conv_w = weights.add(
+        cx,
+        "conv.weight",
+        &[kernel * kernel, filters, channels],
+        channels * kernel * kernel,
+    );
+    let conv_b = weights.add(cx, "conv.bias", &[filters, 1], channels * kernel * kernel);
+
+    let mut conv = None;
+    for ki in 0..kernel {
+        for kj in 0..kernel {
+            let k = ki * kernel + kj;
+            let patch = input
+                .slice((.., ki..ki + out_h, kj..kj + out_w))
+                .contiguous()
+                .reshape((channels, out_h * out_w));
+            let w = conv_w
+                .slice((k..k + 1, .., ..))
+                .contiguous()
+                .reshape((filters, channels));
+            let term = w.matmul(patch);
+            conv = Some(match conv {
+                Some(acc) => acc + term,
+                None => term,
+            });
+        }
+    }
+    let features = (conv.unwrap() + conv_b.expand_to((filters, out_h * out_w))).relu();
+
+    // Global average pooling, as a single row of features
+    let pooled = features.mean_reduce(1).contiguous().reshape((1, filters));
+    let logits = weights.linear(cx, "fc", pooled, 1, filters, classes);
+
+    ExampleModel {
+        input,
+        output: logits.retrieve(),
+        weights: weights.0,
+    }
+}
+
+/// Single-head transformer encoder block over a `(seq_len, d_model)` sequence.
+///
+/// Post-norm self-attention followed by a ReLU feed-forward layer of width `d_ff`,
+/// each with a residual connection. Softmax and layer norm exponentials and divisions, and
+/// the ReLU comparison, have no AIR component and run unproven on the CPU, see `ExampleModel`.
+pub fn transformer_block(
+    cx: &mut Graph,
+    seq_len: usize,
+    d_model: usize,
+    d_ff: usize,
+) -> ExampleModel {
+    let mut weights = Weights::default();
+
+    let input = cx.named_tensor("input", (seq_len, d_model));
+    let mut proj = |cx: &mut Graph, name: &str, x: GraphTensor| {
+        let w = weights.add(cx, name, &[d_model, d_model], d_model);
+        x.matmul(w)
+    };
+    let q = proj(cx, "attn.q.weight", input);
+    let k = proj(cx, "attn.k.weight", input);
+    let v = proj(cx, "attn.v.weight", input);
+
+    let scores = q.matmul(k.permute((1, 0))) * (1.0 / (d_model as f32).sqrt());
+    let attention = scores.softmax(1).matmul(v);
+    let attention = proj(cx, "attn.out.weight", attention);
+    let h = (input + attention).layer_norm(1, 1e-5);
+
+    let ff = weights.linear(cx, "ff.0", h, seq_len, d_model, d_ff).relu();
+    let ff = weights.linear(cx, "ff.1", ff, seq_len, d_ff, d_model);
+    let output = (h + ff).layer_norm(1, 1e-5);
+
+    ExampleModel {
+        input,
+        output: output.retrieve(),
+        weights: weights.0,
+    }
+}
+
+/// Weights of a model being built, in creation order.
+#[derive(Default)]
+struct Weights(Vec<(String, GraphTensor)>);
+
+impl Weights {
+    /// Adds a weight tensor initialized uniformly in `±1/sqrt(fan_in)`.
+    fn add(&mut self, cx: &mut Graph, name: &str, shape: &[usize], fan_in: usize) -> GraphTensor {
+        let len = shape.iter().product();
+        let bound = 1.0 / (fan_in as f32).sqrt();
+        let dims = shape
+            .iter()
+            .map(|&d| Expression::from(d))
+            .collect::<Vec<_>>();
+        let tensor = cx
+            .named_tensor(name, dims)
+            .set(init(len, self.0.len() as u64, bound));
+        self.0.push((name.to_string(), tensor));
+        tensor
+    }
+
+    /// `x @ weight + bias`, with `weight` of shape `(inputs, outputs)`.
+    fn linear(
+        &mut self,
+        cx: &mut Graph,
+        name: &str,
+        x: GraphTensor,
+        rows: usize,
+        inputs: usize,
+        outputs: usize,
+    ) -> GraphTensor {
+        let w = self.add(cx, &format!("{}.weight", name), &[inputs, outputs], inputs);
+        let b = self.add(cx, &format!("{}.bias", name), &[1, outputs], inputs);
+        x.matmul(w) + b.expand_to((rows, outputs))
+    }
+}
+
+/// Deterministic values uniformly spread in `[-bound, bound)`.
+fn init(len: usize, seed: u64, bound: f32) -> Vec<f32> {
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    (0..len)
+        .map(|_| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            ((state >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0) * bound
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::ProjectConfig, graph::LuminairGraph, stwo_compiler, StwoCompiler};
+    use luminair_prover::prover::prove;
+    use luminair_verifier::verifier::verify;
+
+    fn input_data(len: usize) -> Vec<f32> {
+        (0..len).map(|i| (i % 7) as f32 / 7.0 - 0.5).collect()
+    }
+
+    /// The models use activations without an AIR component, run unproven on the CPU.
+    fn compiler() -> (GenericCompiler, StwoCompiler) {
+        let config = ProjectConfig::from_toml("[ops]\ncpu_fallback = true\n").unwrap();
+        (GenericCompiler::default(), stwo_compiler(&config))
+    }
+
+    #[test]
+    fn test_mlp_classifier_proves() {
+        let mut cx = Graph::new();
+        let mut model = mlp_classifier(&mut cx, 2, &[8, 16, 4]);
+        model.input.set(input_data(16));
+        assert_eq!(model.weights.len(), 4);
+
+        cx.compile(compiler(), &mut model.output);
+        let mut settings = cx.gen_circuit_settings();
+        let trace = cx
+            .gen_trace(&mut settings)
+            .expect("Trace generation failed");
+        // Only the ReLU comparison of the hidden layer is left unproven
+        let unproven = trace.execution_resources.unproven_ops.clone();
+        assert!(!unproven.is_empty());
+        assert!(unproven.iter().all(|op| op.contains("LessThan")));
+
+        let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+        assert_eq!(proof.claim.unproven_ops, unproven);
+        assert!(!proof.claim.is_fully_proven());
+        verify(proof, settings).expect("Proof verification failed");
+        assert_eq!(model.output.data().len(), 8);
+    }
+
+    #[test]
+    fn test_small_cnn_runs() {
+        let mut cx = Graph::new();
+        let mut model = small_cnn(&mut cx, (2, 6, 6), 3, 3, 4);
+        model.input.set(input_data(2 * 6 * 6));
+
+        cx.compile(compiler(), &mut model.output);
+        cx.execute();
+        let logits = model.output.data();
+        assert_eq!(logits.len(), 4);
+        assert!(logits.iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn test_transformer_block_runs() {
+        let mut cx = Graph::new();
+        let mut model = transformer_block(&mut cx, 4, 8, 16);
+        model.input.set(input_data(4 * 8));
+
+        cx.compile(compiler(), &mut model.output);
+        cx.execute();
+        let output = model.output.data();
+        assert_eq!(output.len(), 4 * 8);
+        assert!(output.iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn test_init_bounds() {
+        let values = init(1000, 3, 0.25);
+        assert!(values.iter().all(|v| (-0.25..0.25).contains(v)));
+        assert_ne!(values, init(1000, 4, 0.25));
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, Write},
+    path::Path,
+};
+
+use luminair_utils::LuminairError;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// A dense array read from or written to NumPy's `.npy` format.
+///
+/// Values are held as `f32`, the type LuminAIR graphs are fed with and return.
+/// Reading accepts little-endian `f4`, `f8`, `i4`, `i8` and boolean arrays in C order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NpyArray {
+    /// Dimensions of the array.
+    pub shape: Vec<usize>,
+    /// Values in row-major order.
+    pub data: Vec<f32>,
+}
+
+impl NpyArray {
+    /// Creates an array, checking that `data` matches `shape`.
+    pub fn new(shape: Vec<usize>, data: Vec<f32>) -> Result<Self, LuminairError> {
+        if shape.iter().product::<usize>() != data.len() {
+            return Err(npy_error(format!(
+                "{} values don't match shape {:?}",
+                data.len(),
+                shape
+            )));
+        }
+        Ok(Self { shape, data })
+    }
+
+    // --- .npy ---
+    pub fn from_npy_bytes(bytes: &[u8]) -> Result<Self, LuminairError> {
+        if bytes.len() < 10 || &bytes[..6] != MAGIC {
+            return Err(npy_error("Not a .npy file".to_string()));
+        }
+        let (header_len, header_start) = match bytes[6] {
+            1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+            2 | 3 if bytes.len() >= 12 => (
+                u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+                12,
+            ),
+            v => return Err(npy_error(format!("Unsupported .npy version {}", v))),
+        };
+        let body_start = header_start + header_len;
+        let header = bytes
+            .get(header_start..body_start)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .ok_or_else(|| npy_error("Invalid .npy header".to_string()))?;
+
+        let descr = header_value(header, "descr")
+            .map(|v| v.trim_matches(|c| c == '\'' || c == '"'))
+            .ok_or_else(|| npy_error("Missing 'descr' in .npy header".to_string()))?;
+        if header_value(header, "fortran_order") == Some("True") {
+            return Err(npy_error(
+                "Fortran-ordered arrays are not supported".to_string(),
+            ));
+        }
+        let shape = header_value(header, "shape")
+            .ok_or_else(|| npy_error("Missing 'shape' in .npy header".to_string()))?
+            .trim_matches(|c| c == '(' || c == ')')
+            .split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(|d| d.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| npy_error(format!("Invalid shape in .npy header: {}", e)))?;
+
+        let body = &bytes[body_start..];
+        let data = match descr {
+            "<f4" => decode(body, f32::from_le_bytes),
+            "<f8" => decode(body, |b: [u8; 8]| f64::from_le_bytes(b) as f32),
+            "<i4" => decode(body, |b: [u8; 4]| i32::from_le_bytes(b) as f32),
+            "<i8" => decode(body, |b: [u8; 8]| i64::from_le_bytes(b) as f32),
+            "|b1" => body.iter().map(|&b| (b != 0) as u8 as f32).collect(),
+            d => return Err(npy_error(format!("Unsupported .npy dtype '{}'", d))),
+        };
+
+        Self::new(shape, data)
+    }
+
+    pub fn to_npy_bytes(&self) -> Vec<u8> {
+        let shape = match self.shape.len() {
+            1 => format!("({},)", self.shape[0]),
+            _ => format!(
+                "({})",
+                self.shape
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        let mut header = format!(
+            "{{'descr': '<f4', 'fortran_order': False, 'shape': {}, }}",
+            shape
+        );
+        // Pad so the data starts on a 64-byte boundary, header ends with a newline
+        let unpadded = MAGIC.len() + 4 + header.len() + 1;
+        header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+        header.push('\n');
+
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + header.len() + self.data.len() * 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for v in &self.data {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_npy_file<P: AsRef<Path>>(path: P) -> Result<Self, LuminairError> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| npy_error(format!("Failed to read .npy file: {}", e)))?;
+        Self::from_npy_bytes(&bytes)
+    }
+
+    pub fn to_npy_file<P: AsRef<Path>>(&self, path: P) -> Result<(), LuminairError> {
+        std::fs::write(path, self.to_npy_bytes())
+            .map_err(|e| npy_error(format!("Failed to write .npy file: {}", e)))
+    }
+}
+
+// --- .npz ---
+
+/// Reads every array of a `.npz` archive, keyed by name (without the `.npy` suffix).
+pub fn read_npz<P: AsRef<Path>>(path: P) -> Result<BTreeMap<String, NpyArray>, LuminairError> {
+    let file =
+        File::open(path).map_err(|e| npy_error(format!("Failed to open .npz file: {}", e)))?;
+    read_npz_from(BufReader::new(file))
+}
+
+/// Writes arrays to a `.npz` archive, one `<name>.npy` entry per array.
+pub fn write_npz<'a, P: AsRef<Path>>(
+    path: P,
+    arrays: impl IntoIterator<Item = (&'a str, &'a NpyArray)>,
+) -> Result<(), LuminairError> {
+    let file =
+        File::create(path).map_err(|e| npy_error(format!("Failed to create .npz file: {}", e)))?;
+    let mut writer = BufWriter::new(file);
+    write_npz_to(&mut writer, arrays)?;
+    writer
+        .flush()
+        .map_err(|e| npy_error(format!("Failed to flush writer: {}", e)))
+}
+
+fn read_npz_from<R: Read + Seek>(reader: R) -> Result<BTreeMap<String, NpyArray>, LuminairError> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| npy_error(format!("Invalid .npz archive: {}", e)))?;
+
+    let mut arrays = BTreeMap::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| npy_error(format!("Invalid .npz entry: {}", e)))?;
+        let name = entry.name().trim_end_matches(".npy").to_string();
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| npy_error(format!("Failed to read .npz entry '{}': {}", name, e)))?;
+        arrays.insert(name, NpyArray::from_npy_bytes(&bytes)?);
+    }
+    Ok(arrays)
+}
+
+fn write_npz_to<'a, W: Write + Seek>(
+    writer: W,
+    arrays: impl IntoIterator<Item = (&'a str, &'a NpyArray)>,
+) -> Result<(), LuminairError> {
+    let mut archive = zip::ZipWriter::new(writer);
+    let options = zip::write::SimpleFileOptions::default();
+    for (name, array) in arrays {
+        archive
+            .start_file(format!("{}.npy", name), options)
+            .map_err(|e| npy_error(format!("Failed to write .npz entry '{}': {}", name, e)))?;
+        archive
+            .write_all(&array.to_npy_bytes())
+            .map_err(|e| npy_error(format!("Failed to write .npz entry '{}': {}", name, e)))?;
+    }
+    archive
+        .finish()
+        .map_err(|e| npy_error(format!("Failed to finish .npz archive: {}", e)))?;
+    Ok(())
+}
+
+/// Extracts the raw value of `key` from a `.npy` header dictionary.
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header.find(&format!("'{}':", key))? + key.len() + 3;
+    let rest = header[start..].trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')')? + 1
+    } else {
+        rest.find(',')?
+    };
+    Some(rest[..end].trim())
+}
+
+/// Decodes little-endian values of `N` bytes each.
+fn decode<const N: usize>(bytes: &[u8], f: impl Fn([u8; N]) -> f32) -> Vec<f32> {
+    bytes
+        .chunks_exact(N)
+        .map(|chunk| f(chunk.try_into().unwrap()))
+        .collect()
+}
+
+fn npy_error(msg: String) -> LuminairError {
+    LuminairError::SerializationError(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_npy_round_trip() {
+        let array = NpyArray::new(vec![2, 3], vec![1.0, -2.0, 0.5, 0.0, 3.25, -0.125]).unwrap();
+        let bytes = array.to_npy_bytes();
+
+        // Data must start on a 64-byte boundary
+        assert_eq!((bytes.len() - array.data.len() * 4) % 64, 0);
+        assert_eq!(NpyArray::from_npy_bytes(&bytes).unwrap(), array);
+    }
+
+    #[test]
+    fn test_npy_from_numpy_header() {
+        // Header as written by `np.save` for `np.arange(3, dtype=np.int64)`
+        let mut header = "{'descr': '<i8', 'fortran_order': False, 'shape': (3,), }".to_string();
+        header.push_str(&" ".repeat(128 - 10 - header.len() - 1));
+        header.push('\n');
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for v in 0i64..3 {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+
+        let array = NpyArray::from_npy_bytes(&bytes).unwrap();
+        assert_eq!(array.shape, [3]);
+        assert_eq!(array.data, [0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_npz_round_trip() {
+        let a = NpyArray::new(vec![2], vec![1.0, 2.0]).unwrap();
+        let b = NpyArray::new(vec![1, 1], vec![-4.0]).unwrap();
+
+        let mut buffer = Cursor::new(Vec::new());
+        write_npz_to(&mut buffer, [("a", &a), ("b", &b)]).unwrap();
+        buffer.set_position(0);
+        let arrays = read_npz_from(buffer).unwrap();
+
+        assert_eq!(arrays.len(), 2);
+        assert_eq!(arrays["a"], a);
+        assert_eq!(arrays["b"], b);
+    }
+
+    #[test]
+    fn test_shape_mismatch() {
+        assert!(NpyArray::new(vec![2, 2], vec![1.0]).is_err());
+    }
+}
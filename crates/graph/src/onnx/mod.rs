@@ -0,0 +1,597 @@
+use std::{collections::HashMap, path::Path};
+
+use luminair_utils::LuminairError;
+use luminal::prelude::*;
+
+use proto::{ModelProto, NodeProto, TensorProto};
+
+mod proto;
+
+/// ONNX operators the importer can translate into luminal operations.
+pub const SUPPORTED_ONNX_OPS: &[&str] = &[
+    "Abs",
+    "Add",
+    "Constant",
+    "Cos",
+    "Div",
+    "Exp",
+    "Flatten",
+    "Gemm",
+    "Identity",
+    "Log",
+    "MatMul",
+    "Mul",
+    "Neg",
+    "Reciprocal",
+    "ReduceMax",
+    "ReduceMean",
+    "ReduceSum",
+    "Relu",
+    "Reshape",
+    "Sigmoid",
+    "Sin",
+    "Sqrt",
+    "Sub",
+    "Transpose",
+];
+
+/// An ONNX model imported into a luminal graph.
+///
+/// Initializers are bound to constant input tensors, so the graph only needs its
+/// inputs set before being compiled with `StwoCompiler`. Inputs must have static shapes.
+#[derive(Debug)]
+pub struct OnnxModel {
+    /// Graph inputs not provided by an initializer, in declaration order.
+    pub inputs: Vec<(String, GraphTensor)>,
+    /// Graph outputs, marked as retrieved, in declaration order.
+    pub outputs: Vec<(String, GraphTensor)>,
+}
+
+impl OnnxModel {
+    /// Imports the ONNX model stored in `path` into `cx`.
+    pub fn from_file<P: AsRef<Path>>(cx: &mut Graph, path: P) -> Result<Self, LuminairError> {
+        let bytes = std::fs::read(path).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to read ONNX file: {}", e))
+        })?;
+        Self::from_bytes(cx, &bytes)
+    }
+
+    /// Imports an ONNX model from the bytes of a `.onnx` file into `cx`.
+    pub fn from_bytes(cx: &mut Graph, bytes: &[u8]) -> Result<Self, LuminairError> {
+        let graph = ModelProto::decode(bytes)?.graph;
+
+        let mut unsupported = graph
+            .nodes
+            .iter()
+            .filter(|n| !SUPPORTED_ONNX_OPS.contains(&n.op_type.as_str()))
+            .map(|n| n.op_type.clone())
+            .collect::<Vec<_>>();
+        if !unsupported.is_empty() {
+            unsupported.sort_unstable();
+            unsupported.dedup();
+            return Err(LuminairError::UnsupportedOps(unsupported));
+        }
+
+        let mut importer = Importer {
+            cx,
+            tensors: HashMap::new(),
+            constants: graph
+                .initializers
+                .into_iter()
+                .map(|t| (t.name.clone(), t))
+                .collect(),
+        };
+
+        let mut inputs = Vec::new();
+        for input in graph
+            .inputs
+            .iter()
+            .filter(|i| !importer.constants.contains_key(&i.name))
+        {
+            let shape = input
+                .dims
+                .iter()
+                .map(|d| d.and_then(|d| usize::try_from(d).ok()))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "Input '{}' has a dynamic shape, export the model with static shapes",
+                        input.name
+                    ))
+                })?;
+            let tensor = importer.cx.named_tensor(&input.name, to_shape(&shape));
+            importer.tensors.insert(input.name.clone(), (tensor, shape));
+            inputs.push((input.name.clone(), tensor));
+        }
+
+        for node in &graph.nodes {
+            importer.import_node(node)?;
+        }
+
+        let outputs = graph
+            .outputs
+            .iter()
+            .map(|o| Ok((o.name.clone(), importer.operand(&o.name)?.0.retrieve())))
+            .collect::<Result<Vec<_>, LuminairError>>()?;
+
+        Ok(Self { inputs, outputs })
+    }
+
+    /// Returns the input tensor `name`, if declared.
+    pub fn input(&self, name: &str) -> Option<GraphTensor> {
+        self.inputs.iter().find(|(n, _)| n == name).map(|(_, t)| *t)
+    }
+
+    /// Returns the output tensor `name`, if declared.
+    pub fn output(&self, name: &str) -> Option<GraphTensor> {
+        self.outputs
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, t)| *t)
+    }
+}
+
+/// Translates ONNX nodes, tracking the static shape of every tensor.
+struct Importer<'a> {
+    cx: &'a mut Graph,
+    tensors: HashMap<String, (GraphTensor, Vec<usize>)>,
+    /// Initializers and `Constant` outputs, turned into tensors when first read as data.
+    constants: HashMap<String, TensorProto>,
+}
+
+impl Importer<'_> {
+    fn import_node(&mut self, node: &NodeProto) -> Result<(), LuminairError> {
+        let op = node.op_type.as_str();
+        if op == "Constant" {
+            let mut value = node
+                .attribute("value")
+                .and_then(|a| a.t.clone())
+                .ok_or_else(|| invalid(format!("Constant '{}' has no tensor value", node.name)))?;
+            value.name = output(node)?.to_string();
+            self.constants.insert(value.name.clone(), value);
+            return Ok(());
+        }
+
+        let (a, a_shape) = self.operand(input(node, 0)?)?;
+        let (tensor, shape) = match op {
+            "Add" | "Sub" | "Mul" | "Div" => {
+                let (b, b_shape) = self.operand(input(node, 1)?)?;
+                let shape = broadcast_shape(&a_shape, &b_shape)
+                    .ok_or_else(|| invalid(format!("Can't broadcast inputs of '{}'", node.name)))?;
+                let (a, b) = (expand(a, &a_shape, &shape), expand(b, &b_shape, &shape));
+                let tensor = match op {
+                    "Add" => a + b,
+                    "Sub" => a - b,
+                    "Mul" => a * b,
+                    _ => a / b,
+                };
+                (tensor, shape)
+            }
+            "MatMul" => {
+                let (b, b_shape) = self.operand(input(node, 1)?)?;
+                let shape = matmul_shape(&a_shape, &b_shape).ok_or_else(|| {
+                    invalid(format!("Unsupported MatMul shapes in '{}'", node.name))
+                })?;
+                (a.matmul(b), shape)
+            }
+            "Gemm" => self.gemm(node, (a, a_shape))?,
+            "Identity" => (a, a_shape),
+            "Abs" => (a.abs(), a_shape),
+            "Cos" => (a.cos(), a_shape),
+            "Exp" => (a.exp(), a_shape),
+            "Log" => (a.ln(), a_shape),
+            "Neg" => (-a, a_shape),
+            "Reciprocal" => (a.recip(), a_shape),
+            "Relu" => (a.relu(), a_shape),
+            "Sigmoid" => (a.sigmoid(), a_shape),
+            "Sin" => (a.sin(), a_shape),
+            "Sqrt" => (a.sqrt(), a_shape),
+            "ReduceSum" | "ReduceMax" | "ReduceMean" => self.reduce(node, (a, a_shape))?,
+            "Reshape" => {
+                let target = self.constant_ints(input(node, 1)?)?;
+                let shape = reshape_target(&a_shape, &target)
+                    .ok_or_else(|| invalid(format!("Invalid Reshape target in '{}'", node.name)))?;
+                (reshape(a, &shape), shape)
+            }
+            "Flatten" => {
+                // Unlike other axes, the Flatten axis can be equal to the rank
+                let axis = match node.int_attribute("axis", 1) {
+                    axis if axis == a_shape.len() as i64 => a_shape.len(),
+                    axis => normalize_axis(axis, a_shape.len()).ok_or_else(|| {
+                        invalid(format!("Invalid Flatten axis in '{}'", node.name))
+                    })?,
+                };
+                let shape = vec![
+                    a_shape[..axis].iter().product(),
+                    a_shape[axis..].iter().product(),
+                ];
+                (reshape(a, &shape), shape)
+            }
+            "Transpose" => {
+                let perm = match node.attribute("perm") {
+                    Some(attr) => attr
+                        .ints
+                        .iter()
+                        .map(|&p| normalize_axis(p, a_shape.len()))
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| invalid(format!("Invalid perm in '{}'", node.name)))?,
+                    None => (0..a_shape.len()).rev().collect(),
+                };
+                let shape = perm.iter().map(|&p| a_shape[p]).collect();
+                (a.permute(perm), shape)
+            }
+            _ => unreachable!("unsupported operators are rejected before import"),
+        };
+
+        self.tensors
+            .insert(output(node)?.to_string(), (tensor, shape));
+        Ok(())
+    }
+
+    /// `Y = alpha * A' * B' + beta * C`, with `A'` and `B'` optionally transposed.
+    fn gemm(
+        &mut self,
+        node: &NodeProto,
+        (a, a_shape): (GraphTensor, Vec<usize>),
+    ) -> Result<(GraphTensor, Vec<usize>), LuminairError> {
+        let (b, b_shape) = self.operand(input(node, 1)?)?;
+        if a_shape.len() != 2 || b_shape.len() != 2 {
+            return Err(invalid(format!("Gemm '{}' expects 2-d inputs", node.name)));
+        }
+        let (a, m) = match node.int_attribute("transA", 0) {
+            0 => (a, a_shape[0]),
+            _ => (a.permute((1, 0)), a_shape[1]),
+        };
+        let (b, n) = match node.int_attribute("transB", 0) {
+            0 => (b, b_shape[1]),
+            _ => (b.permute((1, 0)), b_shape[0]),
+        };
+        let shape = vec![m, n];
+
+        let mut y = a.matmul(b);
+        let alpha = node.float_attribute("alpha", 1.0);
+        if alpha != 1.0 {
+            y = y * alpha;
+        }
+        if let Some(c) = node.inputs.get(2).filter(|c| !c.is_empty()) {
+            let (mut c, c_shape) = self.operand(c)?;
+            if broadcast_shape(&c_shape, &shape).as_ref() != Some(&shape) {
+                return Err(invalid(format!(
+                    "Can't broadcast C of Gemm '{}'",
+                    node.name
+                )));
+            }
+            let beta = node.float_attribute("beta", 1.0);
+            if beta != 1.0 {
+                c = c * beta;
+            }
+            y = y + expand(c, &c_shape, &shape);
+        }
+        Ok((y, shape))
+    }
+
+    /// Reduces over the `axes` attribute or input, all axes if neither is given.
+    fn reduce(
+        &mut self,
+        node: &NodeProto,
+        (mut a, a_shape): (GraphTensor, Vec<usize>),
+    ) -> Result<(GraphTensor, Vec<usize>), LuminairError> {
+        // Opset 13 (ReduceSum) and 18 (others) moved `axes` from an attribute to an input
+        let axes = match (node.attribute("axes"), node.inputs.get(1)) {
+            (Some(attr), _) => attr.ints.clone(),
+            (None, Some(axes)) if !axes.is_empty() => self.constant_ints(axes)?,
+            _ => vec![],
+        };
+        let mut axes = if axes.is_empty() {
+            (0..a_shape.len()).collect()
+        } else {
+            axes.iter()
+                .map(|&ax| normalize_axis(ax, a_shape.len()))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid(format!("Invalid axes in '{}'", node.name)))?
+        };
+        axes.sort_unstable();
+        axes.dedup();
+
+        // Highest axis first so lower indices stay valid
+        for &axis in axes.iter().rev() {
+            a = match node.op_type.as_str() {
+                "ReduceSum" => a.sum_reduce(axis),
+                "ReduceMax" => a.max_reduce(axis),
+                _ => a.mean_reduce(axis),
+            };
+        }
+
+        let keep_dims = node.int_attribute("keepdims", 1) != 0;
+        let shape = a_shape
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &d)| {
+                if axes.contains(&i) {
+                    keep_dims.then_some(1)
+                } else {
+                    Some(d)
+                }
+            })
+            .collect::<Vec<_>>();
+        if keep_dims {
+            a = reshape(a, &shape);
+        }
+        Ok((a, shape))
+    }
+
+    /// Returns the tensor `name`, creating it from a constant on first use.
+    fn operand(&mut self, name: &str) -> Result<(GraphTensor, Vec<usize>), LuminairError> {
+        if let Some((tensor, shape)) = self.tensors.get(name) {
+            return Ok((*tensor, shape.clone()));
+        }
+        let constant = self
+            .constants
+            .get(name)
+            .ok_or_else(|| invalid(format!("Tensor '{}' is not defined", name)))?;
+        let shape = constant.shape()?;
+        let data = constant
+            .to_f64()?
+            .into_iter()
+            .map(|v| v as f32)
+            .collect::<Vec<_>>();
+        let tensor = self.cx.named_tensor(name, to_shape(&shape)).set(data);
+        self.tensors
+            .insert(name.to_string(), (tensor, shape.clone()));
+        Ok((tensor, shape))
+    }
+
+    /// Returns the integer values of a constant, e.g. a shape or axes input.
+    fn constant_ints(&self, name: &str) -> Result<Vec<i64>, LuminairError> {
+        self.constants
+            .get(name)
+            .ok_or_else(|| invalid(format!("'{}' must be a constant", name)))?
+            .to_i64()
+    }
+}
+
+fn input<'a>(node: &'a NodeProto, index: usize) -> Result<&'a str, LuminairError> {
+    node.inputs
+        .get(index)
+        .map(String::as_str)
+        .ok_or_else(|| invalid(format!("Node '{}' is missing input {}", node.name, index)))
+}
+
+fn output(node: &NodeProto) -> Result<&str, LuminairError> {
+    node.outputs
+        .first()
+        .map(String::as_str)
+        .ok_or_else(|| invalid(format!("Node '{}' has no output", node.name)))
+}
+
+fn to_shape(shape: &[usize]) -> Vec<Expression> {
+    shape.iter().map(|&d| Expression::from(d)).collect()
+}
+
+/// Reshapes `a`, making it contiguous first.
+fn reshape(a: GraphTensor, shape: &[usize]) -> GraphTensor {
+    a.contiguous().reshape(to_shape(shape))
+}
+
+/// Broadcasts `a` from `shape` to `target`, following NumPy's rules.
+fn expand(a: GraphTensor, shape: &[usize], target: &[usize]) -> GraphTensor {
+    if shape == target {
+        return a;
+    }
+    let mut padded = vec![1; target.len() - shape.len()];
+    padded.extend_from_slice(shape);
+    let a = if padded.len() == shape.len() {
+        a
+    } else {
+        reshape(a, &padded)
+    };
+    a.expand_to(to_shape(target))
+}
+
+/// Result shape of broadcasting `a` with `b`, `None` if incompatible.
+fn broadcast_shape(a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+    let rank = a.len().max(b.len());
+    // Dimension `i` of `s` once left-padded with ones to `rank`
+    let dim = |s: &[usize], i: usize| {
+        if i + s.len() >= rank {
+            s[i + s.len() - rank]
+        } else {
+            1
+        }
+    };
+    (0..rank)
+        .map(|i| match (dim(a, i), dim(b, i)) {
+            (x, y) if x == y || y == 1 => Some(x),
+            (1, y) => Some(y),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Result shape of `a.matmul(b)`, for the cases luminal supports.
+fn matmul_shape(a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+    let (k, n) = match b {
+        [k, n] => (*k, *n),
+        [.., k, n] if b.len() == a.len() && b[..b.len() - 2] == a[..a.len() - 2] => (*k, *n),
+        _ => return None,
+    };
+    match a {
+        [.., m, ak] if *ak == k => {
+            let mut shape = a[..a.len() - 2].to_vec();
+            shape.extend([*m, n]);
+            Some(shape)
+        }
+        _ => None,
+    }
+}
+
+/// Resolves an ONNX Reshape target, where `0` copies a dimension and `-1` is inferred.
+fn reshape_target(shape: &[usize], target: &[i64]) -> Option<Vec<usize>> {
+    let mut dims = target
+        .iter()
+        .enumerate()
+        .map(|(i, &d)| match d {
+            0 => shape.get(i).copied(),
+            -1 => Some(0),
+            d => usize::try_from(d).ok(),
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let total = shape.iter().product::<usize>();
+    let inferred = target.iter().filter(|&&d| d == -1).count();
+    let known = dims.iter().filter(|&&d| d != 0).product::<usize>();
+    match inferred {
+        0 => {}
+        1 if known != 0 && total % known == 0 => {
+            let i = target.iter().position(|&d| d == -1).unwrap();
+            dims[i] = total / known;
+        }
+        _ => return None,
+    }
+    (dims.iter().product::<usize>() == total).then_some(dims)
+}
+
+/// Converts a possibly negative axis into an index below `rank`.
+fn normalize_axis(axis: i64, rank: usize) -> Option<usize> {
+    let axis = if axis < 0 { axis + rank as i64 } else { axis };
+    usize::try_from(axis).ok().filter(|&a| a < rank)
+}
+
+fn invalid(msg: String) -> LuminairError {
+    LuminairError::InvalidGraph(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::ProjectConfig, stwo_compiler};
+
+    /// Minimal protobuf encoder for building test models.
+    #[derive(Default)]
+    struct Message(Vec<u8>);
+
+    impl Message {
+        fn varint(mut self, field: u64, v: u64) -> Self {
+            self.key(field, 0);
+            self.raw_varint(v)
+        }
+
+        fn bytes(mut self, field: u64, bytes: &[u8]) -> Self {
+            self.key(field, 2);
+            let len = Message::default().raw_varint(bytes.len() as u64);
+            self.0.extend_from_slice(&len.0);
+            self.0.extend_from_slice(bytes);
+            self
+        }
+
+        fn string(self, field: u64, s: &str) -> Self {
+            self.bytes(field, s.as_bytes())
+        }
+
+        fn message(self, field: u64, m: Message) -> Self {
+            self.bytes(field, &m.0)
+        }
+
+        fn key(&mut self, field: u64, wire: u64) {
+            let key = Message::default().raw_varint((field << 3) | wire);
+            self.0.extend_from_slice(&key.0);
+        }
+
+        fn raw_varint(mut self, mut v: u64) -> Self {
+            while v >= 0x80 {
+                self.0.push((v as u8) | 0x80);
+                v >>= 7;
+            }
+            self.0.push(v as u8);
+            self
+        }
+    }
+
+    fn float_tensor(name: &str, dims: &[i64], data: &[f32]) -> Message {
+        let mut raw = Vec::new();
+        data.iter()
+            .for_each(|v| raw.extend_from_slice(&v.to_le_bytes()));
+        dims.iter()
+            .fold(Message::default(), |m, &d| m.varint(1, d as u64))
+            .varint(2, 1)
+            .string(8, name)
+            .bytes(9, &raw)
+    }
+
+    fn value_info(name: &str, dims: &[i64]) -> Message {
+        let shape = dims.iter().fold(Message::default(), |m, &d| {
+            m.message(1, Message::default().varint(1, d as u64))
+        });
+        let tensor_type = Message::default().varint(1, 1).message(2, shape);
+        Message::default()
+            .string(1, name)
+            .message(2, Message::default().message(1, tensor_type))
+    }
+
+    fn node(op: &str, inputs: &[&str], output: &str) -> Message {
+        inputs
+            .iter()
+            .fold(Message::default(), |m, i| m.string(1, i))
+            .string(2, output)
+            .string(3, output)
+            .string(4, op)
+    }
+
+    /// `y = relu(x @ w^T + b)`, as exported for a `torch.nn.Linear` followed by a ReLU.
+    fn linear_model() -> Vec<u8> {
+        let gemm = node("Gemm", &["x", "w", "b"], "h")
+            .message(5, Message::default().string(1, "transB").varint(3, 1));
+        let graph = Message::default()
+            .message(1, gemm)
+            .message(1, node("Relu", &["h"], "y"))
+            .message(
+                5,
+                float_tensor("w", &[3, 2], &[1.0, 0.0, 0.0, 1.0, 1.0, 1.0]),
+            )
+            .message(5, float_tensor("b", &[3], &[0.5, -10.0, 0.0]))
+            .message(11, value_info("x", &[1, 2]))
+            .message(12, value_info("y", &[1, 3]));
+        Message::default().varint(1, 8).message(7, graph).0
+    }
+
+    #[test]
+    fn test_import_linear_relu() {
+        let mut cx = Graph::new();
+        let model = OnnxModel::from_bytes(&mut cx, &linear_model()).unwrap();
+        assert_eq!(model.inputs.len(), 1);
+
+        model.input("x").unwrap().set(vec![2.0, 3.0]);
+        let mut y = model.output("y").unwrap();
+        // Relu has no AIR component
+        let config = ProjectConfig::from_toml("[ops]\ncpu_fallback = true\n").unwrap();
+        cx.compile((GenericCompiler::default(), stwo_compiler(&config)), &mut y);
+        cx.execute();
+
+        assert_eq!(y.data(), [2.5, 0.0, 5.0]);
+    }
+
+    #[test]
+    fn test_unsupported_ops() {
+        let graph = Message::default()
+            .message(1, node("Softmax", &["x"], "y"))
+            .message(11, value_info("x", &[2]))
+            .message(12, value_info("y", &[2]));
+        let model = Message::default().message(7, graph).0;
+
+        match OnnxModel::from_bytes(&mut Graph::new(), &model) {
+            Err(LuminairError::UnsupportedOps(ops)) => assert_eq!(ops, ["Softmax"]),
+            other => panic!("Expected unsupported ops, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_shape_rules() {
+        assert_eq!(broadcast_shape(&[2, 3], &[3]), Some(vec![2, 3]));
+        assert_eq!(broadcast_shape(&[2, 1], &[1, 4]), Some(vec![2, 4]));
+        assert_eq!(broadcast_shape(&[2, 3], &[2]), None);
+        assert_eq!(matmul_shape(&[4, 2, 3], &[3, 5]), Some(vec![4, 2, 5]));
+        assert_eq!(reshape_target(&[2, 3, 4], &[0, -1]), Some(vec![2, 12]));
+        assert_eq!(reshape_target(&[2, 3], &[4, -1]), None);
+        assert_eq!(normalize_axis(-1, 3), Some(2));
+    }
+}
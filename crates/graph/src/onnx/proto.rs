@@ -0,0 +1,422 @@
+use half::{bf16, f16};
+use luminair_utils::LuminairError;
+
+// ONNX `TensorProto.DataType` values.
+const FLOAT: i32 = 1;
+const INT32: i32 = 6;
+const INT64: i32 = 7;
+const FLOAT16: i32 = 10;
+const DOUBLE: i32 = 11;
+const BFLOAT16: i32 = 16;
+
+/// A decoded protobuf field value.
+enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Reads the fields of a protobuf message, in wire order.
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn varint(&mut self) -> Result<u64, LuminairError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .buf
+                .get(self.pos)
+                .ok_or_else(|| proto_error("Truncated varint"))?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(proto_error("Varint too long"))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], LuminairError> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| proto_error("Truncated field"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn next_field(&mut self) -> Result<Option<(u64, Value<'a>)>, LuminairError> {
+        if self.pos >= self.buf.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let value = match key & 7 {
+            0 => Value::Varint(self.varint()?),
+            1 => Value::Fixed64(u64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            2 => {
+                let len = self.varint()? as usize;
+                Value::Bytes(self.take(len)?)
+            }
+            5 => Value::Fixed32(u32::from_le_bytes(self.take(4)?.try_into().unwrap())),
+            t => return Err(proto_error(&format!("Unsupported wire type {}", t))),
+        };
+        Ok(Some((key >> 3, value)))
+    }
+}
+
+impl<'a> Value<'a> {
+    fn bytes(&self) -> Result<&'a [u8], LuminairError> {
+        match self {
+            Value::Bytes(b) => Ok(b),
+            _ => Err(proto_error("Expected a length-delimited field")),
+        }
+    }
+
+    fn string(&self) -> Result<String, LuminairError> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| proto_error("Invalid UTF-8 string"))
+    }
+
+    fn int(&self) -> Result<i64, LuminairError> {
+        match self {
+            Value::Varint(v) => Ok(*v as i64),
+            _ => Err(proto_error("Expected a varint field")),
+        }
+    }
+
+    fn float(&self) -> Result<f32, LuminairError> {
+        match self {
+            Value::Fixed32(v) => Ok(f32::from_bits(*v)),
+            _ => Err(proto_error("Expected a float field")),
+        }
+    }
+
+    /// Appends a repeated varint field, packed or not.
+    fn push_ints(&self, out: &mut Vec<i64>) -> Result<(), LuminairError> {
+        match self {
+            Value::Bytes(b) => {
+                let mut reader = Reader::new(b);
+                while reader.pos < b.len() {
+                    out.push(reader.varint()? as i64);
+                }
+            }
+            v => out.push(v.int()?),
+        }
+        Ok(())
+    }
+
+    /// Appends a repeated float field, packed or not.
+    fn push_floats(&self, out: &mut Vec<f32>) -> Result<(), LuminairError> {
+        match self {
+            Value::Bytes(b) => out.extend(
+                b.chunks_exact(4)
+                    .map(|c| f32::from_le_bytes(c.try_into().unwrap())),
+            ),
+            v => out.push(v.float()?),
+        }
+        Ok(())
+    }
+
+    /// Appends a repeated double field, packed or not.
+    fn push_doubles(&self, out: &mut Vec<f64>) -> Result<(), LuminairError> {
+        match self {
+            Value::Bytes(b) => out.extend(
+                b.chunks_exact(8)
+                    .map(|c| f64::from_le_bytes(c.try_into().unwrap())),
+            ),
+            Value::Fixed64(v) => out.push(f64::from_bits(*v)),
+            _ => return Err(proto_error("Expected a double field")),
+        }
+        Ok(())
+    }
+}
+
+/// `ModelProto`, reduced to its graph.
+#[derive(Debug, Default)]
+pub(crate) struct ModelProto {
+    pub graph: GraphProto,
+}
+
+/// `GraphProto`.
+#[derive(Debug, Default)]
+pub(crate) struct GraphProto {
+    pub nodes: Vec<NodeProto>,
+    pub initializers: Vec<TensorProto>,
+    pub inputs: Vec<ValueInfoProto>,
+    pub outputs: Vec<ValueInfoProto>,
+}
+
+/// `NodeProto`.
+#[derive(Debug, Default)]
+pub(crate) struct NodeProto {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub name: String,
+    pub op_type: String,
+    pub attributes: Vec<AttributeProto>,
+}
+
+/// `AttributeProto`, for float, int, tensor and int list attributes.
+#[derive(Debug, Default)]
+pub(crate) struct AttributeProto {
+    pub name: String,
+    pub f: f32,
+    pub i: i64,
+    pub t: Option<TensorProto>,
+    pub ints: Vec<i64>,
+}
+
+/// `TensorProto`, holding the data of initializers and constants.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TensorProto {
+    pub name: String,
+    pub dims: Vec<i64>,
+    pub data_type: i32,
+    float_data: Vec<f32>,
+    int32_data: Vec<i64>,
+    int64_data: Vec<i64>,
+    double_data: Vec<f64>,
+    raw_data: Vec<u8>,
+}
+
+/// `ValueInfoProto`, reduced to the name and dimensions of a tensor.
+///
+/// Symbolic dimensions are `None`.
+#[derive(Debug, Default)]
+pub(crate) struct ValueInfoProto {
+    pub name: String,
+    pub dims: Vec<Option<i64>>,
+}
+
+impl ModelProto {
+    pub fn decode(buf: &[u8]) -> Result<Self, LuminairError> {
+        let mut model = Self::default();
+        let mut reader = Reader::new(buf);
+        while let Some((field, value)) = reader.next_field()? {
+            if field == 7 {
+                model.graph = GraphProto::decode(value.bytes()?)?;
+            }
+        }
+        Ok(model)
+    }
+}
+
+impl GraphProto {
+    fn decode(buf: &[u8]) -> Result<Self, LuminairError> {
+        let mut graph = Self::default();
+        let mut reader = Reader::new(buf);
+        while let Some((field, value)) = reader.next_field()? {
+            match field {
+                1 => graph.nodes.push(NodeProto::decode(value.bytes()?)?),
+                5 => graph
+                    .initializers
+                    .push(TensorProto::decode(value.bytes()?)?),
+                11 => graph.inputs.push(ValueInfoProto::decode(value.bytes()?)?),
+                12 => graph.outputs.push(ValueInfoProto::decode(value.bytes()?)?),
+                _ => {}
+            }
+        }
+        Ok(graph)
+    }
+}
+
+impl NodeProto {
+    fn decode(buf: &[u8]) -> Result<Self, LuminairError> {
+        let mut node = Self::default();
+        let mut reader = Reader::new(buf);
+        while let Some((field, value)) = reader.next_field()? {
+            match field {
+                1 => node.inputs.push(value.string()?),
+                2 => node.outputs.push(value.string()?),
+                3 => node.name = value.string()?,
+                4 => node.op_type = value.string()?,
+                5 => node
+                    .attributes
+                    .push(AttributeProto::decode(value.bytes()?)?),
+                _ => {}
+            }
+        }
+        Ok(node)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&AttributeProto> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+
+    pub fn int_attribute(&self, name: &str, default: i64) -> i64 {
+        self.attribute(name).map_or(default, |a| a.i)
+    }
+
+    pub fn float_attribute(&self, name: &str, default: f32) -> f32 {
+        self.attribute(name).map_or(default, |a| a.f)
+    }
+}
+
+impl AttributeProto {
+    fn decode(buf: &[u8]) -> Result<Self, LuminairError> {
+        let mut attribute = Self::default();
+        let mut reader = Reader::new(buf);
+        while let Some((field, value)) = reader.next_field()? {
+            match field {
+                1 => attribute.name = value.string()?,
+                2 => attribute.f = value.float()?,
+                3 => attribute.i = value.int()?,
+                5 => attribute.t = Some(TensorProto::decode(value.bytes()?)?),
+                8 => value.push_ints(&mut attribute.ints)?,
+                _ => {}
+            }
+        }
+        Ok(attribute)
+    }
+}
+
+impl TensorProto {
+    fn decode(buf: &[u8]) -> Result<Self, LuminairError> {
+        let mut tensor = Self::default();
+        let mut reader = Reader::new(buf);
+        while let Some((field, value)) = reader.next_field()? {
+            match field {
+                1 => value.push_ints(&mut tensor.dims)?,
+                2 => tensor.data_type = value.int()? as i32,
+                4 => value.push_floats(&mut tensor.float_data)?,
+                5 => value.push_ints(&mut tensor.int32_data)?,
+                7 => value.push_ints(&mut tensor.int64_data)?,
+                8 => tensor.name = value.string()?,
+                9 => tensor.raw_data = value.bytes()?.to_vec(),
+                10 => value.push_doubles(&mut tensor.double_data)?,
+                _ => {}
+            }
+        }
+        Ok(tensor)
+    }
+
+    /// Returns the dimensions, checking none is negative.
+    pub fn shape(&self) -> Result<Vec<usize>, LuminairError> {
+        self.dims
+            .iter()
+            .map(|&d| {
+                usize::try_from(d).map_err(|_| {
+                    proto_error(&format!("Negative dimension in tensor '{}'", self.name))
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the values converted to `f64`, from the typed fields or the raw data.
+    pub fn to_f64(&self) -> Result<Vec<f64>, LuminairError> {
+        let raw = !self.raw_data.is_empty();
+        let values = match self.data_type {
+            FLOAT if raw => decode(&self.raw_data, |b| f32::from_le_bytes(b) as f64),
+            FLOAT => self.float_data.iter().map(|&v| v as f64).collect(),
+            DOUBLE if raw => decode(&self.raw_data, f64::from_le_bytes),
+            DOUBLE => self.double_data.clone(),
+            INT32 if raw => decode(&self.raw_data, |b| i32::from_le_bytes(b) as f64),
+            INT64 if raw => decode(&self.raw_data, |b| i64::from_le_bytes(b) as f64),
+            INT32 => self.int32_data.iter().map(|&v| v as f64).collect(),
+            INT64 => self.int64_data.iter().map(|&v| v as f64).collect(),
+            FLOAT16 if raw => decode(&self.raw_data, |b| f16::from_le_bytes(b).to_f64()),
+            BFLOAT16 if raw => decode(&self.raw_data, |b| bf16::from_le_bytes(b).to_f64()),
+            // Half-precision values are stored as their bit patterns in `int32_data`
+            FLOAT16 => self
+                .int32_data
+                .iter()
+                .map(|&v| f16::from_bits(v as u16).to_f64())
+                .collect(),
+            BFLOAT16 => self
+                .int32_data
+                .iter()
+                .map(|&v| bf16::from_bits(v as u16).to_f64())
+                .collect(),
+            t => {
+                return Err(proto_error(&format!(
+                    "Unsupported data type {} for tensor '{}'",
+                    t, self.name
+                )))
+            }
+        };
+
+        let expected = self.shape()?.iter().product::<usize>();
+        if values.len() != expected {
+            return Err(proto_error(&format!(
+                "Tensor '{}' has {} values, shape {:?} expects {}",
+                self.name,
+                values.len(),
+                self.dims,
+                expected
+            )));
+        }
+        Ok(values)
+    }
+
+    /// Returns the values as integers, e.g. for shapes and axes.
+    pub fn to_i64(&self) -> Result<Vec<i64>, LuminairError> {
+        Ok(self.to_f64()?.into_iter().map(|v| v as i64).collect())
+    }
+}
+
+impl ValueInfoProto {
+    fn decode(buf: &[u8]) -> Result<Self, LuminairError> {
+        let mut info = Self::default();
+        let mut reader = Reader::new(buf);
+        while let Some((field, value)) = reader.next_field()? {
+            match field {
+                1 => info.name = value.string()?,
+                // TypeProto.tensor_type.shape.dim
+                2 => {
+                    let tensor_type = sub_message(value.bytes()?, 1)?;
+                    let shape = sub_message(tensor_type.unwrap_or_default(), 2)?;
+                    let mut reader = Reader::new(shape.unwrap_or_default());
+                    while let Some((field, dim)) = reader.next_field()? {
+                        if field == 1 {
+                            info.dims.push(dim_value(dim.bytes()?)?);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// Returns the last occurrence of the message field `field` in `buf`.
+fn sub_message(buf: &[u8], field: u64) -> Result<Option<&[u8]>, LuminairError> {
+    let mut found = None;
+    let mut reader = Reader::new(buf);
+    while let Some((f, value)) = reader.next_field()? {
+        if f == field {
+            found = Some(value.bytes()?);
+        }
+    }
+    Ok(found)
+}
+
+/// Reads `TensorShapeProto.Dimension`, `None` if the dimension is symbolic.
+fn dim_value(buf: &[u8]) -> Result<Option<i64>, LuminairError> {
+    let mut reader = Reader::new(buf);
+    while let Some((field, value)) = reader.next_field()? {
+        if field == 1 {
+            return Ok(Some(value.int()?));
+        }
+    }
+    Ok(None)
+}
+
+/// Decodes little-endian values of `N` bytes each.
+fn decode<const N: usize>(bytes: &[u8], f: impl Fn([u8; N]) -> f64) -> Vec<f64> {
+    bytes
+        .chunks_exact(N)
+        .map(|chunk| f(chunk.try_into().unwrap()))
+        .collect()
+}
+
+fn proto_error(msg: &str) -> LuminairError {
+    LuminairError::SerializationError(format!("Invalid ONNX model: {}", msg))
+}
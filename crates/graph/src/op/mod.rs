@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{any::Any, fmt::Debug};
 
 use luminair_air::{components::TraceColumn, pie::NodeInfo};
 use luminal::prelude::*;
@@ -6,6 +6,15 @@ use luminal::prelude::*;
 pub(crate) mod other;
 pub(crate) mod prim;
 
+/// Key of the `Operator::custom` query computing an operator's reference output.
+pub(crate) const REFERENCE_KEY: &str = "luminair_reference";
+
+/// Key of the `Operator::custom` query returning a copy of a LuminAIR operator.
+pub(crate) const CLONE_KEY: &str = "luminair_clone";
+
+/// Key of the `Operator::custom` query comparing a LuminAIR operator with a copy of another one.
+pub(crate) const EQ_KEY: &str = "luminair_eq";
+
 /// Defines an operator specifically designed for LuminAIR, capable of generating execution traces.
 ///
 /// This trait extends Luminal's `Operator` trait. An implementation must provide
@@ -16,7 +25,7 @@ pub(crate) trait LuminairOperator<
     C: TraceColumn + Debug + 'static, // The specific column structure for this op's trace
     T: Debug + 'static,             // The table type to store trace entries (e.g., AddTraceTable)
     L: Debug + 'static,             // Auxiliary lookup data/helper (e.g., SinLookup)
->: Operator
+>: Operator + CloneOperator<C, T, L> + EqOperator
 {
     /// Processes input tensors to produce output tensors and populate the trace table.
     ///
@@ -29,6 +38,47 @@ pub(crate) trait LuminairOperator<
         node_info: &NodeInfo,
         lookup: &mut L,
     ) -> Vec<Tensor>;
+
+    /// Recomputes the output in `f64` from reference inputs, without fixed-point rounding.
+    ///
+    /// Used to measure the fixed-point error of each operator.
+    /// Returns `None` if the operator has no reference implementation.
+    fn reference(&self, _inp: &[(Vec<f64>, ShapeTracker)]) -> Option<Vec<f64>> {
+        None
+    }
+}
+
+/// Clones a boxed `LuminairOperator`, implemented for every `Clone` operator.
+pub(crate) trait CloneOperator<
+    C: TraceColumn + Debug + 'static,
+    T: Debug + 'static,
+    L: Debug + 'static,
+>
+{
+    fn clone_operator(&self) -> Box<dyn LuminairOperator<C, T, L>>;
+}
+
+impl<O, C, T, L> CloneOperator<C, T, L> for O
+where
+    O: LuminairOperator<C, T, L> + Clone + 'static,
+    C: TraceColumn + Debug + 'static,
+    T: Debug + 'static,
+    L: Debug + 'static,
+{
+    fn clone_operator(&self) -> Box<dyn LuminairOperator<C, T, L>> {
+        Box::new(self.clone())
+    }
+}
+
+/// Compares a `LuminairOperator` with another operator, implemented for every `PartialEq` operator.
+pub(crate) trait EqOperator {
+    fn eq_operator(&self, other: &dyn Any) -> bool;
+}
+
+impl<O: PartialEq + 'static> EqOperator for O {
+    fn eq_operator(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<O>().is_some_and(|other| self == other)
+    }
 }
 
 /// A trait to dynamically check if an operator supports trace generation and to invoke it.
@@ -79,6 +129,28 @@ impl<C: TraceColumn + Debug + 'static, T: Debug + 'static, L: Debug + 'static> O
     fn process(&mut self, inp: Vec<(InputTensor, ShapeTracker)>) -> Vec<Tensor> {
         self.0.process(inp)
     }
+
+    /// Answers `REFERENCE_KEY` queries with the wrapped operator's reference output,
+    /// `CLONE_KEY` queries with a copy of the operator, and `EQ_KEY` queries with whether
+    /// the given copy runs the same operator with the same parameters.
+    fn custom(&mut self, key: &str, input: Box<dyn Any>) -> Option<Box<dyn Any>> {
+        if key == CLONE_KEY {
+            let op: Box<dyn Operator> = Box::new(LuminairWrapper(self.0.clone_operator()));
+            return Some(Box::new(op));
+        }
+        if key == EQ_KEY {
+            let other = input.downcast::<Box<dyn Operator>>().ok()?;
+            let other = (**other).as_any().downcast_ref::<Self>()?;
+            return Some(Box::new(self.0.eq_operator((*other.0).as_any())));
+        }
+        if key != REFERENCE_KEY {
+            return None;
+        }
+        let inp = input.downcast::<Vec<(Vec<f64>, ShapeTracker)>>().ok()?;
+        self.0
+            .reference(&inp)
+            .map(|out| Box::new(out) as Box<dyn Any>)
+    }
 }
 
 impl<C: TraceColumn + Debug + 'static, T: Debug + 'static, L: Debug + 'static>
@@ -1,7 +1,27 @@
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
 use itertools::Itertools;
-use luminal::prelude::{petgraph::visit::EdgeRef, *};
+use luminair_air::fixed::{to_fixed, RoundingMode};
+use luminair_utils::LuminairError;
+use luminal::{
+    op::{Constant, ConstantValue, Contiguous, Function},
+    prelude::{petgraph::visit::EdgeRef, *},
+};
 
-use super::prim::{CopyFromStwo, CopyToStwo};
+use super::{
+    prim::{is_unsupported_op, lower_primitive, CopyFromStwo, CopyToStwo, LuminairConstant},
+    CLONE_KEY, EQ_KEY,
+};
+use crate::{
+    config::ProjectConfig,
+    cost::component_of,
+    data::StwoData,
+    utils::{is_plain_view, split_output},
+};
 
 /// A Luminal `Compiler` pass that optimizes redundant copy operations.
 ///
@@ -71,3 +91,678 @@ impl Compiler for CopyCompiler {
         }
     }
 }
+
+/// A Luminal `Compiler` pass that evaluates constant subgraphs on the host.
+///
+/// Runs before `PrimitiveCompiler`. Going through the graph in topological order, every
+/// primitive whose inputs are all float `Constant`s or already folded is evaluated with its
+/// LuminAIR fixed-point kernel, so the folded values are exactly those the trace would hold.
+/// The folded nodes read by the rest of the graph become `Function` inputs holding the
+/// resulting `StwoData` tensor, and the others are dropped. Folded arithmetic is never
+/// executed in the trace nor proven.
+#[derive(Debug, Default)]
+pub struct ConstantFoldingCompiler();
+
+impl Compiler for ConstantFoldingCompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, _ids: To) {
+        let Ok(order) = petgraph::algo::toposort(&graph.graph, None) else {
+            return;
+        };
+        let mut values = HashMap::new();
+        for node in order {
+            if let Some(value) = fold_node(graph, node, &values) {
+                values.insert(node, value);
+            }
+        }
+
+        // Retrieved nodes are computed as usual, from their folded inputs
+        let folded = values
+            .keys()
+            .copied()
+            .filter(|node| {
+                !graph.get_sources(*node).is_empty()
+                    && !graph.no_delete.contains(node)
+                    && !graph.to_retrieve.contains_key(node)
+            })
+            .sorted()
+            .collect::<Vec<_>>();
+        for &node in &folded {
+            if graph
+                .edges_directed(node, petgraph::Direction::Outgoing)
+                .all(|e| folded.binary_search(&e.target()).is_ok())
+            {
+                continue;
+            }
+
+            // Detach the node from its inputs and hand out its value instead
+            for edge in graph
+                .edges_directed(node, petgraph::Direction::Incoming)
+                .map(|e| e.id())
+                .collect::<Vec<_>>()
+            {
+                graph.remove_edge(edge);
+            }
+            let name = format!("Folded {:?}", graph.node_weight(node).unwrap());
+            let data = values[&node].clone();
+            *graph.node_weight_mut(node).unwrap() = Box::new(Function(
+                name,
+                Box::new(move |_| vec![Tensor::new(data.clone())]),
+            ));
+        }
+
+        // Drop the constants nothing depends on anymore, consumers first
+        for node in petgraph::algo::toposort(&graph.graph, None)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .filter(|node| values.contains_key(node))
+        {
+            if graph
+                .edges_directed(node, petgraph::Direction::Outgoing)
+                .next()
+                .is_none()
+                && !graph.no_delete.contains(&node)
+                && !graph.to_retrieve.contains_key(&node)
+            {
+                graph.remove_node(node);
+            }
+        }
+    }
+}
+
+/// Evaluates `node` with its LuminAIR kernel if it is a float constant or a primitive
+/// whose inputs all have a known value in `values`.
+fn fold_node(
+    graph: &Graph,
+    node: NodeIndex,
+    values: &HashMap<NodeIndex, StwoData>,
+) -> Option<StwoData> {
+    let op = graph.node_weight(node)?;
+    if let Some(Constant(ConstantValue::Float(value), _)) = op.as_any().downcast_ref::<Constant>() {
+        let value = to_fixed(*value as f64, RoundingMode::Nearest, 0);
+        return Some(StwoData(Arc::new(vec![value])));
+    }
+
+    let sources = graph.get_sources(node);
+    if sources.is_empty() {
+        return None;
+    }
+    let mut inputs = Vec::with_capacity(sources.len());
+    for (src, _, shape) in sources {
+        // Shapes depending on dynamic dimensions are only known at execution time
+        shape.n_elements().to_usize()?;
+        let value = values.get(&src)?.clone();
+        inputs.push((InputTensor::Owned(Tensor::new(value)), shape));
+    }
+
+    let output = lower_primitive(op.as_ref())?.process(inputs).pop()?;
+    output.downcast_ref::<StwoData>().cloned()
+}
+
+/// A Luminal `Compiler` pass that merges identical operators of the lowered graph.
+///
+/// Runs after `PrimitiveCompiler`. Two nodes are identical when they run the same
+/// operator on the same inputs, so the duplicate is replaced by the original and its
+/// computation is traced and proven once. Constants are merged by value.
+///
+/// Nodes are visited once in topological order, so the inputs of a node are already
+/// merged when it is compared with the nodes reading the same sources.
+#[derive(Debug, Default)]
+pub struct CSECompiler();
+
+impl Compiler for CSECompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, mut ids: To) {
+        let Ok(order) = petgraph::algo::toposort(&graph.graph, None) else {
+            return;
+        };
+        let mut seen: HashMap<Vec<(NodeIndex, u8)>, Vec<NodeIndex>> = HashMap::new();
+        for node in order {
+            let sources = graph.get_sources(node);
+            let op = graph.node_weight(node).unwrap().as_any();
+            // Nodes without sources are inputs, only constants are safe to merge
+            if sources.is_empty() && !op.is::<LuminairConstant>() {
+                continue;
+            }
+
+            let key = sources.iter().map(|(src, ind, _)| (*src, *ind)).collect();
+            let candidates = seen.entry(key).or_default();
+            let original = candidates.iter().copied().find(|&other| {
+                graph.get_sources(other) == sources && same_operator(graph, node, other)
+            });
+            match original {
+                Some(original)
+                    if !graph.no_delete.contains(&node)
+                        && !graph.to_retrieve.contains_key(&node) =>
+                {
+                    move_outgoing_edge(node, original, graph);
+                    remap(node, original, &mut ids, graph);
+                    graph.remove_node(node);
+                }
+                _ => candidates.push(node),
+            }
+        }
+    }
+}
+
+/// Returns true if `a` and `b` run the same operator with the same parameters.
+///
+/// LuminAIR operators are compared with `PartialEq` through the `EQ_KEY` query, and
+/// constants by value. Operators left on the CPU are never merged.
+fn same_operator(graph: &mut Graph, a: NodeIndex, b: NodeIndex) -> bool {
+    let (op_a, op_b) = (
+        graph.node_weight(a).unwrap().as_any(),
+        graph.node_weight(b).unwrap().as_any(),
+    );
+    if let (Some(x), Some(y)) = (
+        op_a.downcast_ref::<LuminairConstant>(),
+        op_b.downcast_ref::<LuminairConstant>(),
+    ) {
+        return x == y;
+    }
+    if (op_a.is::<CopyToStwo>() && op_b.is::<CopyToStwo>())
+        || (op_a.is::<CopyFromStwo>() && op_b.is::<CopyFromStwo>())
+    {
+        return true;
+    }
+
+    let Some(other) = graph
+        .graph
+        .node_weight_mut(b)
+        .unwrap()
+        .custom(CLONE_KEY, Box::new(()))
+    else {
+        return false;
+    };
+    graph
+        .graph
+        .node_weight_mut(a)
+        .unwrap()
+        .custom(EQ_KEY, other)
+        .and_then(|eq| eq.downcast::<bool>().ok())
+        .is_some_and(|eq| *eq)
+}
+
+/// A Luminal `Compiler` pass that removes nodes retrieved outputs don't depend on.
+///
+/// Runs before `PrimitiveCompiler`, so unused operators are never lowered,
+/// traced or proven. Retrieved nodes and nodes marked as `no_delete` are roots.
+/// Input nodes are kept so their data can still be set after compilation.
+#[derive(Debug, Default)]
+pub struct DeadCodeCompiler();
+
+impl Compiler for DeadCodeCompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, _ids: To) {
+        if graph.to_retrieve.is_empty() {
+            return;
+        }
+
+        let mut live = HashSet::new();
+        let mut stack = graph
+            .to_retrieve
+            .keys()
+            .chain(graph.no_delete.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            if live.insert(node) {
+                stack.extend(
+                    graph
+                        .edges_directed(node, petgraph::Direction::Incoming)
+                        .map(|e| e.source()),
+                );
+            }
+        }
+
+        for node in graph
+            .node_indices()
+            .filter(|n| !live.contains(n))
+            .filter(|n| !graph.node_weight(*n).unwrap().as_any().is::<Function>())
+            .collect::<Vec<_>>()
+        {
+            graph.remove_node(node);
+        }
+    }
+}
+
+/// Key of the `custom` query taking the wrapped operator out of a `CpuOp`.
+const UNWRAP_KEY: &str = "luminair_unwrap";
+
+/// Marks an operator to be kept on luminal's CPU backend instead of being lowered to LuminAIR.
+///
+/// Only lives between `CpuPartitionCompiler` and `BoundaryCompiler`, which puts the
+/// original operator back.
+#[derive(Debug)]
+pub(crate) struct CpuOp(Box<dyn Operator>);
+
+impl Operator for CpuOp {
+    fn process(&mut self, inp: Vec<(InputTensor, ShapeTracker)>) -> Vec<Tensor> {
+        self.0.process(inp)
+    }
+
+    fn custom(&mut self, key: &str, input: Box<dyn Any>) -> Option<Box<dyn Any>> {
+        if key == UNWRAP_KEY {
+            return Some(Box::new(std::mem::replace(&mut self.0, placeholder())));
+        }
+        self.0.custom(key, input)
+    }
+}
+
+/// Operator temporarily left in a node while its operator is moved out.
+fn placeholder() -> Box<dyn Operator> {
+    Box::new(Function(String::new(), Box::new(|_| vec![])))
+}
+
+/// A Luminal `Compiler` pass that selects which operators are lowered to LuminAIR.
+///
+/// Runs before `StwoCompiler`. Operators that are not selected are left untouched on
+/// luminal's CPU backend: they are executed natively, left out of the proof, and
+/// remain available to other luminal compilers. Only the selected subgraphs are
+/// traced and proven.
+pub struct CpuPartitionCompiler {
+    lower: Box<dyn Fn(&Graph, NodeIndex) -> bool>,
+}
+
+impl Default for CpuPartitionCompiler {
+    /// Selects every operator.
+    fn default() -> Self {
+        Self::select_by(|_, _| true)
+    }
+}
+
+impl CpuPartitionCompiler {
+    /// Keeps the operators producing `tensors` on the CPU and lowers the others.
+    pub fn new(tensors: impl IntoIterator<Item = GraphTensor>) -> Self {
+        let cpu = tensors.into_iter().map(|t| t.id).collect::<HashSet<_>>();
+        Self::select_by(move |_, node| !cpu.contains(&node))
+    }
+
+    /// Lowers only the operators producing `tensors`.
+    pub fn select(tensors: impl IntoIterator<Item = GraphTensor>) -> Self {
+        let selected = tensors.into_iter().map(|t| t.id).collect::<HashSet<_>>();
+        Self::select_by(move |_, node| selected.contains(&node))
+    }
+
+    /// Lowers only the operators for which `predicate` returns true.
+    pub fn select_by(predicate: impl Fn(&Graph, NodeIndex) -> bool + 'static) -> Self {
+        Self {
+            lower: Box::new(predicate),
+        }
+    }
+}
+
+impl Compiler for CpuPartitionCompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, _ids: To) {
+        for node in graph
+            .node_indices()
+            .filter(|n| !(self.lower)(graph, *n))
+            .collect::<Vec<_>>()
+        {
+            let weight = graph.graph.node_weight_mut(node).unwrap();
+            if weight.as_any().is::<Function>() || weight.as_any().is::<CpuOp>() {
+                continue;
+            }
+            let op = std::mem::replace(weight, placeholder());
+            *weight = Box::new(CpuOp(op));
+        }
+    }
+}
+
+/// A Luminal `Compiler` pass that inserts copies at the boundaries of CPU operators.
+///
+/// Runs after `PrimitiveCompiler`. Operators left on the CPU, by `CpuPartitionCompiler`,
+/// `ops.cpu` or, with `ops.cpu_fallback`, for lack of an AIR component, read and write
+/// `Vec<f32>`, so a `CopyFromStwo` is added before each of their inputs coming from a
+/// LuminAIR operator, and a `CopyToStwo` after their outputs consumed by one. `CopyCompiler`
+/// then removes the pairs this creates between adjacent CPU operators.
+///
+/// Without `ops.cpu_fallback`, unsupported operators are left as they are, and reported by
+/// `LuminairGraph::check_supported_ops`.
+#[derive(Debug)]
+pub struct BoundaryCompiler {
+    /// Whether operators without an AIR component are moved to the CPU.
+    cpu_fallback: bool,
+}
+
+impl Default for BoundaryCompiler {
+    /// Applies the project configuration, from `luminair.toml` if there is one.
+    ///
+    /// Panics if the configuration is invalid, like `PrimitiveCompiler`.
+    fn default() -> Self {
+        let config = ProjectConfig::load().unwrap_or_else(|e| panic!("{}", e));
+        Self::new(&config)
+    }
+}
+
+impl BoundaryCompiler {
+    pub fn new(config: &ProjectConfig) -> Self {
+        Self {
+            cpu_fallback: config.ops.cpu_fallback,
+        }
+    }
+}
+
+impl Compiler for BoundaryCompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, _ids: To) {
+        for node in graph
+            .node_indices()
+            .filter(|n| runs_on_cpu(graph, *n))
+            .filter(|n| self.cpu_fallback || !is_unsupported_op(graph.node_weight(*n).unwrap()))
+            .collect::<Vec<_>>()
+        {
+            // Convert inputs produced by LuminAIR operators
+            for (source, edge, weight) in graph
+                .edges_directed(node, petgraph::Direction::Incoming)
+                .map(|e| (e.source(), e.id(), *e.weight()))
+                .filter(|(src, _, _)| !outputs_f32(graph, *src))
+                .collect::<Vec<_>>()
+            {
+                let (output, weight) = split_output(weight);
+                let copy_node = graph
+                    .add_op(CopyFromStwo::new())
+                    .input(source, output, ShapeTracker::new(()))
+                    .finish();
+                graph.add_edge(copy_node, node, weight);
+                graph.remove_edge(edge);
+            }
+
+            // Convert outputs consumed by LuminAIR operators, one copy per output
+            let mut copies = HashMap::new();
+            for (edge, weight, dest) in graph
+                .edges_directed(node, petgraph::Direction::Outgoing)
+                .map(|e| (e.id(), *e.weight(), e.target()))
+                .filter(|(_, _, dest)| !inputs_f32(graph, *dest))
+                .collect::<Vec<_>>()
+            {
+                let (output, weight) = split_output(weight);
+                let copy_node = *copies.entry(output).or_insert_with(|| {
+                    graph
+                        .add_op(CopyToStwo::new())
+                        .input(node, output, ShapeTracker::new(()))
+                        .finish()
+                });
+                graph.add_edge(copy_node, dest, weight);
+                graph.remove_edge(edge);
+            }
+
+            // Put back operators marked by CpuPartitionCompiler
+            let weight = graph.graph.node_weight_mut(node).unwrap();
+            if let Some(op) = weight
+                .custom(UNWRAP_KEY, Box::new(()))
+                .and_then(|op| op.downcast::<Box<dyn Operator>>().ok())
+            {
+                *weight = *op;
+            }
+        }
+    }
+}
+
+/// A Luminal `Compiler` pass that recomputes selected intermediate tensors instead of
+/// keeping them in memory.
+///
+/// Runs after `StwoCompiler`. A tensor read by several operators is kept until its
+/// last consumer runs. This pass gives every consumer but the first its own copy of
+/// the producing operator, so the tensor is freed after its first use and computed
+/// again when needed. This trades trace rows for host memory on deep graphs.
+/// Only LuminAIR operators are recomputed, and running `CSECompiler` again would
+/// merge the copies back.
+#[derive(Debug, Default)]
+pub struct RematerializeCompiler {
+    tensors: Vec<NodeIndex>,
+}
+
+impl RematerializeCompiler {
+    /// Recomputes `tensors` for each of their consumers.
+    pub fn new(tensors: impl IntoIterator<Item = GraphTensor>) -> Self {
+        Self {
+            tensors: tensors.into_iter().map(|t| t.id).collect(),
+        }
+    }
+}
+
+impl Compiler for RematerializeCompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, _ids: To) {
+        for &node in &self.tensors {
+            if !graph.graph.contains_node(node) {
+                continue;
+            }
+            let consumers = graph
+                .edges_directed(node, petgraph::Direction::Outgoing)
+                .filter(|e| e.weight().as_data().is_some())
+                .map(|e| e.target())
+                .unique()
+                .sorted()
+                .collect::<Vec<_>>();
+            let sources = graph
+                .edges_directed(node, petgraph::Direction::Incoming)
+                .map(|e| (e.source(), *e.weight()))
+                .collect::<Vec<_>>();
+
+            for dest in consumers.into_iter().skip(1) {
+                let Some(op) = graph
+                    .graph
+                    .node_weight_mut(node)
+                    .unwrap()
+                    .custom(CLONE_KEY, Box::new(()))
+                    .and_then(|op| op.downcast::<Box<dyn Operator>>().ok())
+                else {
+                    break;
+                };
+                let copy_node = graph.graph.add_node(*op);
+                for (source, weight) in &sources {
+                    graph.add_edge(*source, copy_node, *weight);
+                }
+                for (edge, weight) in graph
+                    .edges_directed(node, petgraph::Direction::Outgoing)
+                    .filter(|e| e.target() == dest)
+                    .map(|e| (e.id(), *e.weight()))
+                    .collect::<Vec<_>>()
+                {
+                    graph.add_edge(copy_node, dest, weight);
+                    graph.remove_edge(edge);
+                }
+            }
+        }
+    }
+}
+
+/// A Luminal `Compiler` pass that converts constant input tensors to fixed point once.
+///
+/// By default each input is converted to `StwoData` by its `CopyToStwo` on every
+/// execution. The loaders of the selected tensors, typically weights, are run at compile
+/// time and replaced with ones sharing the converted buffer, so each inference only pays
+/// for converting its activations. Setting a tensor again after compiling replaces the
+/// converted data, as usual.
+#[derive(Debug, Default)]
+pub struct WeightsCompiler {
+    tensors: Vec<NodeIndex>,
+}
+
+impl WeightsCompiler {
+    /// Converts the data of the input tensors `tensors`, which must already be set.
+    pub fn new(tensors: impl IntoIterator<Item = GraphTensor>) -> Self {
+        Self {
+            tensors: tensors.into_iter().map(|t| t.id).collect(),
+        }
+    }
+}
+
+impl Compiler for WeightsCompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, _ids: To) {
+        for &node in &self.tensors {
+            let Some(function) = graph
+                .graph
+                .node_weight_mut(node)
+                .and_then(|op| (**op).as_any_mut().downcast_mut::<Function>())
+            else {
+                continue;
+            };
+            let Some(tensor) = (function.1)(vec![]).pop() else {
+                continue;
+            };
+            let mut converted = CopyToStwo::new().process(vec![(
+                InputTensor::Borrowed(&tensor),
+                ShapeTracker::new(()),
+            )]);
+            let data = converted
+                .pop()
+                .unwrap()
+                .downcast_ref::<StwoData>()
+                .unwrap()
+                .clone();
+            function.1 = Box::new(move |_| vec![Tensor::new(data.clone())]);
+        }
+    }
+}
+
+/// A Luminal `Compiler` pass that folds `Contiguous` operators into their consumers.
+///
+/// Runs before `PrimitiveCompiler`. LuminAIR operators read their inputs through their
+/// `ShapeTracker`, so a view materialized by `Contiguous` can be read in place whenever
+/// the view is plain, or the consumer's own read is plain and has the same dimensions as
+/// the view (a reshape of the materialized tensor is left in place). The consumer then reads the
+/// original tensor directly, and the `Contiguous`, which has no AIR component and would
+/// run unproven on the CPU, is removed.
+#[derive(Debug, Default)]
+pub struct ViewFusionCompiler();
+
+impl Compiler for ViewFusionCompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, _ids: To) {
+        for node in graph
+            .node_indices()
+            .filter(|n| graph.node_weight(*n).unwrap().as_any().is::<Contiguous>())
+            .sorted()
+            .collect::<Vec<_>>()
+        {
+            if graph.no_delete.contains(&node) || graph.to_retrieve.contains_key(&node) {
+                continue;
+            }
+            let sources = graph.get_sources(node);
+            let [(source, output_order, view)] = sources[..] else {
+                continue;
+            };
+
+            for (edge, dest, weight) in graph
+                .edges_directed(node, petgraph::Direction::Outgoing)
+                .map(|e| (e.id(), e.target(), *e.weight()))
+                .collect::<Vec<_>>()
+            {
+                let Some((input_order, _, shape)) = weight.as_data() else {
+                    continue;
+                };
+                let shape = if is_plain_view(&view) {
+                    shape
+                } else if is_plain_view(&shape) && shape.dims() == view.dims() {
+                    // The consumer can only read the view instead if it doesn't reshape it
+                    view
+                } else {
+                    continue;
+                };
+                graph.add_edge(
+                    source,
+                    dest,
+                    Dependency::Data {
+                        input_order,
+                        output_order,
+                        shape,
+                    },
+                );
+                graph.remove_edge(edge);
+            }
+
+            if graph
+                .edges_directed(node, petgraph::Direction::Outgoing)
+                .next()
+                .is_none()
+            {
+                graph.remove_node(node);
+            }
+        }
+    }
+}
+
+/// A Luminal `Compiler` pass that checks a compiled graph can be executed by LuminAIR.
+///
+/// Runs last, typically after custom passes inserted in the pipeline. Every operator
+/// must read its inputs in the data format its producer writes, `Vec<f32>` on the CPU
+/// and `StwoData` for LuminAIR operators, with copies in between. Returns the first
+/// violation as a `LuminairError::InvalidGraph`.
+#[derive(Debug, Default)]
+pub struct ValidationCompiler();
+
+impl Compiler for ValidationCompiler {
+    type Output = Result<(), LuminairError>;
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, _ids: To) -> Self::Output {
+        let mut edges = graph
+            .graph
+            .edge_references()
+            .filter(|e| e.weight().as_data().is_some())
+            .map(|e| (e.source(), e.target()))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+
+        for (source, dest) in edges {
+            if graph.node_weight(dest).unwrap().as_any().is::<CpuOp>() {
+                return Err(LuminairError::InvalidGraph(format!(
+                    "node {} is still marked by CpuPartitionCompiler",
+                    dest.index()
+                )));
+            }
+            // Copies are no-ops on data already in their output format
+            let op = graph.node_weight(dest).unwrap().as_any();
+            if op.is::<CopyToStwo>() || op.is::<CopyFromStwo>() {
+                continue;
+            }
+            if outputs_f32(graph, source) != inputs_f32(graph, dest) {
+                return Err(LuminairError::InvalidGraph(format!(
+                    "node {} ({:?}) reads node {} ({:?}) without a copy",
+                    dest.index(),
+                    graph.node_weight(dest).unwrap(),
+                    source.index(),
+                    graph.node_weight(source).unwrap()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if `node` is executed by luminal's CPU backend.
+///
+/// That is every operator except inputs, copies and LuminAIR operators.
+pub(crate) fn runs_on_cpu(graph: &Graph, node: NodeIndex) -> bool {
+    let op = graph.node_weight(node).unwrap().as_any();
+    !(op.is::<Function>()
+        || op.is::<CopyToStwo>()
+        || op.is::<CopyFromStwo>()
+        || op.is::<LuminairConstant>()
+        || component_of(graph, node).is_some())
+}
+
+/// Returns true if `node` outputs `Vec<f32>` rather than `StwoData`.
+pub(crate) fn outputs_f32(graph: &Graph, node: NodeIndex) -> bool {
+    let op = graph.node_weight(node).unwrap().as_any();
+    runs_on_cpu(graph, node) || op.is::<Function>() || op.is::<CopyFromStwo>()
+}
+
+/// Returns true if `node` reads `Vec<f32>` rather than `StwoData`.
+fn inputs_f32(graph: &Graph, node: NodeIndex) -> bool {
+    let op = graph.node_weight(node).unwrap().as_any();
+    runs_on_cpu(graph, node) || op.is::<Function>() || op.is::<CopyToStwo>()
+}
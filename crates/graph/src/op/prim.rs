@@ -1,3 +1,4 @@
+use itertools::Itertools;
 use luminair_air::{
     components::{
         add::table::{AddColumn, AddTraceTable, AddTraceTableRow},
@@ -9,6 +10,7 @@ use luminair_air::{
         sqrt::table::{SqrtColumn, SqrtTraceTable, SqrtTraceTableRow},
         sum_reduce::table::{SumReduceColumn, SumReduceTraceTable, SumReduceTraceTableRow},
     },
+    fixed::{to_fixed, RoundingMode},
     pie::NodeInfo,
     DEFAULT_FP_SCALE,
 };
@@ -18,19 +20,28 @@ use luminal::{
 };
 use num_traits::{identities::Zero, One};
 use numerair::Fixed;
-use std::{ops::Deref, sync::Arc};
+use std::{any::TypeId, sync::Arc};
 use stwo_prover::core::fields::m31::{BaseField, M31};
 
 use crate::{
-    data::StwoData,
-    utils::{get_buffer_from_tensor, get_index, is},
+    compress::CompressedData,
+    config::ProjectConfig,
+    data::{CustomData, HalfData, IntegerData, StwoData},
+    quant::QuantizedData,
+    report::{LoweredOp, LoweringReport},
+    sparse::SparseData,
+    utils::{
+        get_buffer_from_tensor, get_index, is, is_plain_view, output_mult, read_mult,
+        reduce_input_index, reference_elementwise, reference_reduce, split_output,
+    },
 };
 
-use super::{IntoOperator, LuminairOperator};
+use super::{IntoOperator, LuminairOperator, LuminairWrapper};
 
 // ================== COPY ==================
 
-/// Operator to convert tensor data from standard `Vec<f32>` to `StwoData` (fixed-point).
+/// Operator to convert tensor data from standard `Vec<f32>`, `IntegerData`, `HalfData`,
+/// `QuantizedData`, `SparseData`, `CompressedData` or `CustomData` to `StwoData` (fixed-point).
 /// No-op if the input tensor is already `StwoData`.
 #[derive(Clone, Debug)]
 pub struct CopyToStwo {}
@@ -48,6 +59,38 @@ impl Operator for CopyToStwo {
             return vec![inp.pop().unwrap().0.cloned()];
         }
 
+        // Convert integer data to StwoData
+        if let Some(int_data) = inp[0].0.borrowed().downcast_ref::<IntegerData>() {
+            return vec![Tensor::new(StwoData::from_integers(int_data))];
+        }
+
+        // Convert half-precision data to StwoData
+        if let Some(half_data) = inp[0].0.borrowed().downcast_ref::<HalfData>() {
+            return vec![Tensor::new(StwoData::from_half(half_data))];
+        }
+
+        // Dequantize INT8 data to StwoData
+        if let Some(quantized) = inp[0].0.borrowed().downcast_ref::<QuantizedData>() {
+            return vec![Tensor::new(StwoData::from_quantized(quantized))];
+        }
+
+        // Densify sparse data to StwoData
+        if let Some(sparse) = inp[0].0.borrowed().downcast_ref::<SparseData>() {
+            let data = StwoData::from_sparse(sparse).unwrap_or_else(|e| panic!("{}", e));
+            return vec![Tensor::new(data)];
+        }
+
+        // Decompress run-length encoded data to StwoData
+        if let Some(compressed) = inp[0].0.borrowed().downcast_ref::<CompressedData>() {
+            let data = compressed.decode().unwrap_or_else(|e| panic!("{}", e));
+            return vec![Tensor::new(StwoData::from_f32(&data))];
+        }
+
+        // Convert user-defined dtypes with their own rules
+        if let Some(custom) = inp[0].0.borrowed().downcast_ref::<CustomData>() {
+            return vec![Tensor::new(StwoData(Arc::new(custom.0.to_fixed())))];
+        }
+
         // Convert Vec<f32> to StwoData
         let cpu_data = inp[0].0.borrowed().downcast_ref::<Vec<f32>>().unwrap();
         vec![Tensor::new(StwoData::from_f32(cpu_data))]
@@ -82,17 +125,35 @@ impl Operator for CopyFromStwo {
 
 /// Represents a constant value within the LuminAIR graph, stored as `StwoData`.
 ///
-/// Currently supports only float constants; dynamic expressions are not yet implemented.
-#[derive(Debug, Clone, PartialEq)]
+/// Expression constants (e.g., depending on a symbolic dimension) are evaluated at
+/// execution time against the graph's dynamic dimensions.
+#[derive(Clone)]
 pub struct LuminairConstant {
     /// The constant value.
     pub value: ConstantValue,
+    /// Dynamic dimensions of the graph, used to evaluate expression constants.
+    dyn_map: *const FxHashMap<char, usize>,
 }
 
 impl LuminairConstant {
     /// Creates a new `LuminairConstant` operator holding the specified value.
-    pub fn new(value: ConstantValue) -> Self {
-        Self { value }
+    pub fn new(value: ConstantValue, dyn_map: *const FxHashMap<char, usize>) -> Self {
+        Self { value, dyn_map }
+    }
+}
+
+// The dynamic dimensions pointer is the graph's, not part of the constant
+impl std::fmt::Debug for LuminairConstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LuminairConstant")
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl PartialEq for LuminairConstant {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
     }
 }
 
@@ -101,14 +162,20 @@ impl Operator for LuminairConstant {
         // Create a new tensor with the constant value
         let value = match &self.value {
             ConstantValue::Float(f) => *f,
-            ConstantValue::Expression(_expr) => {
-                panic!("Dynamic expressions not yet supported")
+            ConstantValue::Expression(expr) => {
+                // SAFETY: `dyn_map` is the pointer of the luminal `Constant` this was lowered
+                // from, to the `dyn_map` field of the graph owning this operator. As for luminal's
+                // own constants, the graph isn't moved once built, outlives its operators, and
+                // doesn't mutate the map while an operator is processed.
+                let dyn_map = unsafe { self.dyn_map.as_ref().unwrap() };
+                expr.exec(dyn_map)
+                    .expect("Unbound dynamic dimension in constant") as f32
             }
         };
 
         // Create and return a single element with the constant value
         let mut data = Vec::with_capacity(1);
-        data.push(Fixed::<DEFAULT_FP_SCALE>::from_f64(value as f64));
+        data.push(to_fixed(value as f64, RoundingMode::Nearest, 0));
         vec![Tensor::new(StwoData(Arc::new(data)))]
     }
 }
@@ -188,19 +255,18 @@ impl LuminairOperator<RecipColumn, RecipTraceTable, ()> for LuminairRecip {
         let input_id: BaseField = node_info.inputs[0].id.into();
         let output_size = inp[0].1.n_elements().to_usize().unwrap();
 
-        let input_mult = if node_info.inputs[0].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-        let out_mult = if node_info.output.is_final_output {
-            BaseField::zero()
-        } else {
-            BaseField::one() * BaseField::from_u32_unchecked(node_info.num_consumers)
-        };
+        let input_valid = inp[0].1.valid_expression();
+        let mut stack: Vec<i64> = vec![];
 
         for (idx, (input_val, out_val, rem_val)) in intermediate_values.into_iter().enumerate() {
             let is_last_idx: u32 = if idx == (output_size - 1) { 1 } else { 0 };
+            let input_mult = read_mult(
+                node_info.inputs[0].is_initializer,
+                &input_valid,
+                &mut stack,
+                idx,
+            );
+            let out_mult = output_mult(node_info, idx);
 
             table.add_row(RecipTraceTableRow {
                 node_id,
@@ -221,6 +287,10 @@ impl LuminairOperator<RecipColumn, RecipTraceTable, ()> for LuminairRecip {
 
         vec![Tensor::new(StwoData(Arc::new(out_data)))]
     }
+
+    fn reference(&self, inp: &[(Vec<f64>, ShapeTracker)]) -> Option<Vec<f64>> {
+        Some(reference_elementwise(inp, |v| 1.0 / v[0]))
+    }
 }
 
 impl Operator for LuminairRecip {
@@ -299,19 +369,18 @@ impl LuminairOperator<SinColumn, SinTraceTable, SinLookup> for LuminairSin {
         let input_id: BaseField = node_info.inputs[0].id.into();
         let output_size = inp[0].1.n_elements().to_usize().unwrap();
 
-        let input_mult = if node_info.inputs[0].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-        let out_mult = if node_info.output.is_final_output {
-            BaseField::zero()
-        } else {
-            BaseField::one() * BaseField::from_u32_unchecked(node_info.num_consumers)
-        };
+        let input_valid = inp[0].1.valid_expression();
+        let mut stack: Vec<i64> = vec![];
 
         for (idx, (input_val, out_val)) in intermediate_values.into_iter().enumerate() {
             let is_last_idx: u32 = if idx == (output_size - 1) { 1 } else { 0 };
+            let input_mult = read_mult(
+                node_info.inputs[0].is_initializer,
+                &input_valid,
+                &mut stack,
+                idx,
+            );
+            let out_mult = output_mult(node_info, idx);
 
             table.add_row(SinTraceTableRow {
                 node_id,
@@ -339,6 +408,10 @@ impl LuminairOperator<SinColumn, SinTraceTable, SinLookup> for LuminairSin {
 
         vec![Tensor::new(StwoData(Arc::new(out_data)))]
     }
+
+    fn reference(&self, inp: &[(Vec<f64>, ShapeTracker)]) -> Option<Vec<f64>> {
+        Some(reference_elementwise(inp, |v| v[0].sin()))
+    }
 }
 
 impl Operator for LuminairSin {
@@ -421,19 +494,18 @@ impl LuminairOperator<SqrtColumn, SqrtTraceTable, ()> for LuminairSqrt {
         let input_id: BaseField = node_info.inputs[0].id.into();
         let output_size = inp[0].1.n_elements().to_usize().unwrap();
 
-        let input_mult = if node_info.inputs[0].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-        let out_mult = if node_info.output.is_final_output {
-            BaseField::zero()
-        } else {
-            BaseField::one() * BaseField::from_u32_unchecked(node_info.num_consumers)
-        };
+        let input_valid = inp[0].1.valid_expression();
+        let mut stack: Vec<i64> = vec![];
 
         for (idx, (input_val, out_val, rem_val)) in intermediate_values.into_iter().enumerate() {
             let is_last_idx: u32 = if idx == (output_size - 1) { 1 } else { 0 };
+            let input_mult = read_mult(
+                node_info.inputs[0].is_initializer,
+                &input_valid,
+                &mut stack,
+                idx,
+            );
+            let out_mult = output_mult(node_info, idx);
 
             table.add_row(SqrtTraceTableRow {
                 node_id,
@@ -454,6 +526,10 @@ impl LuminairOperator<SqrtColumn, SqrtTraceTable, ()> for LuminairSqrt {
 
         vec![Tensor::new(StwoData(Arc::new(out_data)))]
     }
+
+    fn reference(&self, inp: &[(Vec<f64>, ShapeTracker)]) -> Option<Vec<f64>> {
+        Some(reference_elementwise(inp, |v| v[0].sqrt()))
+    }
 }
 
 impl Operator for LuminairSqrt {
@@ -543,24 +619,24 @@ impl LuminairOperator<AddColumn, AddTraceTable, ()> for LuminairAdd {
         let lhs_id: BaseField = node_info.inputs[0].id.into();
         let rhs_id: BaseField = node_info.inputs[1].id.into();
 
-        let lhs_mult = if node_info.inputs[0].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-        let rhs_mult = if node_info.inputs[1].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-        let out_mult = if node_info.output.is_final_output {
-            BaseField::zero()
-        } else {
-            BaseField::one() * BaseField::from_u32_unchecked(node_info.num_consumers)
-        };
+        let (lhs_valid, rhs_valid) = (inp[0].1.valid_expression(), inp[1].1.valid_expression());
+        let mut stack: Vec<i64> = vec![];
 
         for (idx, (lhs_val, rhs_val, out_val)) in intermediate_values.into_iter().enumerate() {
             let is_last_idx: u32 = if idx == (output_size - 1) { 1 } else { 0 };
+            let lhs_mult = read_mult(
+                node_info.inputs[0].is_initializer,
+                &lhs_valid,
+                &mut stack,
+                idx,
+            );
+            let rhs_mult = read_mult(
+                node_info.inputs[1].is_initializer,
+                &rhs_valid,
+                &mut stack,
+                idx,
+            );
+            let out_mult = output_mult(node_info, idx);
 
             table.add_row(AddTraceTableRow {
                 node_id,
@@ -583,6 +659,10 @@ impl LuminairOperator<AddColumn, AddTraceTable, ()> for LuminairAdd {
 
         vec![Tensor::new(StwoData(Arc::new(out_data)))]
     }
+
+    fn reference(&self, inp: &[(Vec<f64>, ShapeTracker)]) -> Option<Vec<f64>> {
+        Some(reference_elementwise(inp, |v| v[0] + v[1]))
+    }
 }
 
 impl Operator for LuminairAdd {
@@ -672,26 +752,26 @@ impl LuminairOperator<MulColumn, MulTraceTable, ()> for LuminairMul {
         let lhs_id: BaseField = node_info.inputs[0].id.into();
         let rhs_id: BaseField = node_info.inputs[1].id.into();
 
-        let lhs_mult = if node_info.inputs[0].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-        let rhs_mult = if node_info.inputs[1].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-        let out_mult = if node_info.output.is_final_output {
-            BaseField::zero()
-        } else {
-            BaseField::one() * BaseField::from_u32_unchecked(node_info.num_consumers)
-        };
+        let (lhs_valid, rhs_valid) = (inp[0].1.valid_expression(), inp[1].1.valid_expression());
+        let mut stack: Vec<i64> = vec![];
 
         for (idx, (lhs_val, rhs_val, out_val, rem_val)) in
             intermediate_values.into_iter().enumerate()
         {
             let is_last_idx: u32 = if idx == (output_size - 1) { 1 } else { 0 };
+            let lhs_mult = read_mult(
+                node_info.inputs[0].is_initializer,
+                &lhs_valid,
+                &mut stack,
+                idx,
+            );
+            let rhs_mult = read_mult(
+                node_info.inputs[1].is_initializer,
+                &rhs_valid,
+                &mut stack,
+                idx,
+            );
+            let out_mult = output_mult(node_info, idx);
 
             table.add_row(MulTraceTableRow {
                 node_id,
@@ -715,6 +795,10 @@ impl LuminairOperator<MulColumn, MulTraceTable, ()> for LuminairMul {
 
         vec![Tensor::new(StwoData(Arc::new(out_data)))]
     }
+
+    fn reference(&self, inp: &[(Vec<f64>, ShapeTracker)]) -> Option<Vec<f64>> {
+        Some(reference_elementwise(inp, |v| v[0] * v[1]))
+    }
 }
 
 impl Operator for LuminairMul {
@@ -726,18 +810,20 @@ impl Operator for LuminairMul {
 
 // ================== REDUCE ==================
 
-/// LuminAIR operator for sum reduction along a specified dimension.
+/// LuminAIR operator for sum reduction along one or more contiguous dimensions.
+///
+/// Holds the first reduced dimension and the number of reduced dimensions.
 ///
 /// Implements both the standard `Operator` trait for graph execution and the
 /// `LuminairOperator` trait to generate trace entries for `SumReduceTraceTable`,
 /// capturing the accumulation process step-by-step.
 #[derive(Debug, Clone, Default, PartialEq)]
-struct LuminairSumReduce(pub usize);
+struct LuminairSumReduce(pub usize, pub usize);
 
 impl LuminairSumReduce {
     /// Creates a new `LuminairSumReduce` operator instance for the given reduction dimension.
     pub fn new(value: usize) -> Self {
-        Self(value)
+        Self(value, 1)
     }
 }
 
@@ -761,8 +847,8 @@ impl LuminairSumReduce {
     ) {
         let sh = inp[0].1.shape_usize();
         let front_size = sh.iter().take(self.0).product::<usize>().max(1);
-        let back_size = sh.iter().skip(self.0 + 1).product::<usize>().max(1);
-        let dim_size = sh[self.0];
+        let back_size = sh.iter().skip(self.0 + self.1).product::<usize>().max(1);
+        let dim_size = sh[self.0..self.0 + self.1].iter().product::<usize>();
 
         let output_size = front_size * back_size;
         let mut out_data = vec![Fixed::<DEFAULT_FP_SCALE>::zero(); output_size];
@@ -823,21 +909,21 @@ impl LuminairOperator<SumReduceColumn, SumReduceTraceTable, ()> for LuminairSumR
         let input_id: BaseField = node_info.inputs[0].id.into();
         let output_size = out_data.len();
 
-        let input_mult = if node_info.inputs[0].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-        let out_mult = if node_info.output.is_final_output {
-            BaseField::zero()
-        } else {
-            BaseField::one() * BaseField::from_u32_unchecked(node_info.num_consumers)
-        };
+        let input_valid = inp[0].1.valid_expression();
+        let shape = inp[0].1.shape_usize();
+        let mut stack: Vec<i64> = vec![];
 
-        for entry in intermediate_values {
+        for (row, entry) in intermediate_values.into_iter().enumerate() {
             let (idx, input_val, out_val, acc, next_acc, is_last_step) = entry;
 
-            let out_mult = out_mult * is_last_step;
+            let input_index = reduce_input_index(&shape, self.0..self.0 + self.1, row);
+            let input_mult = read_mult(
+                node_info.inputs[0].is_initializer,
+                &input_valid,
+                &mut stack,
+                input_index,
+            );
+            let out_mult = output_mult(node_info, idx) * is_last_step;
 
             let is_last_idx: u32 = if idx == (output_size - 1) { 1 } else { 0 };
 
@@ -861,6 +947,10 @@ impl LuminairOperator<SumReduceColumn, SumReduceTraceTable, ()> for LuminairSumR
 
         vec![Tensor::new(StwoData(Arc::new(out_data)))]
     }
+
+    fn reference(&self, inp: &[(Vec<f64>, ShapeTracker)]) -> Option<Vec<f64>> {
+        Some(reference_reduce(inp, self.0..self.0 + self.1, |a, b| a + b))
+    }
 }
 
 impl Operator for LuminairSumReduce {
@@ -870,18 +960,20 @@ impl Operator for LuminairSumReduce {
     }
 }
 
-/// LuminAIR operator for max reduction along a specified dimension.
+/// LuminAIR operator for max reduction along one or more contiguous dimensions.
+///
+/// Holds the first reduced dimension and the number of reduced dimensions.
 ///
 /// Implements both the standard `Operator` trait for graph execution and the
 /// `LuminairOperator` trait to generate trace entries for `MaxReduceTraceTable`,
 /// capturing the comparison and update process step-by-step.
 #[derive(Debug, Clone, Default, PartialEq)]
-struct LuminairMaxReduce(pub usize);
+struct LuminairMaxReduce(pub usize, pub usize);
 
 impl LuminairMaxReduce {
     /// Creates a new `LuminairMaxReduce` operator instance for the given reduction dimension.
     pub fn new(value: usize) -> Self {
-        Self(value)
+        Self(value, 1)
     }
 }
 
@@ -906,8 +998,8 @@ impl LuminairMaxReduce {
     ) {
         let sh = inp[0].1.shape_usize();
         let front_size = sh.iter().take(self.0).product::<usize>().max(1);
-        let back_size = sh.iter().skip(self.0 + 1).product::<usize>().max(1);
-        let dim_size = sh[self.0];
+        let back_size = sh.iter().skip(self.0 + self.1).product::<usize>().max(1);
+        let dim_size = sh[self.0..self.0 + self.1].iter().product::<usize>();
 
         let output_size = front_size * back_size;
         let mut out_data = vec![Fixed::<DEFAULT_FP_SCALE>::zero(); output_size];
@@ -994,22 +1086,21 @@ impl LuminairOperator<MaxReduceColumn, MaxReduceTraceTable, ()> for LuminairMaxR
         let input_id: BaseField = node_info.inputs[0].id.into();
         let output_size = out_data.len();
 
-        let input_mult = if node_info.inputs[0].is_initializer {
-            BaseField::zero()
-        } else {
-            -BaseField::one()
-        };
-
-        let out_mult = if node_info.output.is_final_output {
-            BaseField::zero()
-        } else {
-            BaseField::one() * BaseField::from_u32_unchecked(node_info.num_consumers)
-        };
+        let input_valid = inp[0].1.valid_expression();
+        let shape = inp[0].1.shape_usize();
+        let mut stack: Vec<i64> = vec![];
 
-        for entry in intermediate_values {
+        for (row, entry) in intermediate_values.into_iter().enumerate() {
             let (idx, input_val, out_val, max_val, next_max_val, is_max, is_last_step_flag) = entry;
 
-            let out_mult = out_mult * is_last_step_flag;
+            let input_index = reduce_input_index(&shape, self.0..self.0 + self.1, row);
+            let input_mult = read_mult(
+                node_info.inputs[0].is_initializer,
+                &input_valid,
+                &mut stack,
+                input_index,
+            );
+            let out_mult = output_mult(node_info, idx) * is_last_step_flag;
 
             let is_last_idx: u32 = if idx == (output_size - 1) { 1 } else { 0 };
 
@@ -1034,6 +1125,10 @@ impl LuminairOperator<MaxReduceColumn, MaxReduceTraceTable, ()> for LuminairMaxR
 
         vec![Tensor::new(StwoData(Arc::new(out_data)))]
     }
+
+    fn reference(&self, inp: &[(Vec<f64>, ShapeTracker)]) -> Option<Vec<f64>> {
+        Some(reference_reduce(inp, self.0..self.0 + self.1, f64::max))
+    }
 }
 
 impl Operator for LuminairMaxReduce {
@@ -1045,6 +1140,23 @@ impl Operator for LuminairMaxReduce {
 
 // ================== COMPILER ==================
 
+/// Returns `true` if `op` is a Luminal primitive that `PrimitiveCompiler` can't lower.
+///
+/// Such nodes would receive `StwoData` at execution time and fail.
+///
+/// None of them has an AIR component: `LessThan` (used by `relu` and `max`) needs a range
+/// checked comparison, `Exp2` and `Log2` (used by `exp` and `log`) need a lookup table like
+/// `sin`, and `Mod` needs a division gadget. Until those components exist, graphs using them
+/// are rejected by `check_supported_ops`.
+pub(crate) fn is_unsupported_op(op: &dyn Operator) -> bool {
+    let op = op.as_any();
+    op.is::<Log2>()
+        || op.is::<Exp2>()
+        || op.is::<Contiguous>()
+        || op.is::<Mod>()
+        || op.is::<LessThan>()
+}
+
 /// A Luminal `Compiler` pass that adapts a standard computation graph for LuminAIR.
 ///
 /// This compiler performs two main tasks:
@@ -1053,14 +1165,39 @@ impl Operator for LuminairMaxReduce {
 ///    and the `StwoData` format used internally by LuminAIR operators.
 /// 2. **Replaces Primitive Operators:** Substitutes standard Luminal operators (e.g., `luminal::op::Add`)
 ///    with their LuminAIR counterparts (e.g., `LuminairAdd`) that implement trace generation.
-#[derive(Default)]
-pub struct PrimitiveCompiler();
+///
+/// Operators whose component is listed in the project's `ops.cpu` setting are not replaced,
+/// and run unproven on the CPU.
+pub struct PrimitiveCompiler {
+    /// Components whose operators are left on the CPU.
+    cpu_ops: Vec<String>,
+}
+
+impl Default for PrimitiveCompiler {
+    /// Applies the project configuration, from `luminair.toml` if there is one.
+    ///
+    /// Panics if the configuration is invalid, rather than proving a different set of
+    /// operators than configured.
+    fn default() -> Self {
+        let config = ProjectConfig::load().unwrap_or_else(|e| panic!("{}", e));
+        Self::new(&config)
+    }
+}
+
+impl PrimitiveCompiler {
+    pub fn new(config: &ProjectConfig) -> Self {
+        Self {
+            cpu_ops: config.ops.cpu.clone(),
+        }
+    }
+}
 
 impl Compiler for PrimitiveCompiler {
-    type Output = ();
+    type Output = LoweringReport;
 
     /// Executes the compilation pass on the graph.
-    /// Modifies the graph in-place to insert copy operators and replace primitives.
+    /// Modifies the graph in-place to insert copy operators and replace primitives,
+    /// and reports what each operator was replaced with.
     fn compile<T: ToIdsMut>(&self, graph: &mut Graph, mut ids: T) -> Self::Output {
         // Go through the graph and insert copy ops.
         // Copy Function nodes (data input/output)
@@ -1103,9 +1240,10 @@ impl Compiler for PrimitiveCompiler {
                 .map(|e| (e.source(), e.id(), *e.weight()))
                 .collect::<Vec<_>>()
             {
+                let (output, edge_weight) = split_output(edge_weight);
                 let copy_from_node = graph
                     .add_op(CopyFromStwo::new())
-                    .input(source, 0, ShapeTracker::new(()))
+                    .input(source, output, ShapeTracker::new(()))
                     .finish();
                 graph.add_edge(copy_from_node, function_node, edge_weight);
                 graph.remove_edge(edge);
@@ -1113,12 +1251,14 @@ impl Compiler for PrimitiveCompiler {
         }
 
         // Add CopyFromStwo for retrieved outputs
-        for (output_node, (_, output_shape)) in graph
+        for (output_node, (output_index, output_shape)) in graph
             .to_retrieve
             .iter()
             .map(|(a, b)| (*a, *b))
             // Filter to non-functions
             .filter(|(n, _)| !graph.node_weight(*n).unwrap().as_any().is::<LFunction>())
+            // Insert copies in a stable order, whatever the map's iteration order
+            .sorted_by_key(|(n, _)| *n)
             .collect::<Vec<_>>()
         {
             if graph
@@ -1140,47 +1280,175 @@ impl Compiler for PrimitiveCompiler {
                 // Create copy node
                 let copy_node = graph
                     .add_op(CopyFromStwo::new())
-                    .input(output_node, 0, output_shape)
+                    .input(output_node, output_index, output_shape)
                     .finish();
+                // Keep the fixed-point output so `data_custom` can read it without going through f32
+                graph.no_delete.insert(output_node);
 
                 remap(output_node, copy_node, &mut ids, graph);
             }
         }
 
         // Replace Luminal's ops with LuminAIR ops
-        for id in graph.node_indices().collect::<Vec<_>>() {
+        let mut report = LoweringReport::default();
+        let mut nodes = graph.node_indices().collect::<Vec<_>>();
+        nodes.sort_unstable();
+        for id in nodes {
             let op = graph.node_weight(id).unwrap().as_any().type_id();
+            if is::<LFunction>(op) || is::<CopyToStwo>(op) || is::<CopyFromStwo>(op) {
+                continue;
+            }
+            let original = format!("{:?}", graph.node_weight(id).unwrap());
+            let input_shapes = graph
+                .get_sources(id)
+                .iter()
+                .map(|(_, _, st)| format!("{:?}", st.shape()))
+                .collect();
             let op_ref = graph.graph.node_weight_mut(id).unwrap();
 
-            if let Some(c) = op_ref.as_any().downcast_ref::<luminal::op::Constant>() {
-                *op_ref = Box::new(LuminairConstant::new(c.0.clone()));
-            } else if is::<luminal::op::Add>(op) {
-                *op_ref = LuminairAdd::new().into_operator()
-            } else if is::<luminal::op::Mul>(op) {
-                *op_ref = LuminairMul::new().into_operator()
-            } else if is::<luminal::op::Recip>(op) {
-                *op_ref = LuminairRecip::new().into_operator()
-            } else if is::<luminal::op::Sin>(op) {
-                *op_ref = LuminairSin::new().into_operator()
-            } else if is::<luminal::op::SumReduce>(op) {
-                let dim_index =
-                    if let Some(sum_reduce) = op_ref.deref().as_any().downcast_ref::<SumReduce>() {
-                        sum_reduce.0 // Access the usize field (the 0 in SumReduce(0))
-                    } else {
-                        0
-                    };
-                *op_ref = LuminairSumReduce::new(dim_index).into_operator()
-            } else if is::<luminal::op::MaxReduce>(op) {
-                let dim_index =
-                    if let Some(max_reduce) = op_ref.deref().as_any().downcast_ref::<MaxReduce>() {
-                        max_reduce.0 // Access the usize field
-                    } else {
-                        0
-                    };
-                *op_ref = LuminairMaxReduce::new(dim_index).into_operator()
-            } else if is::<luminal::op::Sqrt>(op) {
-                *op_ref = LuminairSqrt::new().into_operator()
+            if self
+                .cpu_ops
+                .iter()
+                .any(|component| primitive_of(component) == Some(op))
+            {
+                // Left to luminal's CPU backend by the project configuration
+            } else if let Some(c) = op_ref.as_any().downcast_ref::<luminal::op::Constant>() {
+                *op_ref = Box::new(LuminairConstant::new(c.0.clone(), c.1));
+            } else if let Some(lowered) = lower_primitive(op_ref.as_ref()) {
+                *op_ref = lowered;
             }
+
+            let lowered = graph.node_weight(id).unwrap();
+            report.ops.push(LoweredOp {
+                node: id.index() as u32,
+                original,
+                lowered: (lowered.as_any().type_id() != op).then(|| format!("{:?}", lowered)),
+                input_shapes,
+            });
+        }
+
+        report
+    }
+}
+
+/// Returns the LuminAIR operator replacing the Luminal primitive `op`, if it has an AIR component.
+pub(crate) fn lower_primitive(op: &dyn Operator) -> Option<Box<dyn Operator>> {
+    let op = op.as_any();
+    Some(if op.is::<luminal::op::Add>() {
+        LuminairAdd::new().into_operator()
+    } else if op.is::<luminal::op::Mul>() {
+        LuminairMul::new().into_operator()
+    } else if op.is::<luminal::op::Recip>() {
+        LuminairRecip::new().into_operator()
+    } else if op.is::<luminal::op::Sin>() {
+        LuminairSin::new().into_operator()
+    } else if let Some(sum_reduce) = op.downcast_ref::<SumReduce>() {
+        LuminairSumReduce::new(sum_reduce.0).into_operator()
+    } else if let Some(max_reduce) = op.downcast_ref::<MaxReduce>() {
+        LuminairMaxReduce::new(max_reduce.0).into_operator()
+    } else if op.is::<luminal::op::Sqrt>() {
+        LuminairSqrt::new().into_operator()
+    } else {
+        return None;
+    })
+}
+
+/// Returns the type of the Luminal primitive proven by `component`.
+fn primitive_of(component: &str) -> Option<TypeId> {
+    Some(match component {
+        "add" => TypeId::of::<luminal::op::Add>(),
+        "mul" => TypeId::of::<luminal::op::Mul>(),
+        "recip" => TypeId::of::<luminal::op::Recip>(),
+        "sin" => TypeId::of::<luminal::op::Sin>(),
+        "sum_reduce" => TypeId::of::<luminal::op::SumReduce>(),
+        "max_reduce" => TypeId::of::<luminal::op::MaxReduce>(),
+        "sqrt" => TypeId::of::<luminal::op::Sqrt>(),
+        _ => return None,
+    })
+}
+
+/// Returns the reduction performed by `node` as `(is_max, first dim, number of dims)`,
+/// if it is a LuminAIR reduce operator.
+fn reduce_of(graph: &Graph, node: NodeIndex) -> Option<(bool, usize, usize)> {
+    let op = graph.node_weight(node).unwrap().as_any();
+    if let Some(wrapper) =
+        op.downcast_ref::<LuminairWrapper<SumReduceColumn, SumReduceTraceTable, ()>>()
+    {
+        let reduce = wrapper.0.as_any().downcast_ref::<LuminairSumReduce>()?;
+        Some((false, reduce.0, reduce.1))
+    } else if let Some(wrapper) =
+        op.downcast_ref::<LuminairWrapper<MaxReduceColumn, MaxReduceTraceTable, ()>>()
+    {
+        let reduce = wrapper.0.as_any().downcast_ref::<LuminairMaxReduce>()?;
+        Some((true, reduce.0, reduce.1))
+    } else {
+        None
+    }
+}
+
+/// A Luminal `Compiler` pass that merges chained reductions into a single operator.
+///
+/// Runs after `PrimitiveCompiler`. Luminal reduces over several dimensions with one
+/// `SumReduce` or `MaxReduce` per dimension. When consecutive reductions of the same kind
+/// cover contiguous dimensions, they are replaced by one operator reducing them all at once,
+/// which removes the intermediate tensors and their trace rows.
+#[derive(Debug, Default)]
+pub struct ReduceFusionCompiler();
+
+impl Compiler for ReduceFusionCompiler {
+    type Output = ();
+
+    fn compile<To: ToIdsMut>(&self, graph: &mut Graph, mut ids: To) {
+        let mut nodes = graph.node_indices().collect::<Vec<_>>();
+        nodes.sort_unstable();
+        for node in nodes {
+            // Sources fused into an earlier node are removed
+            if !graph.graph.contains_node(node) {
+                continue;
+            }
+            let Some((is_max, dim, n_dims)) = reduce_of(graph, node) else {
+                continue;
+            };
+            let sources = graph.get_sources(node);
+            let [(source, _, shape)] = sources[..] else {
+                continue;
+            };
+            let Some((source_is_max, source_dim, source_n_dims)) = reduce_of(graph, source) else {
+                continue;
+            };
+
+            // The source must be read as is, by this node only
+            if source_is_max != is_max
+                || !is_plain_view(&shape)
+                || graph
+                    .edges_directed(source, petgraph::Direction::Outgoing)
+                    .count()
+                    != 1
+                || graph.no_delete.contains(&source)
+                || graph.to_retrieve.contains_key(&source)
+            {
+                continue;
+            }
+            // The reduced dimensions must be contiguous in the source's input
+            if source_dim < dim || source_dim > dim + n_dims {
+                continue;
+            }
+
+            let fused = if is_max {
+                LuminairMaxReduce(dim, n_dims + source_n_dims).into_operator()
+            } else {
+                LuminairSumReduce(dim, n_dims + source_n_dims).into_operator()
+            };
+            *graph.graph.node_weight_mut(node).unwrap() = fused;
+
+            let (input, weight) = graph
+                .edges_directed(source, petgraph::Direction::Incoming)
+                .map(|e| (e.source(), *e.weight()))
+                .next()
+                .unwrap();
+            graph.add_edge(input, node, weight);
+            remap(source, node, &mut ids, graph);
+            graph.remove_node(source);
         }
     }
 }
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// Fixed-point error of a single operator, measured against an `f64` reference.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OpPrecision {
+    /// The unique ID of the node.
+    pub node: u32,
+    /// Description of the operator.
+    pub op: String,
+    /// Largest absolute difference between the fixed-point output and the reference.
+    pub max_abs_error: f64,
+    /// Mean absolute difference between the fixed-point output and the reference.
+    pub mean_abs_error: f64,
+    /// Index of the output element with the largest error.
+    pub max_error_index: usize,
+}
+
+impl OpPrecision {
+    /// Compares the fixed-point output of a node with its reference output.
+    pub(crate) fn measure(node: u32, op: String, output: &[f64], reference: &[f64]) -> Self {
+        let (mut max_abs_error, mut max_error_index, mut total) = (0f64, 0, 0f64);
+        for (i, (out, expected)) in output.iter().zip(reference.iter()).enumerate() {
+            let error = (out - expected).abs();
+            total += error;
+            if error > max_abs_error {
+                max_abs_error = error;
+                max_error_index = i;
+            }
+        }
+
+        Self {
+            node,
+            op,
+            max_abs_error,
+            mean_abs_error: total / output.len().max(1) as f64,
+            max_error_index,
+        }
+    }
+}
+
+/// Per-operator fixed-point error of a graph execution.
+///
+/// Generated by `LuminairGraph::validate_precision`. Errors accumulate: an operator's
+/// error includes the error propagated from its inputs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct PrecisionReport {
+    /// Errors of each operator, in execution order.
+    pub ops: Vec<OpPrecision>,
+}
+
+impl PrecisionReport {
+    /// Returns the operator with the largest absolute error, if any.
+    pub fn worst(&self) -> Option<&OpPrecision> {
+        self.ops
+            .iter()
+            .max_by(|a, b| a.max_abs_error.total_cmp(&b.max_abs_error))
+    }
+
+    /// Returns the largest absolute error across all operators.
+    pub fn max_abs_error(&self) -> f64 {
+        self.worst().map_or(0.0, |op| op.max_abs_error)
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::Instant;
+
+use luminair_prover::prover::prove;
+use luminair_utils::LuminairError;
+use luminair_verifier::verifier::verify;
+use luminal::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::graph::LuminairGraph;
+
+/// A timed span of a profiled run.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimelineEvent {
+    /// Name of the stage or operator.
+    pub name: String,
+    /// `"stage"` for pipeline stages, `"node"` for operators executed during trace generation.
+    pub category: String,
+    /// Start time, in microseconds since the start of the run.
+    pub start_us: u64,
+    /// Duration, in microseconds.
+    pub duration_us: u64,
+}
+
+/// Timeline of a full trace generation, proving and verification run.
+///
+/// Generated by `profile_graph`, and rendered with `to_chrome_trace` for viewing
+/// in `chrome://tracing` or Perfetto.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Timeline {
+    /// Stages and operators, in start order.
+    pub events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    fn push(&mut self, name: impl Into<String>, category: &str, start_us: u64, duration_us: u64) {
+        self.events.push(TimelineEvent {
+            name: name.into(),
+            category: category.to_string(),
+            start_us,
+            duration_us,
+        });
+    }
+
+    /// Records a stage that started at `start`, and ends now.
+    fn push_stage(&mut self, name: &str, run_start: Instant, start: Instant) {
+        let (start_us, duration_us) =
+            ((start - run_start).as_micros(), start.elapsed().as_micros());
+        self.push(name, "stage", start_us as u64, duration_us as u64);
+    }
+
+    /// Returns the operators, slowest first.
+    pub fn slowest_nodes(&self) -> Vec<&TimelineEvent> {
+        let mut nodes = self
+            .events
+            .iter()
+            .filter(|e| e.category == "node")
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|e| std::cmp::Reverse(e.duration_us));
+        nodes
+    }
+
+    /// Renders the timeline in the Chrome trace event format.
+    pub fn to_chrome_trace(&self) -> Result<String, LuminairError> {
+        let events = self
+            .events
+            .iter()
+            .map(|e| {
+                json!({
+                    "name": e.name,
+                    "cat": e.category,
+                    "ph": "X",
+                    "ts": e.start_us,
+                    "dur": e.duration_us,
+                    "pid": 0,
+                    "tid": 0,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_string(&json!({ "traceEvents": events })).map_err(|e| {
+            LuminairError::SerializationError(format!("Failed to serialize timeline: {}", e))
+        })
+    }
+}
+
+/// Generates the trace of, proves and verifies a compiled graph, recording the time
+/// spent in each stage and in each operator.
+pub fn profile_graph(graph: &mut Graph) -> Result<Timeline, LuminairError> {
+    let mut timeline = Timeline::default();
+    let run_start = Instant::now();
+
+    let start = Instant::now();
+    let mut settings = graph.gen_circuit_settings();
+    timeline.push_stage("circuit_settings", run_start, start);
+
+    let start = Instant::now();
+    let pie = graph.gen_trace(&mut settings)?;
+    timeline.push_stage("trace_generation", run_start, start);
+    let trace_start_us = (start - run_start).as_micros() as u64;
+    for timing in &pie.execution_resources.node_timings {
+        timeline.push(
+            format!("{} ({})", timing.op, timing.node),
+            "node",
+            trace_start_us + timing.start_us,
+            timing.duration_us,
+        );
+    }
+
+    let start = Instant::now();
+    let proof = prove(pie, settings.clone())?;
+    timeline.push_stage("proving", run_start, start);
+
+    let start = Instant::now();
+    verify(proof, settings)?;
+    timeline.push_stage("verification", run_start, start);
+
+    timeline.events.sort_by_key(|e| e.start_us);
+    Ok(timeline)
+}
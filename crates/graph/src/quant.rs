@@ -0,0 +1,188 @@
+use luminal::prelude::*;
+
+/// Granularity of the scales used to quantize a tensor.
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuantGranularity {
+    /// A single scale for the whole tensor.
+    PerTensor,
+    /// One scale per slice along `axis`.
+    ///
+    /// `channel_stride` is the number of contiguous elements sharing a channel,
+    /// i.e. the product of the dimensions after `axis`.
+    PerChannel {
+        axis: usize,
+        channels: usize,
+        channel_stride: usize,
+    },
+    /// One scale per block of `block_size` contiguous elements, as in GGML block formats.
+    PerBlock { block_size: usize },
+}
+
+/// Symmetric INT8 tensor data with its dequantization scales.
+///
+/// Values are dequantized as `q * scale` when the tensor enters the circuit
+/// (in `CopyToStwo`), so a single `QuantizedData` can be bound to an input
+/// tensor instead of the original `f32` weights.
+///
+/// This is a storage format only: it shrinks weights on the host, not the proof.
+/// There are no integer AIR components or requantization operators, and every
+/// operator is proven in fixed point. Trace cells are M31 elements whatever the
+/// width of the values they hold, so INT8 kernels would not reduce the trace size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantizedData {
+    /// Quantized values, in row-major order.
+    pub values: Vec<i8>,
+    /// Dequantization scales (one for per-tensor, `channels` for per-channel, one per block).
+    pub scales: Vec<f32>,
+    /// Granularity of `scales`.
+    pub granularity: QuantGranularity,
+}
+
+impl QuantizedData {
+    /// Quantizes `data` with a single symmetric scale.
+    pub fn per_tensor(data: &[f32]) -> Self {
+        let scale = symmetric_scale(data.iter().copied());
+        Self {
+            values: data.iter().map(|&v| quantize(v, scale)).collect(),
+            scales: vec![scale],
+            granularity: QuantGranularity::PerTensor,
+        }
+    }
+
+    /// Quantizes `data` of the given `shape` with one symmetric scale per slice along `axis`.
+    pub fn per_channel(data: &[f32], shape: &[usize], axis: usize) -> Self {
+        assert!(axis < shape.len(), "Quantization axis out of bounds");
+        assert_eq!(
+            data.len(),
+            shape.iter().product::<usize>(),
+            "Data length doesn't match the shape"
+        );
+
+        let channels = shape[axis];
+        let channel_stride = shape.iter().skip(axis + 1).product::<usize>().max(1);
+        let channel_of = |i: usize| (i / channel_stride) % channels;
+
+        let scales = (0..channels)
+            .map(|c| {
+                symmetric_scale(
+                    data.iter()
+                        .enumerate()
+                        .filter(|(i, _)| channel_of(*i) == c)
+                        .map(|(_, &v)| v),
+                )
+            })
+            .collect::<Vec<_>>();
+        let values = data
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| quantize(v, scales[channel_of(i)]))
+            .collect();
+
+        Self {
+            values,
+            scales,
+            granularity: QuantGranularity::PerChannel {
+                axis,
+                channels,
+                channel_stride,
+            },
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the dequantization scale applying to element `i`.
+    pub fn scale_at(&self, i: usize) -> f32 {
+        match self.granularity {
+            QuantGranularity::PerTensor => self.scales[0],
+            QuantGranularity::PerChannel {
+                channels,
+                channel_stride,
+                ..
+            } => self.scales[(i / channel_stride) % channels],
+            QuantGranularity::PerBlock { block_size } => self.scales[i / block_size],
+        }
+    }
+
+    /// Dequantizes the tensor back to `f32` values.
+    pub fn dequantize(&self) -> Vec<f32> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, &q)| q as f32 * self.scale_at(i))
+            .collect()
+    }
+}
+
+impl Data for QuantizedData {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Computes the symmetric scale mapping the largest magnitude to `i8::MAX`.
+/// Returns 1.0 for all-zero data so dequantization stays well defined.
+fn symmetric_scale(values: impl Iterator<Item = f32>) -> f32 {
+    let max_abs = values.fold(0f32, |acc, v| acc.max(v.abs()));
+    if max_abs == 0.0 {
+        1.0
+    } else {
+        max_abs / i8::MAX as f32
+    }
+}
+
+/// Quantizes a single value with the given scale, saturating to the INT8 range.
+fn quantize(value: f32, scale: f32) -> i8 {
+    (value / scale)
+        .round()
+        .clamp(-(i8::MAX as f32), i8::MAX as f32) as i8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_tensor_round_trip() {
+        let data = [0.5, -1.0, 0.25, 0.0];
+        let quantized = QuantizedData::per_tensor(&data);
+
+        assert_eq!(quantized.scales.len(), 1);
+        assert_eq!(quantized.values[1], -i8::MAX);
+        for (a, b) in quantized.dequantize().iter().zip(data.iter()) {
+            assert!((a - b).abs() <= quantized.scales[0] / 2.0);
+        }
+    }
+
+    #[test]
+    fn test_per_channel_scales() {
+        // Two rows with very different magnitudes.
+        let data = [0.01, -0.02, 10.0, -5.0];
+        let quantized = QuantizedData::per_channel(&data, &[2, 2], 0);
+
+        assert_eq!(quantized.scales.len(), 2);
+        assert_eq!(quantized.values[1], -i8::MAX);
+        assert_eq!(quantized.values[2], i8::MAX);
+        assert_eq!(quantized.scale_at(0), quantized.scales[0]);
+        assert_eq!(quantized.scale_at(3), quantized.scales[1]);
+    }
+
+    #[test]
+    fn test_all_zero_data() {
+        let quantized = QuantizedData::per_tensor(&[0.0; 4]);
+        assert_eq!(quantized.scales, [1.0]);
+        assert_eq!(quantized.dequantize(), [0.0; 4]);
+    }
+}
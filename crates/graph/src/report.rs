@@ -0,0 +1,49 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// What `PrimitiveCompiler` did with a single Luminal node.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LoweredOp {
+    /// The unique ID of the node.
+    pub node: u32,
+    /// The original Luminal operator.
+    pub original: String,
+    /// The LuminAIR operator it was replaced with, `None` if it was left to luminal's CPU backend.
+    pub lowered: Option<String>,
+    /// Shapes of the operator's inputs.
+    pub input_shapes: Vec<String>,
+}
+
+/// Node-by-node report of a `PrimitiveCompiler` pass.
+///
+/// Returned by `PrimitiveCompiler::compile`, and printable with `Display`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LoweringReport {
+    /// Every operator of the graph, in node order. Inputs and copies are not listed.
+    pub ops: Vec<LoweredOp>,
+}
+
+impl LoweringReport {
+    /// Returns the operators that were not lowered and run unproven on the CPU.
+    pub fn fallbacks(&self) -> impl Iterator<Item = &LoweredOp> {
+        self.ops.iter().filter(|op| op.lowered.is_none())
+    }
+}
+
+impl fmt::Display for LoweringReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for op in &self.ops {
+            let lowered = op.lowered.as_deref().unwrap_or("CPU fallback");
+            writeln!(
+                f,
+                "node {}: {} -> {} [{}]",
+                op.node,
+                op.original,
+                lowered,
+                op.input_shapes.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,613 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::Read,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use luminair_utils::LuminairError;
+use luminal::prelude::*;
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Response, Server};
+
+use crate::{
+    config::ProjectConfig,
+    description::{Model, ModelDescription},
+    metrics::Metrics,
+    npy::NpyArray,
+    weights::SafetensorsWeights,
+};
+
+/// Largest request body accepted, in bytes; larger ones are answered with 413.
+const MAX_BODY: u64 = 64 << 20;
+
+/// Options of a `Service`.
+#[derive(Clone, Debug)]
+pub struct ServiceConfig {
+    /// Worker threads executing and proving jobs.
+    pub workers: usize,
+    /// Finished jobs kept for retrieval; the oldest ones are dropped beyond it.
+    pub max_finished_jobs: usize,
+    /// Jobs waiting for a worker; submissions beyond it are answered with 503.
+    pub max_queued_jobs: usize,
+    /// Most jobs executed by a worker on the same compiled graph.
+    pub max_batch_size: usize,
+    /// Longest a worker waits for more jobs to batch with the first one it picks up.
+    pub max_batch_delay: Duration,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            workers: 1,
+            max_finished_jobs: 1024,
+            max_queued_jobs: 1024,
+            max_batch_size: 8,
+            max_batch_delay: Duration::from_millis(10),
+        }
+    }
+}
+
+/// Values of a tensor, in row-major order.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TensorValues {
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+/// Body of `POST /jobs`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobRequest {
+    /// Values of the model inputs not set by its description or the weights.
+    #[serde(default)]
+    pub inputs: BTreeMap<String, TensorValues>,
+    /// Whether to prove the execution, or only return the outputs.
+    #[serde(default = "default_prove")]
+    pub prove: bool,
+}
+
+fn default_prove() -> bool {
+    true
+}
+
+/// State of a job, as returned by `GET /jobs/{id}`.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    /// Outputs of the model; the proof and settings are served as binary if the job was proven.
+    ///
+    /// `proven` is only set if the proof attests to every output: operators run on the CPU
+    /// are listed in `unproven_ops` instead.
+    Done {
+        outputs: BTreeMap<String, TensorValues>,
+        proven: bool,
+        unproven_ops: Vec<String>,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// An HTTP response, before it is written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reply {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(value).unwrap(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, &BTreeMap::from([("error", message.into())]))
+    }
+
+    fn binary(body: &[u8]) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/octet-stream",
+            body: body.to_vec(),
+        }
+    }
+}
+
+struct Job {
+    status: JobStatus,
+    /// Serialized proof and circuit settings, once proven.
+    proof: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+#[derive(Default)]
+struct Jobs {
+    next_id: u64,
+    jobs: BTreeMap<u64, Job>,
+    finished: VecDeque<u64>,
+}
+
+struct Shared {
+    description: ModelDescription,
+    weights: Option<SafetensorsWeights>,
+    config: ProjectConfig,
+    metrics: Metrics,
+    jobs: Mutex<Jobs>,
+}
+
+/// A verifiable-inference service for a single model.
+///
+/// Clients submit inputs as jobs, which worker threads pick up in submission order to
+/// execute and prove. Jobs submitted within `max_batch_delay` of each other are batched:
+/// the model is built and compiled once, then executed and proven for each job in turn.
+/// Endpoints:
+///
+/// - `POST /jobs`: queue a `JobRequest`, returns the job id.
+/// - `GET /jobs/{id}`: `JobStatus` of a job, with the outputs once done.
+/// - `GET /jobs/{id}/proof` and `GET /jobs/{id}/settings`: the proof and circuit settings of a
+///   proven job, in the binary format of `luminair verify`.
+/// - `GET /model`: the model description.
+/// - `GET /metrics`: Prometheus metrics, see `Metrics`.
+/// - `GET /health`
+///
+/// Jobs are kept in memory only. Request bodies over 64 MiB are answered with 413, and
+/// submissions with 503 while `max_queued_jobs` jobs are waiting.
+pub struct Service {
+    shared: Arc<Shared>,
+    queue: mpsc::SyncSender<(u64, JobRequest)>,
+    max_finished_jobs: usize,
+}
+
+impl Service {
+    /// Checks the model builds and starts the workers.
+    ///
+    /// Inputs found in `weights` don't need to be sent with each job.
+    pub fn new(
+        description: ModelDescription,
+        weights: Option<SafetensorsWeights>,
+        config: ServiceConfig,
+    ) -> Result<Self, LuminairError> {
+        description.build(&mut Graph::new())?;
+        let shared = Arc::new(Shared {
+            description,
+            weights,
+            config: ProjectConfig::load()?,
+            metrics: Metrics::new(),
+            jobs: Mutex::default(),
+        });
+
+        let (queue, receiver) = mpsc::sync_channel::<(u64, JobRequest)>(config.max_queued_jobs);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..config.workers.max(1) {
+            let shared = shared.clone();
+            let receiver = receiver.clone();
+            let config = config.clone();
+            thread::spawn(move || loop {
+                // The queue is closed once the service is dropped
+                let Some(batch) = next_batch(&receiver, &config) else {
+                    return;
+                };
+                shared.work(batch, config.max_finished_jobs);
+            });
+        }
+
+        Ok(Self {
+            shared,
+            queue,
+            max_finished_jobs: config.max_finished_jobs,
+        })
+    }
+
+    /// Listens on `addr`, e.g. `127.0.0.1:8080`, serving requests until the process exits.
+    pub fn serve(&self, addr: &str) -> Result<(), LuminairError> {
+        let server = Server::http(addr).map_err(|e| {
+            LuminairError::ConfigError(format!("Failed to listen on {}: {}", addr, e))
+        })?;
+        for mut request in server.incoming_requests() {
+            let mut body = Vec::new();
+            let reply = if request.body_length().unwrap_or(0) as u64 > MAX_BODY {
+                too_large()
+            } else {
+                // The length isn't always announced, one more byte tells if the body is too large
+                match request
+                    .as_reader()
+                    .take(MAX_BODY + 1)
+                    .read_to_end(&mut body)
+                {
+                    Ok(len) if len as u64 > MAX_BODY => too_large(),
+                    Ok(_) => self.handle(&request.method().to_string(), request.url(), &body),
+                    Err(e) => Reply::error(400, format!("Failed to read request body: {}", e)),
+                }
+            };
+            let header = Header::from_bytes("Content-Type", reply.content_type).unwrap();
+            let response = Response::from_data(reply.body)
+                .with_status_code(reply.status)
+                .with_header(header);
+            // The client may have disconnected, which doesn't concern other requests
+            let _ = request.respond(response);
+        }
+        Ok(())
+    }
+
+    /// Answers a request, independently of the transport.
+    pub fn handle(&self, method: &str, url: &str, body: &[u8]) -> Reply {
+        let path = url.split('?').next().unwrap_or_default();
+        let segments = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        match (method, segments.as_slice()) {
+            ("GET", ["health"]) => Reply::json(200, &BTreeMap::from([("status", "ok")])),
+            ("GET", ["model"]) => Reply::json(200, &self.shared.description),
+            ("GET", ["metrics"]) => Reply {
+                status: 200,
+                content_type: "text/plain; version=0.0.4",
+                body: self.shared.metrics.render().into_bytes(),
+            },
+            ("POST", ["jobs"]) => self.submit(body),
+            ("GET", ["jobs", id, rest @ ..]) => {
+                let Ok(id) = id.parse::<u64>() else {
+                    return Reply::error(404, format!("No job '{}'", id));
+                };
+                let jobs = self.shared.jobs.lock().unwrap();
+                let Some(job) = jobs.jobs.get(&id) else {
+                    return Reply::error(404, format!("No job {}", id));
+                };
+                match (rest, &job.proof) {
+                    ([], _) => Reply::json(
+                        200,
+                        &JobView {
+                            id,
+                            status: &job.status,
+                        },
+                    ),
+                    (["proof"], Some((proof, _))) => Reply::binary(proof),
+                    (["settings"], Some((_, settings))) => Reply::binary(settings),
+                    (["proof"] | ["settings"], None) => {
+                        Reply::error(409, format!("Job {} has no proof", id))
+                    }
+                    _ => Reply::error(404, format!("Unknown path {}", path)),
+                }
+            }
+            (_, ["health"] | ["model"] | ["metrics"] | ["jobs", ..]) => {
+                Reply::error(405, format!("{} is not allowed on {}", method, path))
+            }
+            _ => Reply::error(404, format!("Unknown path {}", path)),
+        }
+    }
+
+    fn submit(&self, body: &[u8]) -> Reply {
+        let request: JobRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return Reply::error(400, format!("Invalid job request: {}", e)),
+        };
+        if let Err(e) = self.shared.check_inputs(&request) {
+            return Reply::error(400, e.to_string());
+        }
+
+        let mut jobs = self.shared.jobs.lock().unwrap();
+        let id = jobs.next_id;
+        jobs.next_id += 1;
+        jobs.jobs.insert(
+            id,
+            Job {
+                status: JobStatus::Queued,
+                proof: None,
+            },
+        );
+        drop(jobs);
+
+        match self.queue.try_send((id, request)) {
+            Ok(()) => {}
+            Err(mpsc::TrySendError::Full(_)) => {
+                self.shared.jobs.lock().unwrap().jobs.remove(&id);
+                return Reply::error(503, "Too many queued jobs, retry later");
+            }
+            Err(mpsc::TrySendError::Disconnected(_)) => {
+                let status = JobStatus::Failed {
+                    error: "The workers have stopped".to_string(),
+                };
+                self.shared.finish(id, status, None, self.max_finished_jobs);
+            }
+        }
+        Reply::json(
+            202,
+            &JobView {
+                id,
+                status: &JobStatus::Queued,
+            },
+        )
+    }
+}
+
+fn too_large() -> Reply {
+    Reply::error(413, format!("Request body larger than {} bytes", MAX_BODY))
+}
+
+/// Waits for a job, then for more jobs to batch with it, until the batch is full or
+/// `max_batch_delay` has passed.
+fn next_batch(
+    receiver: &Mutex<mpsc::Receiver<(u64, JobRequest)>>,
+    config: &ServiceConfig,
+) -> Option<Vec<(u64, JobRequest)>> {
+    let receiver = receiver.lock().unwrap();
+    let mut batch = vec![receiver.recv().ok()?];
+    let deadline = Instant::now() + config.max_batch_delay;
+    while batch.len() < config.max_batch_size {
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(job) => batch.push(job),
+            Err(_) => break,
+        }
+    }
+    Some(batch)
+}
+
+#[derive(Serialize)]
+struct JobView<'a> {
+    id: u64,
+    #[serde(flatten)]
+    status: &'a JobStatus,
+}
+
+impl Shared {
+    /// Checks every input is given once, with the model's shape, before queuing a job.
+    fn check_inputs(&self, request: &JobRequest) -> Result<(), LuminairError> {
+        for (name, values) in &request.inputs {
+            let spec = self
+                .description
+                .inputs
+                .iter()
+                .find(|s| &s.name == name && s.data.is_none())
+                .ok_or_else(|| {
+                    LuminairError::InvalidGraph(format!("'{}' is not an input to set", name))
+                })?;
+            if values.shape != spec.shape {
+                return Err(LuminairError::InvalidGraph(format!(
+                    "Input '{}' has shape {:?}, model expects {:?}",
+                    name, values.shape, spec.shape
+                )));
+            }
+            NpyArray::new(values.shape.clone(), values.data.clone())?;
+        }
+        let weights = self.weights.as_ref();
+        if let Some(spec) = self.description.inputs.iter().find(|s| {
+            s.data.is_none()
+                && !request.inputs.contains_key(&s.name)
+                && weights.and_then(|w| w.shape(&s.name)).is_none()
+        }) {
+            return Err(LuminairError::InvalidGraph(format!(
+                "No data for input '{}'",
+                spec.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Executes a batch of jobs, compiling the model for the first one and reusing it.
+    fn work(&self, batch: Vec<(u64, JobRequest)>, max_finished_jobs: usize) {
+        let mut jobs = self.jobs.lock().unwrap();
+        for (id, _) in &batch {
+            jobs.jobs.get_mut(id).unwrap().status = JobStatus::Running;
+        }
+        drop(jobs);
+
+        let mut compiled = None;
+        for (id, request) in batch {
+            let result = catch_unwind(AssertUnwindSafe(|| self.execute(&mut compiled, &request)));
+            let (status, proof) = match result {
+                Ok(Ok((outputs, proof, unproven_ops))) => (
+                    JobStatus::Done {
+                        outputs,
+                        proven: proof.is_some() && unproven_ops.is_empty(),
+                        unproven_ops,
+                    },
+                    proof,
+                ),
+                Ok(Err(e)) => (
+                    JobStatus::Failed {
+                        error: e.to_string(),
+                    },
+                    None,
+                ),
+                Err(_) => {
+                    // The graph may be left half executed, the next job compiles it again
+                    compiled = None;
+                    (
+                        JobStatus::Failed {
+                            error: "Execution panicked".to_string(),
+                        },
+                        None,
+                    )
+                }
+            };
+            self.finish(id, status, proof, max_finished_jobs);
+        }
+    }
+
+    /// Executes, and proves if requested, the model on the inputs of a job.
+    ///
+    /// The model is built and compiled into `compiled` unless a previous job of the batch
+    /// did, then bound to the job's inputs. Also returns the operators of the proven
+    /// execution that ran on the CPU.
+    #[allow(clippy::type_complexity)]
+    fn execute(
+        &self,
+        compiled: &mut Option<(Graph, Model)>,
+        request: &JobRequest,
+    ) -> Result<
+        (
+            BTreeMap<String, TensorValues>,
+            Option<(Vec<u8>, Vec<u8>)>,
+            Vec<String>,
+        ),
+        LuminairError,
+    > {
+        let arrays = request
+            .inputs
+            .iter()
+            .map(|(name, values)| {
+                let array = NpyArray::new(values.shape.clone(), values.data.clone())?;
+                Ok((name.clone(), array))
+            })
+            .collect::<Result<BTreeMap<_, _>, LuminairError>>()?;
+
+        if compiled.is_none() {
+            let mut cx = Graph::new();
+            let weights = self.weights.as_ref();
+            let model = self
+                .description
+                .compile(&mut cx, &arrays, weights, &self.config)?;
+            *compiled = Some((cx, model));
+        }
+        let (cx, model) = compiled.as_mut().unwrap();
+        model.bind_inputs(&self.description, &arrays, self.weights.as_ref())?;
+
+        if !request.prove {
+            return Ok((outputs(model.run(cx)?), None, Vec::new()));
+        }
+        let proven = model.prove(cx, Some(&self.metrics))?;
+        let bytes = proven.to_bincode()?;
+        let unproven_ops = proven.proof.claim.unproven_ops;
+        Ok((outputs(proven.outputs), Some(bytes), unproven_ops))
+    }
+
+    /// Records the result of a job, dropping the oldest finished jobs beyond the limit.
+    fn finish(
+        &self,
+        id: u64,
+        status: JobStatus,
+        proof: Option<(Vec<u8>, Vec<u8>)>,
+        max_finished_jobs: usize,
+    ) {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.jobs.insert(id, Job { status, proof });
+        jobs.finished.push_back(id);
+        while jobs.finished.len() > max_finished_jobs {
+            let oldest = jobs.finished.pop_front().unwrap();
+            jobs.jobs.remove(&oldest);
+        }
+    }
+}
+
+fn outputs(arrays: BTreeMap<String, NpyArray>) -> BTreeMap<String, TensorValues> {
+    arrays
+        .into_iter()
+        .map(|(name, array)| {
+            let values = TensorValues {
+                shape: array.shape,
+                data: array.data,
+            };
+            (name, values)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use luminair_air::settings::CircuitSettings;
+    use luminair_prover::LuminairProof;
+    use luminair_verifier::verifier::verify;
+
+    use super::*;
+
+    const MODEL: &str = r#"{
+        "inputs": [{ "name": "a", "shape": [2, 2] }],
+        "nodes": [{ "name": "b", "op": "mul", "inputs": ["a", "a"] }],
+        "outputs": ["b"]
+    }"#;
+
+    fn service() -> Service {
+        let description = ModelDescription::from_json(MODEL).unwrap();
+        Service::new(description, None, ServiceConfig::default()).unwrap()
+    }
+
+    fn status(service: &Service, id: u64) -> serde_json::Value {
+        loop {
+            let reply = service.handle("GET", &format!("/jobs/{}", id), &[]);
+            let status: serde_json::Value = serde_json::from_slice(&reply.body).unwrap();
+            if !matches!(status["status"].as_str(), Some("queued" | "running")) {
+                return status;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_prove_job() {
+        let service = service();
+        let body = br#"{ "inputs": { "a": { "shape": [2, 2], "data": [1, 2, 3, 4] } } }"#;
+        let reply = service.handle("POST", "/jobs", body);
+        assert_eq!(reply.status, 202);
+
+        let status = status(&service, 0);
+        assert_eq!(status["status"], "done");
+        assert_eq!(
+            status["outputs"]["b"]["data"],
+            serde_json::json!([1.0, 4.0, 9.0, 16.0])
+        );
+
+        let proof = service.handle("GET", "/jobs/0/proof", &[]).body;
+        let settings = service.handle("GET", "/jobs/0/settings", &[]).body;
+        verify(
+            LuminairProof::from_bincode(&proof).unwrap(),
+            CircuitSettings::from_bincode(&settings).unwrap(),
+        )
+        .unwrap();
+        let metrics = service.handle("GET", "/metrics", &[]).body;
+        assert!(String::from_utf8(metrics)
+            .unwrap()
+            .contains("luminair_proofs_total{result=\"ok\"} 1\n"));
+    }
+
+    #[test]
+    fn test_batched_jobs() {
+        let description = ModelDescription::from_json(MODEL).unwrap();
+        let config = ServiceConfig {
+            max_batch_size: 2,
+            max_batch_delay: Duration::from_secs(5),
+            ..ServiceConfig::default()
+        };
+        let service = Service::new(description, None, config).unwrap();
+        let first = br#"{ "inputs": { "a": { "shape": [2, 2], "data": [1, 2, 3, 4] } } }"#;
+        let second =
+            br#"{ "inputs": { "a": { "shape": [2, 2], "data": [5, 6, 7, 8] } }, "prove": false }"#;
+        service.handle("POST", "/jobs", first);
+        service.handle("POST", "/jobs", second);
+
+        // Both jobs run on the same compiled graph, each with its own inputs
+        assert_eq!(
+            status(&service, 0)["outputs"]["b"]["data"],
+            serde_json::json!([1.0, 4.0, 9.0, 16.0])
+        );
+        assert_eq!(status(&service, 0)["proven"], true);
+        assert_eq!(
+            status(&service, 1)["outputs"]["b"]["data"],
+            serde_json::json!([25.0, 36.0, 49.0, 64.0])
+        );
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let service = service();
+        let wrong_shape = br#"{ "inputs": { "a": { "shape": [4], "data": [1, 2, 3, 4] } } }"#;
+        assert_eq!(service.handle("POST", "/jobs", wrong_shape).status, 400);
+        assert_eq!(service.handle("POST", "/jobs", b"{}").status, 400);
+        assert_eq!(service.handle("GET", "/jobs/7", &[]).status, 404);
+        assert_eq!(service.handle("DELETE", "/jobs", &[]).status, 405);
+
+        let unproven =
+            br#"{ "inputs": { "a": { "shape": [2, 2], "data": [1, 2, 3, 4] } }, "prove": false }"#;
+        service.handle("POST", "/jobs", unproven);
+        assert_eq!(status(&service, 0)["proven"], false);
+        assert_eq!(service.handle("GET", "/jobs/0/proof", &[]).status, 409);
+    }
+}
@@ -0,0 +1,263 @@
+use luminair_utils::LuminairError;
+use luminal::prelude::*;
+
+/// Sparse tensor data that can be fed to a LuminAIR graph.
+///
+/// Only non-zero entries are stored on the host. The tensor is densified when it
+/// enters the circuit (in `CopyToStwo`).
+#[derive(Clone, Debug, PartialEq)]
+pub enum SparseData {
+    /// Coordinate format over the row-major flattened tensor.
+    Coo {
+        /// Total number of elements of the dense tensor.
+        len: usize,
+        /// Flat row-major index of each non-zero entry.
+        indices: Vec<usize>,
+        /// Value of each non-zero entry.
+        values: Vec<f32>,
+    },
+    /// Compressed sparse row format for a 2D tensor.
+    Csr {
+        /// Number of rows.
+        rows: usize,
+        /// Number of columns.
+        cols: usize,
+        /// Offsets into `indices`/`values` for each row (`rows + 1` entries).
+        indptr: Vec<usize>,
+        /// Column index of each non-zero entry.
+        indices: Vec<usize>,
+        /// Value of each non-zero entry.
+        values: Vec<f32>,
+    },
+}
+
+impl SparseData {
+    /// Builds a COO tensor from its dense representation, keeping non-zero entries only.
+    pub fn coo_from_dense(data: &[f32]) -> Self {
+        let (indices, values) = data
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| v != 0.0)
+            .map(|(i, &v)| (i, v))
+            .unzip();
+        SparseData::Coo {
+            len: data.len(),
+            indices,
+            values,
+        }
+    }
+
+    /// Builds a CSR tensor from a dense row-major `rows x cols` matrix.
+    pub fn csr_from_dense(data: &[f32], rows: usize, cols: usize) -> Self {
+        assert_eq!(
+            data.len(),
+            rows * cols,
+            "Data length doesn't match the shape"
+        );
+        let mut indptr = Vec::with_capacity(rows + 1);
+        let mut indices = Vec::new();
+        let mut values = Vec::new();
+
+        indptr.push(0);
+        for row in data.chunks(cols.max(1)).take(rows) {
+            for (col, &v) in row.iter().enumerate() {
+                if v != 0.0 {
+                    indices.push(col);
+                    values.push(v);
+                }
+            }
+            indptr.push(values.len());
+        }
+
+        SparseData::Csr {
+            rows,
+            cols,
+            indptr,
+            indices,
+            values,
+        }
+    }
+
+    /// Returns the number of elements of the dense tensor.
+    pub fn len(&self) -> usize {
+        match self {
+            SparseData::Coo { len, .. } => *len,
+            SparseData::Csr { rows, cols, .. } => rows * cols,
+        }
+    }
+
+    /// Returns `true` if the dense tensor has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of stored (non-zero) entries.
+    pub fn nnz(&self) -> usize {
+        match self {
+            SparseData::Coo { values, .. } | SparseData::Csr { values, .. } => values.len(),
+        }
+    }
+
+    /// Checks that the stored entries describe a valid tensor.
+    ///
+    /// Every index must be within the dense tensor, and CSR row offsets must be
+    /// non-decreasing, start at 0 and end at the number of stored entries.
+    pub fn validate(&self) -> Result<(), LuminairError> {
+        let invalid = |msg: String| Err(LuminairError::InvalidTensorData(msg));
+        match self {
+            SparseData::Coo {
+                len,
+                indices,
+                values,
+            } => {
+                if indices.len() != values.len() {
+                    return invalid(format!(
+                        "COO tensor has {} indices for {} values",
+                        indices.len(),
+                        values.len()
+                    ));
+                }
+                if let Some(i) = indices.iter().find(|&&i| i >= *len) {
+                    return invalid(format!(
+                        "COO index {} is out of bounds for {} elements",
+                        i, len
+                    ));
+                }
+            }
+            SparseData::Csr {
+                rows,
+                cols,
+                indptr,
+                indices,
+                values,
+            } => {
+                if indices.len() != values.len() {
+                    return invalid(format!(
+                        "CSR tensor has {} column indices for {} values",
+                        indices.len(),
+                        values.len()
+                    ));
+                }
+                if indptr.len() != rows + 1 {
+                    return invalid(format!(
+                        "CSR indptr has {} entries, expected {}",
+                        indptr.len(),
+                        rows + 1
+                    ));
+                }
+                if indptr[0] != 0
+                    || indptr[*rows] != values.len()
+                    || indptr.windows(2).any(|w| w[0] > w[1])
+                {
+                    return invalid(format!(
+                        "CSR indptr must increase from 0 to {}",
+                        values.len()
+                    ));
+                }
+                if let Some(c) = indices.iter().find(|&&c| c >= *cols) {
+                    return invalid(format!(
+                        "CSR column {} is out of bounds for {} columns",
+                        c, cols
+                    ));
+                }
+                if rows.checked_mul(*cols).is_none() {
+                    return invalid(format!("CSR shape {}x{} is too large", rows, cols));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterates over `(flat_index, value)` pairs of the stored entries.
+    ///
+    /// The entries must have been checked with `validate`.
+    pub fn entries(&self) -> Box<dyn Iterator<Item = (usize, f32)> + '_> {
+        match self {
+            SparseData::Coo {
+                indices, values, ..
+            } => Box::new(indices.iter().copied().zip(values.iter().copied())),
+            SparseData::Csr {
+                cols,
+                indptr,
+                indices,
+                values,
+                ..
+            } => Box::new(indptr.windows(2).enumerate().flat_map(move |(row, w)| {
+                (w[0]..w[1]).map(move |k| (row * cols + indices[k], values[k]))
+            })),
+        }
+    }
+
+    /// Densifies the tensor into a row-major `Vec<f32>`.
+    pub fn to_dense(&self) -> Result<Vec<f32>, LuminairError> {
+        self.validate()?;
+        let mut dense = vec![0.0; self.len()];
+        for (i, v) in self.entries() {
+            dense[i] = v;
+        }
+        Ok(dense)
+    }
+}
+
+impl Data for SparseData {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coo_round_trip() {
+        let dense = [0.0, 1.5, 0.0, 0.0, -2.0, 0.0];
+        let sparse = SparseData::coo_from_dense(&dense);
+
+        assert_eq!(sparse.len(), 6);
+        assert_eq!(sparse.nnz(), 2);
+        assert_eq!(sparse.to_dense().unwrap(), dense);
+    }
+
+    #[test]
+    fn test_csr_round_trip() {
+        let dense = [0.0, 1.0, 0.0, 2.0, 0.0, 3.0];
+        let sparse = SparseData::csr_from_dense(&dense, 2, 3);
+
+        assert_eq!(sparse.nnz(), 3);
+        assert_eq!(sparse.to_dense().unwrap(), dense);
+        if let SparseData::Csr { indptr, .. } = &sparse {
+            assert_eq!(indptr, &[0, 1, 3]);
+        }
+    }
+
+    #[test]
+    fn test_invalid_entries() {
+        let coo = SparseData::Coo {
+            len: 4,
+            indices: vec![1, 4],
+            values: vec![1.0, 2.0],
+        };
+        assert!(matches!(
+            coo.to_dense(),
+            Err(LuminairError::InvalidTensorData(_))
+        ));
+
+        let csr = |indptr: Vec<usize>, indices: Vec<usize>| SparseData::Csr {
+            rows: 2,
+            cols: 2,
+            indptr,
+            indices,
+            values: vec![1.0, 2.0],
+        };
+        assert!(csr(vec![0, 1, 2], vec![0, 1]).validate().is_ok());
+        assert!(csr(vec![0, 1, 2], vec![0, 2]).validate().is_err());
+        assert!(csr(vec![0, 2, 1], vec![0, 1]).validate().is_err());
+        assert!(csr(vec![0, 1, 3], vec![0, 1]).validate().is_err());
+        assert!(csr(vec![0, 2], vec![0, 1]).validate().is_err());
+    }
+}
@@ -1,11 +1,30 @@
-use super::{assert_close, random_vec_rng};
+use super::{assert_close, assert_close_precision, random_vec_rng};
+use crate::batch::run_batch;
+use crate::bench::bench_graph;
+use crate::config::ProjectConfig;
+use crate::data::{CustomDtype, GetTensorData, OutputData, OutputKind, SetTensorData, StwoData};
+use crate::doctor::{diagnose, CheckStatus};
+use crate::export::GraphExport;
 use crate::graph::LuminairGraph;
-use crate::StwoCompiler;
+use crate::profile::profile_graph;
+use crate::sparse::SparseData;
 use crate::{binary_test, unary_test};
-use luminair_prover::prover::prove;
-use luminair_verifier::verifier::verify;
+use crate::{
+    op, stwo_compiler, BoundaryCompiler, CpuPartitionCompiler, RematerializeCompiler, StwoCompiler,
+    StwoFolding, StwoLowering, StwoOptimization, ValidationCompiler, ViewFusionCompiler,
+    WeightsCompiler,
+};
+use half::{bf16, f16};
+use luminair_air::{
+    fixed::{to_fixed, RoundingMode},
+    DEFAULT_FP_SCALE,
+};
+use luminair_prover::{prover::prove, LuminairProof};
+use luminair_utils::LuminairError;
+use luminair_verifier::verifier::{verify, verify_trace_layout};
 use luminal::prelude::*;
 use luminal_cpu::CPUCompiler;
+use numerair::Fixed;
 use rand::{rngs::StdRng, SeedableRng};
 
 // The tests are inspired by Luminal's CUDA tests:
@@ -115,3 +134,975 @@ fn test_max_reduce() {
     assert_close(&c.data(), &c_cpu.data());
     assert_close(&d.data(), &d_cpu.data());
 }
+
+#[test]
+fn test_multi_axis_reduce() {
+    // Graph setup
+    let mut cx = Graph::new();
+    let mut rng = StdRng::seed_from_u64(2);
+    let data = random_vec_rng(2 * 3 * 4 * 5, &mut rng, false);
+    let a = cx.tensor((2, 3, 4, 5));
+    a.set(data.clone());
+    let mut b = a.sum_reduce((1, 2)).retrieve();
+    let mut c = a.max_reduce((0, 1, 2)).retrieve();
+
+    // Compilation and execution using StwoCompiler
+    cx.compile(
+        <(GenericCompiler, StwoCompiler)>::default(),
+        (&mut b, &mut c),
+    );
+
+    // Each chain of reductions is fused into a single operator
+    let reduces = cx
+        .node_indices()
+        .filter(|n| format!("{:?}", cx.node_weight(*n).unwrap()).contains("Reduce"))
+        .count();
+    assert_eq!(reduces, 2);
+
+    let mut settings = cx.gen_circuit_settings();
+    b.drop();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    // CPUCompiler comparison
+    let mut cx_cpu = Graph::new();
+    let a_cpu = cx_cpu.tensor((2, 3, 4, 5)).set(data.clone());
+    let mut b_cpu = a_cpu.sum_reduce((1, 2)).retrieve();
+    let mut c_cpu = a_cpu.max_reduce((0, 1, 2)).retrieve();
+    cx_cpu.compile(
+        <(GenericCompiler, CPUCompiler)>::default(),
+        (&mut b_cpu, &mut c_cpu),
+    );
+    cx_cpu.execute();
+
+    // Assert outputs are close
+    assert_close(&b.data(), &b_cpu.data());
+    assert_close(&c.data(), &c_cpu.data());
+}
+
+// =============== OVERFLOW ===============
+
+#[test]
+fn test_fixed_point_overflow() {
+    // Graph setup
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 1000.0, 4.0]);
+    let b = cx.tensor((2, 2)).set(vec![1.0, 2.0, 1000.0, 4.0]);
+    let mut c = (a * b).retrieve();
+
+    // Compilation and execution using StwoCompiler
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+
+    match cx.gen_trace(&mut settings) {
+        Err(LuminairError::FixedPointOverflow { index, .. }) => assert_eq!(index, 2),
+        other => panic!(
+            "Expected a fixed-point overflow, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+#[test]
+fn test_integer_input_overflow() {
+    // Graph setup
+    let mut cx = Graph::new();
+    let a = cx.tensor(3).set_integers(vec![1i64, 1 << 60, -(1 << 52)]);
+    let b = cx.tensor(3).set_integers(vec![1i64, 2, 3]);
+    let mut c = (a + b).retrieve();
+
+    // Compilation and execution using StwoCompiler
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+
+    // Shifting by the scale would wrap 1 << 60 into range
+    match cx.gen_trace(&mut settings) {
+        Err(LuminairError::FixedPointOverflow { index, .. }) => assert_eq!(index, 1),
+        other => panic!(
+            "Expected a fixed-point overflow, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+// =============== PRECISION ===============
+
+#[test]
+fn test_validate_precision() {
+    // Graph setup
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![0.1, -0.3, 1.7, 2.2]);
+    let b = cx.tensor((2, 2)).set(vec![0.01, 3.3, -0.7, 0.45]);
+    let mut c = (a * b + a.sin()).retrieve();
+
+    // Compilation using StwoCompiler
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+    let report = cx.validate_precision();
+
+    // Inputs, mul, sin and add are all measured
+    assert!(report.ops.len() >= 5);
+    assert!(report.max_abs_error() > 0.0);
+    assert!(report.max_abs_error() < 1e-2);
+}
+
+// =============== STRICT MODE ===============
+
+#[test]
+fn test_check_supported_ops() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let mut b = (a.exp2() + a.log2() + a).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+
+    assert_eq!(cx.unsupported_ops().len(), 2);
+    match cx.check_supported_ops() {
+        Err(LuminairError::UnsupportedOps(ops)) => assert_eq!(ops.len(), 2),
+        other => panic!("Expected unsupported ops, got {:?}", other),
+    }
+
+    // With the CPU fallback, they run unproven instead
+    let config = ProjectConfig::from_toml("[ops]\ncpu_fallback = true\n").unwrap();
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let mut b = (a.exp2() + a.log2() + a).retrieve();
+    cx.compile((GenericCompiler::default(), stwo_compiler(&config)), &mut b);
+    assert!(cx.check_supported_ops().is_ok());
+
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let mut b = (a * a).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+    assert!(cx.check_supported_ops().is_ok());
+}
+
+#[test]
+fn test_constant_folding() {
+    let build = || {
+        let mut cx = Graph::new();
+        let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+        let k = (cx.constant(2.0) * cx.constant(8.0)).sqrt() + cx.constant(0.5);
+        let b = (a * k.expand_to((2, 2))).retrieve();
+        (cx, b)
+    };
+
+    let (mut unfolded, mut b) = build();
+    unfolded.compile(
+        <(
+            GenericCompiler,
+            op::prim::PrimitiveCompiler,
+            op::other::CopyCompiler,
+        )>::default(),
+        &mut b,
+    );
+
+    let (mut cx, mut c) = build();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+    assert!(cx.graph.node_count() < unfolded.graph.node_count());
+
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    assert_close(&c.data(), &[4.5, 9.0, 13.5, 18.0]);
+}
+
+#[test]
+fn test_constant_folding_subgraph() {
+    let mut cx = Graph::new();
+    let a = cx.tensor(2).set(vec![1.0, 2.0]);
+    // A tensor computed from constants only, through a reduction
+    let k = (cx.constant(1.5).expand_to((2, 3)) * cx.constant(2.0)).sum_reduce(1);
+    let mut b = (a + k).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+
+    let mut settings = cx.gen_circuit_settings();
+    b.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let op_counter = &trace.execution_resources.op_counter;
+    assert_eq!(
+        (op_counter.add, op_counter.mul, op_counter.sum_reduce),
+        (1, 0, 0)
+    );
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    assert_close(&b.data(), &[10.0, 11.0]);
+}
+
+#[test]
+fn test_cse() {
+    let build = || {
+        let mut cx = Graph::new();
+        let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+        let b = ((a.sin() * 2.0) + (a.sin() * 2.0)).retrieve();
+        (cx, b)
+    };
+
+    // Without GenericCompiler, duplicates reach the lowered graph
+    let (mut unmerged, mut b) = build();
+    unmerged.compile(
+        <(op::prim::PrimitiveCompiler, op::other::CopyCompiler)>::default(),
+        &mut b,
+    );
+
+    let (mut cx, mut c) = build();
+    cx.compile(StwoCompiler::default(), &mut c);
+    assert!(cx.graph.node_count() < unmerged.graph.node_count());
+
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    // The duplicated chain is merged in one pass, from the sin up to the mul
+    let op_counter = &trace.execution_resources.op_counter;
+    assert_eq!((op_counter.sin, op_counter.mul), (1, 1));
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    let expected = [1.0f32, 2.0, 3.0, 4.0].map(|x| 4.0 * x.sin());
+    assert_close_precision(&c.data(), &expected, 1e-2);
+}
+
+#[test]
+fn test_dead_code_elimination() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let _unused = a.sin().sqrt();
+    let mut b = (a * a).retrieve();
+    cx.compile(StwoCompiler::default(), &mut b);
+
+    assert!(cx
+        .graph
+        .node_indices()
+        .all(|n| !format!("{:?}", cx.graph.node_weight(n).unwrap()).contains("Sin")));
+
+    let mut settings = cx.gen_circuit_settings();
+    b.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    assert_close(&b.data(), &[1.0, 4.0, 9.0, 16.0]);
+}
+
+#[test]
+fn test_cpu_partition() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let b = a * a;
+    // Unsupported op, kept on the CPU by the fallback
+    let c = b.exp2() * 0.01;
+    let d = a + b;
+    let mut e = (c.sin() + d).retrieve();
+    let config = ProjectConfig::from_toml("[ops]\ncpu_fallback = true\n").unwrap();
+    cx.compile(
+        (
+            GenericCompiler::default(),
+            CpuPartitionCompiler::new([d]),
+            stwo_compiler(&config),
+        ),
+        &mut e,
+    );
+
+    let mut settings = cx.gen_circuit_settings();
+    e.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    // Exp2 and the partitioned Add
+    assert_eq!(trace.execution_resources.unproven_ops.len(), 2);
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    let expected = [1.0f32, 2.0, 3.0, 4.0].map(|x| ((x * x).exp2() * 0.01).sin() + x + x * x);
+    assert_close_precision(&e.data(), &expected, 1e-2);
+}
+
+#[test]
+fn test_dynamic_dimensions() {
+    // Graph setup, the sequence length is only known at execution time
+    let mut cx = Graph::new();
+    let a = cx.tensor(('s', 2));
+    let mut b = a.mean_reduce(0).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+
+    // The same compiled graph serves several sequence lengths
+    for len in [3, 5] {
+        let data = (0..len * 2).map(|i| i as f32).collect::<Vec<_>>();
+        a.set_dyn(data.clone(), (len, 2));
+
+        let mut settings = cx.gen_circuit_settings();
+        b.drop();
+        let trace = cx
+            .gen_trace(&mut settings)
+            .expect("Trace generation failed");
+        let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+        verify(proof, settings).expect("Proof verification failed");
+
+        let expected = [0, 1].map(|j| (0..len).map(|i| data[i * 2 + j]).sum::<f32>() / len as f32);
+        assert_close_precision(&b.data(), &expected, 1e-2);
+    }
+}
+
+#[test]
+fn test_estimate_cost() {
+    let mut cx = Graph::new();
+    let a = cx
+        .tensor((4, 8))
+        .set(random_vec_rng(32, &mut StdRng::seed_from_u64(3), false));
+    let b = cx
+        .tensor((4, 8))
+        .set(random_vec_rng(32, &mut StdRng::seed_from_u64(4), false));
+    let mut c = (a * b + a).sum_reduce(1).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+
+    let estimate = cx.estimate_cost();
+    assert_eq!(estimate.ops.len(), 3);
+    assert!(estimate.components.iter().all(|c| c.rows == 32));
+
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    assert_eq!(
+        estimate.max_log_size(),
+        trace.execution_resources.max_log_size
+    );
+}
+
+#[test]
+fn test_lowering_report() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 3)).set(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let mut b = (a.exp2() + a).retrieve();
+    let report = cx.compile(op::prim::PrimitiveCompiler::default(), &mut b);
+
+    assert_eq!(report.ops.len(), 2);
+    let fallbacks = report.fallbacks().collect::<Vec<_>>();
+    assert_eq!(fallbacks.len(), 1);
+    assert!(fallbacks[0].original.contains("Exp2"));
+    assert!(report.to_string().contains("CPU fallback"));
+}
+
+#[test]
+fn test_selective_compilation() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let b = a * a;
+    let mut c = (b + a).retrieve();
+    cx.compile(
+        (
+            GenericCompiler::default(),
+            CpuPartitionCompiler::select([b]),
+            StwoCompiler::default(),
+        ),
+        &mut c,
+    );
+
+    // The unselected Add is left as a luminal op
+    assert!(cx.node_indices().any(|n| cx
+        .node_weight(n)
+        .unwrap()
+        .as_any()
+        .is::<luminal::op::Add>()));
+
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    assert_eq!(trace.execution_resources.op_counter.mul, 1);
+    assert_eq!(trace.execution_resources.op_counter.add, 0);
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    assert_close(&c.data(), &[2.0, 6.0, 12.0, 20.0]);
+}
+
+#[test]
+fn test_graph_export() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let mut b = (a.exp2() * a).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+
+    let export = GraphExport::new(&cx);
+    assert_eq!(
+        export
+            .nodes
+            .iter()
+            .filter(|n| n.component.as_deref() == Some("mul"))
+            .count(),
+        1
+    );
+    assert_eq!(export.nodes.iter().filter(|n| n.on_cpu).count(), 1);
+    assert_eq!(export.edges.len(), cx.graph.edge_count());
+
+    let dot = export.to_dot();
+    assert!(dot.starts_with("digraph"));
+    assert_eq!(dot.matches(" -> ").count(), export.edges.len());
+    assert!(export.to_json().unwrap().contains("\"on_cpu\": true"));
+
+    // Broadcast inputs are flagged on their edge
+    let mut cx = Graph::new();
+    let a = cx.tensor(2).set(vec![1.0, 2.0]);
+    let b = cx.tensor((3, 2)).set(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let mut c = (a.expand(0, 3) + b).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+
+    let export = GraphExport::new(&cx);
+    assert_eq!(export.edges.iter().filter(|e| e.broadcast).count(), 1);
+    assert!(export.to_dot().contains("style=dashed"));
+}
+
+#[test]
+fn test_run_batch() {
+    let mut cx = Graph::new();
+    let a = cx.tensor(2);
+    let w = cx.tensor(2).set(vec![10.0, 20.0]);
+    let mut b = (a * w).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+
+    let samples = vec![vec![vec![1.0, 2.0]], vec![vec![3.0, 4.0]]];
+    let runs = run_batch(&mut cx, &[a], samples, &[b]).unwrap();
+    assert_eq!(runs[0].outputs[0], vec![10.0, 40.0]);
+    assert_eq!(runs[1].outputs[0], vec![30.0, 80.0]);
+
+    for run in runs {
+        let proof = prove(run.pie, run.settings.clone()).unwrap();
+        verify(proof, run.settings).unwrap();
+    }
+
+    assert!(run_batch(&mut cx, &[a], vec![vec![]], &[b]).is_err());
+}
+
+#[test]
+fn test_bench_graph() {
+    let result = bench_graph("add", 2, || {
+        let mut cx = Graph::new();
+        let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+        let mut b = (a + a).retrieve();
+        cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+        cx
+    })
+    .unwrap();
+
+    assert_eq!(result.iterations, 2);
+    assert_eq!(result.cost.ops.len(), 1);
+    assert!(result.proving_ms > 0.0);
+    assert!(result.nodes.iter().any(|n| n.op.contains("Add")));
+    assert!(result.to_json().unwrap().contains("\"name\": \"add\""));
+}
+
+#[test]
+fn test_doctor() {
+    let dir = std::env::temp_dir();
+    let mut settings = Graph::new().gen_circuit_settings();
+    let report = diagnose(&dir, Some(&settings));
+    assert!(report.is_healthy(), "{:?}", report);
+    assert!(report.diagnostics.iter().any(|d| d.check == "prover"));
+
+    settings.fp_scale += 1;
+    let report = diagnose(&dir.join("missing"), Some(&settings));
+    let failed = report
+        .diagnostics
+        .iter()
+        .filter(|d| d.status == CheckStatus::Error)
+        .map(|d| d.check.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(failed, ["trace directory", "settings"]);
+}
+
+#[test]
+fn test_proof_summary() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let mut b = (a * a).sin().retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+    let mut settings = cx.gen_circuit_settings();
+    let trace = cx.gen_trace(&mut settings).unwrap();
+    let proof = prove(trace, settings.clone()).unwrap();
+
+    let summary = proof.summary().unwrap();
+    let components = summary
+        .components
+        .iter()
+        .map(|c| c.component.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(components, ["mul", "sin", "sin_lookup"]);
+    assert_eq!(summary.commitments.len(), 3);
+    assert_eq!(summary.trace_layout_hash.len(), 64);
+    assert!(summary.total_size() <= proof.to_bincode().unwrap().len() as u64);
+    assert!(summary.to_string().contains("sin: log size"));
+
+    let bytes = proof.to_bincode().unwrap();
+    let decode = || LuminairProof::from_bincode(&bytes).unwrap();
+    verify_trace_layout(decode(), settings.clone(), &summary.trace_layout_hash).unwrap();
+    assert!(matches!(
+        verify_trace_layout(decode(), settings, &"0".repeat(64)),
+        Err(LuminairError::TraceLayoutMismatch { .. })
+    ));
+
+    let mut truncated = decode();
+    truncated.proof.commitments.0.clear();
+    assert!(matches!(
+        truncated.summary(),
+        Err(LuminairError::SerializationError(_))
+    ));
+}
+
+#[test]
+fn test_profile_graph() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let mut b = (a * a + a).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut b);
+
+    let timeline = profile_graph(&mut cx).unwrap();
+    let stages = timeline
+        .events
+        .iter()
+        .filter(|e| e.category == "stage")
+        .map(|e| e.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(stages[1], "trace_generation");
+    assert_eq!(stages.len(), 4);
+    assert!(timeline.slowest_nodes().len() >= 2);
+    // Struct fields, which may hold pointers, are left out of the operator names
+    assert!(timeline.events.iter().all(|e| !e.name.contains('{')));
+    assert!(timeline
+        .to_chrome_trace()
+        .unwrap()
+        .starts_with("{\"traceEvents\":"));
+}
+
+#[test]
+fn test_view_fusion() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 3)).set(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let b = cx.tensor((3, 2)).set(vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+    let mut c = (a.permute((1, 0)).contiguous() + b).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+
+    // The transpose is read in place by the proven Add
+    assert!(GraphExport::new(&cx).nodes.iter().all(|n| !n.on_cpu));
+
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+    assert_close_precision(&c.data(), &[2.0, 5.0, 3.0, 6.0, 4.0, 7.0], 1e-2);
+}
+
+#[test]
+fn test_view_fusion_reshape() {
+    let config = ProjectConfig::from_toml("[ops]\ncpu_fallback = true\n").unwrap();
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 3)).set(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let mut b = a
+        .permute((1, 0))
+        .contiguous()
+        .reshape((6,))
+        .sum_reduce(0)
+        .retrieve();
+    cx.compile((GenericCompiler::default(), stwo_compiler(&config)), &mut b);
+
+    // The reshape can't read the transpose in place, so it is materialized on the CPU
+    assert!(GraphExport::new(&cx).nodes.iter().any(|n| n.on_cpu));
+
+    let mut settings = cx.gen_circuit_settings();
+    b.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+    assert_close_precision(&b.data(), &[21.0], 1e-2);
+}
+
+#[test]
+fn test_sliced_view() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 3)).set(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    let b = cx.tensor((1, 3)).set(vec![1.0, 1.0, 1.0]);
+    // Only the first row of the sine is read by the proven Add
+    let mut c = (a.sin().slice((..1, ..)) + b).retrieve();
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    let expected = [1.0f32, 2.0, 3.0].map(|x| x.sin() + 1.0);
+    assert_close_precision(&c.data(), &expected, 1e-2);
+}
+
+#[test]
+fn test_weights_preconversion() {
+    let mut cx = Graph::new();
+    let w = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let x = cx.tensor((2, 2)).set(vec![0.5, 0.5, 0.5, 0.5]);
+    let mut y = (w * x).retrieve();
+    cx.compile(
+        (
+            GenericCompiler::default(),
+            WeightsCompiler::new([w]),
+            StwoCompiler::default(),
+        ),
+        &mut y,
+    );
+
+    // The weights loader now returns fixed-point data
+    let loader = cx.get_op::<luminal::op::Function>(w.id);
+    assert!((loader.1)(vec![])[0].downcast_ref::<StwoData>().is_some());
+
+    let mut settings = cx.gen_circuit_settings();
+    y.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+    assert_close_precision(&y.data(), &[0.5, 1.0, 1.5, 2.0], 1e-2);
+}
+
+#[test]
+fn test_deterministic_trace() {
+    let trace = || {
+        let mut cx = Graph::new();
+        let a = cx
+            .tensor((3, 4))
+            .set(random_vec_rng(12, &mut StdRng::seed_from_u64(5), true));
+        let b = cx
+            .tensor((3, 4))
+            .set(random_vec_rng(12, &mut StdRng::seed_from_u64(6), true));
+        let mut c = ((a * b).sin() + (a + b).sqrt()).sum_reduce(1).retrieve();
+        cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+        let mut settings = cx.gen_circuit_settings();
+        c.drop();
+        cx.gen_trace(&mut settings)
+            .expect("Trace generation failed")
+            .to_bincode()
+            .unwrap()
+    };
+
+    assert_eq!(trace(), trace());
+}
+
+#[test]
+fn test_rematerialization() {
+    let build = || {
+        let mut cx = Graph::new();
+        let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+        let b = a.sin();
+        let c = ((b * a) + b).retrieve();
+        (cx, b, c)
+    };
+
+    let (mut kept, _, mut c) = build();
+    kept.compile(StwoCompiler::default(), &mut c);
+
+    let (mut cx, b, mut c) = build();
+    cx.compile(
+        (StwoCompiler::default(), RematerializeCompiler::new([b])),
+        &mut c,
+    );
+    assert_eq!(cx.graph.node_count(), kept.graph.node_count() + 1);
+    assert_eq!(
+        cx.estimate_cost().ops.len(),
+        kept.estimate_cost().ops.len() + 1
+    );
+
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    let expected = [1.0f32, 2.0, 3.0, 4.0].map(|x| x.sin() * x + x.sin());
+    assert_close_precision(&c.data(), &expected, 1e-2);
+}
+
+#[test]
+fn test_custom_pass_pipeline() {
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let b = a * a;
+    let mut c = (b + a).retrieve();
+    let (.., valid) = cx.compile(
+        (
+            StwoFolding::default(),
+            CpuPartitionCompiler::new([b]),
+            StwoLowering::default(),
+            StwoOptimization::default(),
+            ValidationCompiler::default(),
+        ),
+        &mut c,
+    );
+    valid.expect("Graph validation failed");
+
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+    assert_close_precision(&c.data(), &[2.0, 6.0, 12.0, 20.0], 1e-2);
+
+    // Without BoundaryCompiler, the CPU fallback reads StwoData
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let mut b = (a.exp2() + a).retrieve();
+    let (_, valid) = cx.compile(
+        (
+            op::prim::PrimitiveCompiler::default(),
+            ValidationCompiler::default(),
+        ),
+        &mut b,
+    );
+    assert!(valid.is_err());
+}
+
+#[test]
+fn test_project_config() {
+    let config = ProjectConfig::from_toml("[ops]\ncpu = [\"sin\"]\n").unwrap();
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set(vec![1.0, 2.0, 3.0, 4.0]);
+    let mut b = (a.sin() * a).retrieve();
+    cx.compile(
+        (
+            GenericCompiler::default(),
+            StwoFolding::default(),
+            (
+                ViewFusionCompiler::default(),
+                op::prim::PrimitiveCompiler::new(&config),
+                BoundaryCompiler::new(&config),
+            ),
+            StwoOptimization::default(),
+        ),
+        &mut b,
+    );
+
+    // Sin runs on the CPU, only the multiplication is proven
+    let cost = cx.estimate_cost();
+    let components = cost
+        .components
+        .iter()
+        .map(|c| c.component.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(components, ["mul"]);
+    assert!(config.check_cost(&cost).is_ok());
+    let limited = ProjectConfig::from_toml("[limits]\nmax_log_size = 1\n").unwrap();
+    assert!(limited.check_cost(&cost).is_err());
+
+    let mut settings = cx.gen_circuit_settings();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    let expected = [1.0f32, 2.0, 3.0, 4.0].map(|x| x.sin() * x);
+    assert_close_precision(&b.data(), &expected, 1e-2);
+}
+
+// =============== INTEGER INPUTS ===============
+
+#[test]
+fn test_integer_inputs() {
+    let a_data: Vec<i32> = vec![1, -2, 3, -4, 5, 6];
+    let b_data: Vec<i64> = vec![7, 8, -9, 10, 11, -12];
+
+    // Graph setup
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 3)).set_integers(a_data.clone());
+    let b = cx.tensor((2, 3)).set_integers(b_data.clone());
+    let mut c = (a + b).retrieve();
+
+    // Compilation and execution using StwoCompiler
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    let expected: Vec<f32> = a_data
+        .iter()
+        .zip(b_data.iter())
+        .map(|(&a, &b)| (a as i64 + b) as f32)
+        .collect();
+    assert_close(&c.data(), &expected);
+}
+
+#[test]
+fn test_typed_outputs() {
+    // Graph setup
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set_integers(vec![1, -2, 3, 0]);
+    let b = cx.tensor((2, 2)).set_integers(vec![-1, 5, 4, 0]);
+    let mut c = (a + b).retrieve();
+
+    // Compilation and execution using StwoCompiler
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    assert_eq!(c.data_i64(), [0, 3, 7, 0]);
+    assert_eq!(c.data_bool(), [false, true, true, false]);
+    assert_eq!(c.output_kind(), OutputKind::F32);
+    assert!(matches!(c.data_typed(), OutputData::F32(_)));
+}
+
+#[test]
+fn test_half_inputs() {
+    let a_data: Vec<f32> = vec![0.5, -0.25, 0.125, 1.5];
+    let b_data: Vec<f32> = vec![-1.0, 0.75, 0.0625, 2.0];
+
+    // Graph setup
+    let mut cx = Graph::new();
+    let a = cx
+        .tensor((2, 2))
+        .set_half(a_data.iter().map(|&v| f16::from_f32(v)).collect::<Vec<_>>());
+    let b = cx.tensor((2, 2)).set_half(
+        b_data
+            .iter()
+            .map(|&v| bf16::from_f32(v))
+            .collect::<Vec<_>>(),
+    );
+    let mut c = (a * b).retrieve();
+
+    // Compilation and execution using StwoCompiler
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    let expected: Vec<f32> = a_data
+        .iter()
+        .zip(b_data.iter())
+        .map(|(a, b)| a * b)
+        .collect();
+    assert_close(&c.data(), &expected);
+}
+
+/// Signed 8.8 fixed-point values, used to exercise custom dtypes.
+#[derive(Debug, PartialEq)]
+struct Q8x8(Vec<i16>);
+
+impl CustomDtype for Q8x8 {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn to_fixed(&self) -> Vec<Fixed<DEFAULT_FP_SCALE>> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| to_fixed(v as f64 / 256.0, RoundingMode::Nearest, i))
+            .collect()
+    }
+
+    fn from_fixed(values: &[Fixed<DEFAULT_FP_SCALE>]) -> Self {
+        Q8x8(
+            values
+                .iter()
+                .map(|v| (v.to_f64() * 256.0).round() as i16)
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_custom_dtype() {
+    // Graph setup
+    let mut cx = Graph::new();
+    let a = cx.tensor((2, 2)).set_custom(Q8x8(vec![256, -128, 64, 0]));
+    let b = cx.tensor((2, 2)).set_custom(Q8x8(vec![512, 32, -64, 1024]));
+    let mut c = (a + b).retrieve();
+
+    // Compilation and execution using StwoCompiler
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut c);
+    let mut settings = cx.gen_circuit_settings();
+    c.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    assert_eq!(c.data_custom::<Q8x8>(), Q8x8(vec![768, -96, 0, 1024]));
+}
+
+#[test]
+fn test_sparse_inputs() {
+    let a_data: Vec<f32> = vec![0.0, 1.5, 0.0, -2.0, 0.0, 0.0];
+    let b_data: Vec<f32> = vec![0.0, 0.0, 3.0, 0.0, 0.0, 0.25];
+    let c_data: Vec<f32> = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+
+    // Graph setup
+    let mut cx = Graph::new();
+    let a = cx
+        .tensor((2, 3))
+        .set_sparse(SparseData::csr_from_dense(&a_data, 2, 3));
+    let b = cx
+        .tensor((2, 3))
+        .set_sparse(SparseData::coo_from_dense(&b_data));
+    let c = cx.tensor((2, 3)).set(c_data.clone());
+    let mut d = ((a + b) * c).retrieve();
+
+    // Compilation and execution using StwoCompiler
+    cx.compile(<(GenericCompiler, StwoCompiler)>::default(), &mut d);
+    let mut settings = cx.gen_circuit_settings();
+    d.drop();
+    let trace = cx
+        .gen_trace(&mut settings)
+        .expect("Trace generation failed");
+    let proof = prove(trace, settings.clone()).expect("Proof generation failed");
+    verify(proof, settings).expect("Proof verification failed");
+
+    let expected: Vec<f32> = a_data
+        .iter()
+        .zip(b_data.iter())
+        .zip(c_data.iter())
+        .map(|((a, b), c)| (a + b) * c)
+        .collect();
+    assert_close(&d.data(), &expected);
+}
@@ -1,16 +1,69 @@
 use std::any::{Any, TypeId};
 
 use crate::data::StwoData;
-use luminair_air::{preprocessed::Range, DEFAULT_FP_SCALE};
+use luminair_air::{
+    fixed::{to_fixed, RoundingMode},
+    pie::NodeInfo,
+    preprocessed::Range,
+    DEFAULT_FP_SCALE,
+};
 use luminal::prelude::*;
-use num_traits::Zero;
+use num_traits::{One, Zero};
 use numerair::Fixed;
+use stwo_prover::core::fields::m31::BaseField;
 
 /// Generic helper function to check if a given `TypeId` corresponds to the type `T`.
 pub(crate) fn is<T: Any>(type_id: TypeId) -> bool {
     type_id == TypeId::of::<T>()
 }
 
+/// Splits an edge weight into the source output it reads and the same weight reading output 0.
+///
+/// Used when a single-output copy node is inserted on an edge of a multi-output operator.
+pub(crate) fn split_output(weight: Dependency) -> (u8, Dependency) {
+    match weight {
+        Dependency::Data {
+            input_order,
+            output_order,
+            shape,
+        } => (
+            output_order,
+            Dependency::Data {
+                input_order,
+                output_order: 0,
+                shape,
+            },
+        ),
+        schedule => (0, schedule),
+    }
+}
+
+/// Describes an operator by its `Debug` output, without the fields of struct operators.
+///
+/// Fields may hold pointers, like the graph's dynamic dimensions, which would differ between
+/// runs of the same graph; tuple operators such as `SumReduce(0)` are kept whole.
+pub(crate) fn op_name(op: &dyn Operator) -> String {
+    let mut name = String::new();
+    let mut depth = 0usize;
+    for c in format!("{:?}", op).chars() {
+        match c {
+            '{' => {
+                depth += 1;
+                name.truncate(name.trim_end().len());
+            }
+            '}' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => name.push(c),
+            _ => {}
+        }
+    }
+    name
+}
+
+/// Returns true if `shape` reads a tensor as is, without any permute, slice, pad or broadcast.
+pub(crate) fn is_plain_view(shape: &ShapeTracker) -> bool {
+    !shape.is_reshaped() && !shape.fake.iter().any(|f| *f)
+}
+
 /// Safely attempts to downcast a Luminal `InputTensor` to a reference to `StwoData`.
 /// Returns `None` if the tensor does not contain `StwoData`.
 pub(crate) fn get_buffer_from_tensor<'a>(tensor: &'a InputTensor) -> Option<&'a StwoData> {
@@ -38,6 +91,47 @@ pub(crate) fn get_index(
     }
 }
 
+/// Returns the multiplicity of the read of input element `index` in the node relation.
+///
+/// Initializers are not part of the relation, and masked elements read a padding zero
+/// rather than a value produced by the input node.
+pub(crate) fn read_mult(
+    is_initializer: bool,
+    valid: &Expression,
+    stack: &mut Vec<i64>,
+    index: usize,
+) -> BaseField {
+    if is_initializer || valid.exec_single_var_stack(index, stack) == 0 {
+        BaseField::zero()
+    } else {
+        -BaseField::one()
+    }
+}
+
+/// Returns the multiplicity of output element `index` in the node relation.
+pub(crate) fn output_mult(node_info: &NodeInfo, index: usize) -> BaseField {
+    if node_info.output.is_final_output {
+        BaseField::zero()
+    } else {
+        BaseField::from_u32_unchecked(node_info.consumers_of(index))
+    }
+}
+
+/// Returns the input element read by trace row `row` of a reduction along `dims`.
+///
+/// Reduce operators emit one row per input element, output by output.
+pub(crate) fn reduce_input_index(
+    shape: &[usize],
+    dims: std::ops::Range<usize>,
+    row: usize,
+) -> usize {
+    let dim_size = shape[dims.clone()].iter().product::<usize>();
+    let back_size = shape.iter().skip(dims.end).product::<usize>().max(1);
+    let (output, k) = (row / dim_size, row % dim_size);
+    let (i, j) = (output / back_size, output % back_size);
+    i * dim_size * back_size + k * back_size + j
+}
+
 /// Computes the combined value range across multiple source tensors, adding padding.
 ///
 /// Iterates through the provided source tensors (`srcs`), extracts their `StwoData`,
@@ -77,8 +171,76 @@ fn buffer_range(range: Range) -> Range {
     let span = max - min;
 
     let delta = span * RANGE_MARGIN;
-    let low = Fixed::from_f64(min - delta);
-    let high = Fixed::from_f64(max + delta);
+    let low = to_fixed(min - delta, RoundingMode::Nearest, 0);
+    let high = to_fixed(max + delta, RoundingMode::Nearest, 0);
 
     Range(low, high)
 }
+
+/// Retrieves a reference value the same way `get_index` does for `StwoData`.
+pub(crate) fn get_reference_index(
+    data: &[f64],
+    (ind, val): &(Expression, Expression),
+    stack: &mut Vec<i64>,
+    index: usize,
+) -> f64 {
+    if val.exec_single_var_stack(index, stack) != 0 {
+        data[ind.exec_single_var_stack(index, stack)]
+    } else {
+        0.0
+    }
+}
+
+/// Computes the reference output of an element-wise operator.
+///
+/// `f` receives the value of every input at the current output index.
+pub(crate) fn reference_elementwise(
+    inp: &[(Vec<f64>, ShapeTracker)],
+    f: impl Fn(&[f64]) -> f64,
+) -> Vec<f64> {
+    let exprs = inp
+        .iter()
+        .map(|(_, st)| (st.index_expression(), st.valid_expression()))
+        .collect::<Vec<_>>();
+    let output_size = inp[0].1.n_elements().to_usize().unwrap();
+    let mut stack: Vec<i64> = vec![];
+    let mut values = vec![0.0; inp.len()];
+
+    (0..output_size)
+        .map(|idx| {
+            for (value, ((data, _), expr)) in values.iter_mut().zip(inp.iter().zip(exprs.iter())) {
+                *value = get_reference_index(data, expr, &mut stack, idx);
+            }
+            f(&values)
+        })
+        .collect()
+}
+
+/// Computes the reference output of a reduction along the contiguous `dims`, folding with `f`.
+pub(crate) fn reference_reduce(
+    inp: &[(Vec<f64>, ShapeTracker)],
+    dims: std::ops::Range<usize>,
+    f: impl Fn(f64, f64) -> f64,
+) -> Vec<f64> {
+    let sh = inp[0].1.shape_usize();
+    let front_size = sh.iter().take(dims.start).product::<usize>().max(1);
+    let back_size = sh.iter().skip(dims.end).product::<usize>().max(1);
+    let dim_size = sh[dims].iter().product::<usize>();
+    let expr = (inp[0].1.index_expression(), inp[0].1.valid_expression());
+    let mut stack: Vec<i64> = vec![];
+
+    let mut out = Vec::with_capacity(front_size * back_size);
+    for i in 0..front_size {
+        for j in 0..back_size {
+            let value = (0..dim_size)
+                .map(|k| {
+                    let orig_index = i * dim_size * back_size + k * back_size + j;
+                    get_reference_index(&inp[0].0, &expr, &mut stack, orig_index)
+                })
+                .reduce(&f)
+                .unwrap_or(0.0);
+            out.push(value);
+        }
+    }
+    out
+}
@@ -0,0 +1,191 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::data::{HalfData, IntegerData, StwoData};
+use half::{bf16, f16};
+use luminair_air::{fixed::find_overflow, hash::hash_tensors};
+use luminair_utils::LuminairError;
+use luminal::{op::Function, prelude::*};
+use safetensors::{Dtype, SafeTensors};
+use stwo_prover::core::vcs::blake2_hash::Blake2sHash;
+
+/// Weights loaded from a `.safetensors` file, already converted to fixed point.
+///
+/// Conversion happens once at load time, so binding the weights to a graph for
+/// each inference only shares the converted buffers.
+/// The canonical hash of the named tensors (see `luminair_air::hash`) is recorded
+/// so the exact weights used by a proof can be identified.
+#[derive(Clone, Debug)]
+pub struct SafetensorsWeights {
+    tensors: HashMap<String, (Vec<usize>, StwoData)>,
+    commitment: Blake2sHash,
+}
+
+impl SafetensorsWeights {
+    /// Loads and converts the weights stored in a `.safetensors` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, LuminairError> {
+        let data = std::fs::read(path).map_err(|e| {
+            LuminairError::WeightsError(format!("Failed to read safetensors file: {}", e))
+        })?;
+        Self::from_bytes(&data)
+    }
+
+    /// Loads and converts weights from the bytes of a `.safetensors` file.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, LuminairError> {
+        let file = SafeTensors::deserialize(data).map_err(|e| {
+            LuminairError::WeightsError(format!("Failed to parse safetensors: {}", e))
+        })?;
+
+        let mut tensors = HashMap::new();
+        for (name, view) in file.tensors() {
+            let bytes = view.data();
+            let stwo_data = match view.dtype() {
+                Dtype::F32 => StwoData::from_f32(&decode(bytes, f32::from_le_bytes)),
+                Dtype::F16 => {
+                    StwoData::from_half(&HalfData::F16(decode(bytes, f16::from_le_bytes)))
+                }
+                Dtype::BF16 => {
+                    StwoData::from_half(&HalfData::BF16(decode(bytes, bf16::from_le_bytes)))
+                }
+                Dtype::I32 => {
+                    StwoData::from_integers(&IntegerData::I32(decode(bytes, i32::from_le_bytes)))
+                }
+                Dtype::I64 => {
+                    StwoData::from_integers(&IntegerData::I64(decode(bytes, i64::from_le_bytes)))
+                }
+                dtype => {
+                    return Err(LuminairError::WeightsError(format!(
+                        "Unsupported dtype {:?} for tensor '{}'",
+                        dtype, name
+                    )))
+                }
+            };
+            if let Some(index) = find_overflow(&stwo_data.0) {
+                return Err(LuminairError::WeightsError(format!(
+                    "Element {} of tensor '{}' is out of the fixed-point range",
+                    index, name
+                )));
+            }
+            tensors.insert(name, (view.shape().to_vec(), stwo_data));
+        }
+
+        let commitment = commit(&tensors)?;
+        Ok(Self {
+            tensors,
+            commitment,
+        })
+    }
+
+    /// Returns the commitment to the loaded weights.
+    pub fn commitment(&self) -> Blake2sHash {
+        self.commitment
+    }
+
+    /// Returns the names of the loaded tensors, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.tensors.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Returns the shape of the tensor `name`, if loaded.
+    pub fn shape(&self, name: &str) -> Option<&[usize]> {
+        self.tensors.get(name).map(|(shape, _)| shape.as_slice())
+    }
+
+    /// Binds the weights of `name` to the input tensor `tensor`.
+    pub fn bind(&self, name: &str, tensor: GraphTensor) -> Result<GraphTensor, LuminairError> {
+        let (_, data) = self.tensors.get(name).ok_or_else(|| {
+            LuminairError::WeightsError(format!("Tensor '{}' not found in weights", name))
+        })?;
+
+        let expected = tensor.shape.n_elements().to_usize().unwrap();
+        if data.0.len() != expected {
+            return Err(LuminairError::WeightsError(format!(
+                "Tensor '{}' has {} elements, graph tensor expects {}",
+                name,
+                data.0.len(),
+                expected
+            )));
+        }
+
+        let data = data.clone();
+        tensor.graph().get_op_mut::<Function>(tensor.id).1 =
+            Box::new(move |_| vec![Tensor::new(data.clone())]);
+        Ok(tensor)
+    }
+
+    /// Binds every `(name, tensor)` pair, stopping at the first error.
+    pub fn bind_all<'a>(
+        &self,
+        tensors: impl IntoIterator<Item = (&'a str, GraphTensor)>,
+    ) -> Result<(), LuminairError> {
+        for (name, tensor) in tensors {
+            self.bind(name, tensor)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes little-endian values of `N` bytes each.
+pub(crate) fn decode<T, const N: usize>(bytes: &[u8], from_le_bytes: fn([u8; N]) -> T) -> Vec<T> {
+    bytes
+        .chunks_exact(N)
+        .map(|chunk| from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+/// Hashes the named fixed-point tensors canonically.
+pub(crate) fn commit(
+    tensors: &HashMap<String, (Vec<usize>, StwoData)>,
+) -> Result<Blake2sHash, LuminairError> {
+    hash_tensors(
+        tensors
+            .iter()
+            .map(|(name, (shape, data))| (name.as_str(), shape.as_slice(), data.0.as_slice())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use safetensors::tensor::TensorView;
+
+    fn serialize(name: &str, shape: &[usize], data: &[f32]) -> Vec<u8> {
+        let bytes = data
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        let view = TensorView::new(Dtype::F32, shape.to_vec(), &bytes).unwrap();
+        safetensors::serialize([(name, view)], &None).unwrap()
+    }
+
+    #[test]
+    fn test_load_and_bind() {
+        let file = serialize("w", &[2, 2], &[1.0, -0.5, 0.25, 2.0]);
+        let weights = SafetensorsWeights::from_bytes(&file).unwrap();
+
+        assert_eq!(weights.names(), ["w"]);
+        assert_eq!(weights.shape("w"), Some([2, 2].as_slice()));
+
+        let mut cx = Graph::new();
+        assert!(weights.bind("w", cx.tensor((2, 2))).is_ok());
+        assert!(weights.bind("w", cx.tensor((3, 2))).is_err());
+        assert!(weights.bind("missing", cx.tensor((2, 2))).is_err());
+    }
+
+    #[test]
+    fn test_commitment() {
+        let a = serialize("w", &[2], &[1.0, 2.0]);
+        let b = serialize("w", &[2], &[1.0, 3.0]);
+
+        let commitment = SafetensorsWeights::from_bytes(&a).unwrap().commitment();
+        assert_eq!(
+            commitment,
+            SafetensorsWeights::from_bytes(&a).unwrap().commitment()
+        );
+        assert_ne!(
+            commitment,
+            SafetensorsWeights::from_bytes(&b).unwrap().commitment()
+        );
+    }
+}
@@ -0,0 +1,456 @@
+use std::{collections::BTreeMap, pin::Pin, sync::Arc};
+
+use luminair_air::settings::CircuitSettings;
+use luminair_graph::{
+    config::ProjectConfig, description::ModelDescription, npy::NpyArray,
+    weights::SafetensorsWeights,
+};
+use luminair_prover::LuminairProof;
+use luminair_utils::LuminairError;
+use luminair_verifier::verifier::verify;
+use luminal::prelude::*;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::proto::{
+    luminair_server::Luminair, prove_response, verify_request, GetModelRequest, GetModelResponse,
+    ProveResponse, TensorChunk, VerifyRequest, VerifyResponse,
+};
+
+/// Messages generated from `proto/luminair.proto`.
+pub mod proto {
+    tonic::include_proto!("luminair.v1");
+}
+
+/// Values per tensor chunk sent by the service, 256 KiB of `f32`.
+pub const CHUNK_VALUES: usize = 1 << 16;
+
+/// Bytes per proof or settings chunk sent by the service.
+pub const CHUNK_BYTES: usize = 1 << 20;
+
+/// Maximum number of input values received by `Run` or `Prove`, 1 GiB of `f32`.
+pub const MAX_INPUT_VALUES: usize = 1 << 28;
+
+/// Maximum size of the proof, and of the settings, received by `Verify`, 256 MiB.
+pub const MAX_VERIFY_BYTES: usize = 256 << 20;
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Reassembles tensors received as `TensorChunk`s.
+///
+/// Shapes and received values are limited to `MAX_INPUT_VALUES` values in total.
+#[derive(Debug, Default)]
+pub struct Assembler {
+    tensors: BTreeMap<String, (Vec<usize>, Vec<f32>)>,
+    last: Option<String>,
+    values: usize,
+}
+
+impl Assembler {
+    /// Appends a chunk to its tensor, which must be the one of the previous chunk if known.
+    pub fn push(&mut self, chunk: TensorChunk) -> Result<(), LuminairError> {
+        let shape = chunk
+            .shape
+            .iter()
+            .map(|&d| usize::try_from(d).ok())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| too_large(&chunk.name))?;
+        if shape_size(&shape).map_or(true, |size| size > MAX_INPUT_VALUES) {
+            return Err(too_large(&chunk.name));
+        }
+        self.values = self
+            .values
+            .checked_add(chunk.data.len())
+            .filter(|&values| values <= MAX_INPUT_VALUES)
+            .ok_or_else(|| too_large(&chunk.name))?;
+
+        match self.tensors.get_mut(&chunk.name) {
+            Some(_) if self.last.as_deref() != Some(chunk.name.as_str()) => {
+                return Err(LuminairError::InvalidGraph(format!(
+                    "Chunks of tensor '{}' are not consecutive",
+                    chunk.name
+                )));
+            }
+            Some((known, _)) if !shape.is_empty() && shape != *known => {
+                return Err(LuminairError::InvalidGraph(format!(
+                    "Chunk of tensor '{}' has shape {:?}, previous chunks {:?}",
+                    chunk.name, shape, known
+                )));
+            }
+            Some((_, data)) => data.extend(chunk.data),
+            None => {
+                self.tensors.insert(chunk.name.clone(), (shape, chunk.data));
+            }
+        }
+        self.last = Some(chunk.name);
+        Ok(())
+    }
+
+    /// Checks every tensor received all its values.
+    pub fn finish(self) -> Result<BTreeMap<String, NpyArray>, LuminairError> {
+        self.tensors
+            .into_iter()
+            .map(|(name, (shape, data))| {
+                let expected = shape_size(&shape).ok_or_else(|| too_large(&name))?;
+                if data.len() != expected {
+                    return Err(LuminairError::InvalidGraph(format!(
+                        "Tensor '{}' has {} values, shape {:?} expects {}",
+                        name,
+                        data.len(),
+                        shape,
+                        expected
+                    )));
+                }
+                Ok((name, NpyArray::new(shape, data)?))
+            })
+            .collect()
+    }
+}
+
+/// Number of values of a tensor of `shape`, `None` if it overflows.
+fn shape_size(shape: &[usize]) -> Option<usize> {
+    shape
+        .iter()
+        .try_fold(1usize, |size, &d| size.checked_mul(d))
+}
+
+fn too_large(name: &str) -> LuminairError {
+    LuminairError::InvalidGraph(format!(
+        "Tensor '{}' exceeds {} input values",
+        name, MAX_INPUT_VALUES
+    ))
+}
+
+/// Splits a tensor into chunks of at most `CHUNK_VALUES` values, the first carrying its shape.
+pub fn tensor_chunks(name: &str, array: &NpyArray) -> Vec<TensorChunk> {
+    let mut chunks = array
+        .data
+        .chunks(CHUNK_VALUES)
+        .map(|data| TensorChunk {
+            name: name.to_string(),
+            shape: Vec::new(),
+            data: data.to_vec(),
+        })
+        .collect::<Vec<_>>();
+    if chunks.is_empty() {
+        chunks.push(TensorChunk {
+            name: name.to_string(),
+            shape: Vec::new(),
+            data: Vec::new(),
+        });
+    }
+    chunks[0].shape = array.shape.iter().map(|&d| d as u64).collect();
+    chunks
+}
+
+/// Result of executing the model on a set of inputs.
+#[derive(Clone, Debug)]
+pub struct Execution {
+    pub outputs: BTreeMap<String, NpyArray>,
+    /// Serialized proof and circuit settings, if proven.
+    pub proof: Option<(Vec<u8>, Vec<u8>)>,
+    /// Operators of a proven execution that ran on the CPU, outside of the proof.
+    pub unproven_ops: Vec<String>,
+}
+
+/// gRPC service executing, proving and verifying a single model.
+///
+/// Executions run on Tokio's blocking thread pool, so requests are served concurrently.
+#[derive(Clone)]
+pub struct LuminairService {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    description: ModelDescription,
+    weights: Option<SafetensorsWeights>,
+    config: ProjectConfig,
+}
+
+impl LuminairService {
+    /// Checks the model builds and loads the project configuration.
+    ///
+    /// Inputs found in `weights` don't need to be sent with each request.
+    pub fn new(
+        description: ModelDescription,
+        weights: Option<SafetensorsWeights>,
+    ) -> Result<Self, LuminairError> {
+        description.build(&mut Graph::new())?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                description,
+                weights,
+                config: ProjectConfig::load()?,
+            }),
+        })
+    }
+
+    /// Executes the model on `inputs`, and proves the execution if `prove_execution` is set.
+    pub fn execute(
+        &self,
+        inputs: &BTreeMap<String, NpyArray>,
+        prove_execution: bool,
+    ) -> Result<Execution, LuminairError> {
+        let inner = &self.inner;
+        let mut cx = Graph::new();
+        let weights = inner.weights.as_ref();
+        let model = inner
+            .description
+            .compile(&mut cx, inputs, weights, &inner.config)?;
+
+        if !prove_execution {
+            return Ok(Execution {
+                outputs: model.run(&mut cx)?,
+                proof: None,
+                unproven_ops: Vec::new(),
+            });
+        }
+        let proven = model.prove(&mut cx, None)?;
+        Ok(Execution {
+            proof: Some(proven.to_bincode()?),
+            outputs: proven.outputs,
+            unproven_ops: proven.proof.claim.unproven_ops,
+        })
+    }
+
+    /// Receives the inputs of a request and executes the model off the async runtime.
+    async fn execute_streamed(
+        &self,
+        mut stream: Streaming<TensorChunk>,
+        prove_execution: bool,
+    ) -> Result<Execution, Status> {
+        let mut assembler = Assembler::default();
+        while let Some(chunk) = stream.message().await? {
+            assembler.push(chunk).map_err(status)?;
+        }
+        let inputs = assembler.finish().map_err(status)?;
+
+        let service = self.clone();
+        tokio::task::spawn_blocking(move || service.execute(&inputs, prove_execution))
+            .await
+            .map_err(|e| Status::internal(format!("Execution panicked: {}", e)))?
+            .map_err(status)
+    }
+}
+
+#[tonic::async_trait]
+impl Luminair for LuminairService {
+    type RunStream = ResponseStream<TensorChunk>;
+    type ProveStream = ResponseStream<ProveResponse>;
+
+    async fn run(
+        &self,
+        request: Request<Streaming<TensorChunk>>,
+    ) -> Result<Response<Self::RunStream>, Status> {
+        let execution = self.execute_streamed(request.into_inner(), false).await?;
+        let chunks = output_chunks(&execution.outputs)
+            .into_iter()
+            .map(Ok::<_, Status>);
+        Ok(Response::new(Box::pin(tokio_stream::iter(chunks))))
+    }
+
+    async fn prove(
+        &self,
+        request: Request<Streaming<TensorChunk>>,
+    ) -> Result<Response<Self::ProveStream>, Status> {
+        let execution = self.execute_streamed(request.into_inner(), true).await?;
+        let (proof, settings) = execution
+            .proof
+            .ok_or_else(|| Status::internal("Execution was not proven"))?;
+
+        let mut chunks = output_chunks(&execution.outputs)
+            .into_iter()
+            .map(prove_response::Chunk::Output)
+            .collect::<Vec<_>>();
+        chunks.extend(
+            execution
+                .unproven_ops
+                .into_iter()
+                .map(prove_response::Chunk::UnprovenOp),
+        );
+        chunks.extend(
+            proof
+                .chunks(CHUNK_BYTES)
+                .map(|c| prove_response::Chunk::Proof(c.to_vec())),
+        );
+        chunks.extend(
+            settings
+                .chunks(CHUNK_BYTES)
+                .map(|c| prove_response::Chunk::Settings(c.to_vec())),
+        );
+        let responses = chunks
+            .into_iter()
+            .map(|chunk| Ok::<_, Status>(ProveResponse { chunk: Some(chunk) }));
+        Ok(Response::new(Box::pin(tokio_stream::iter(responses))))
+    }
+
+    async fn verify(
+        &self,
+        request: Request<Streaming<VerifyRequest>>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let mut stream = request.into_inner();
+        let (mut proof, mut settings) = (Vec::new(), Vec::new());
+        while let Some(message) = stream.message().await? {
+            match message.chunk {
+                Some(verify_request::Chunk::Proof(bytes)) => append(&mut proof, bytes, "proof")?,
+                Some(verify_request::Chunk::Settings(bytes)) => {
+                    append(&mut settings, bytes, "settings")?
+                }
+                None => {}
+            }
+        }
+
+        let result = tokio::task::spawn_blocking(move || {
+            let proof = LuminairProof::from_bincode(&proof)?;
+            let settings = CircuitSettings::from_bincode(&settings)?;
+            let unproven_ops = proof.claim.unproven_ops.clone();
+            verify(proof, settings).map(|()| unproven_ops)
+        })
+        .await
+        .map_err(|e| Status::internal(format!("Verification panicked: {}", e)))?;
+
+        Ok(Response::new(match result {
+            Ok(unproven_ops) => VerifyResponse {
+                valid: true,
+                error: String::new(),
+                unproven_ops,
+            },
+            Err(e) => VerifyResponse {
+                valid: false,
+                error: e.to_string(),
+                unproven_ops: Vec::new(),
+            },
+        }))
+    }
+
+    async fn get_model(
+        &self,
+        _: Request<GetModelRequest>,
+    ) -> Result<Response<GetModelResponse>, Status> {
+        let description = serde_json::to_string(&self.inner.description)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(GetModelResponse { description }))
+    }
+}
+
+fn output_chunks(outputs: &BTreeMap<String, NpyArray>) -> Vec<TensorChunk> {
+    outputs
+        .iter()
+        .flat_map(|(name, array)| tensor_chunks(name, array))
+        .collect()
+}
+
+/// Appends a chunk received by `Verify`, unless it makes `buffer` exceed `MAX_VERIFY_BYTES`.
+fn append(buffer: &mut Vec<u8>, bytes: Vec<u8>, what: &str) -> Result<(), Status> {
+    if buffer.len() + bytes.len() > MAX_VERIFY_BYTES {
+        return Err(Status::resource_exhausted(format!(
+            "The {} exceeds {} bytes",
+            what, MAX_VERIFY_BYTES
+        )));
+    }
+    buffer.extend(bytes);
+    Ok(())
+}
+
+/// Maps an error to the gRPC status of its cause.
+fn status(e: LuminairError) -> Status {
+    match e {
+        LuminairError::InvalidGraph(_) | LuminairError::WeightsError(_) => {
+            Status::invalid_argument(e.to_string())
+        }
+        LuminairError::ConfigError(_) => Status::failed_precondition(e.to_string()),
+        _ => Status::internal(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: &str = r#"{
+        "inputs": [{ "name": "a", "shape": [2, 2] }],
+        "nodes": [{ "name": "b", "op": "mul", "inputs": ["a", "a"] }],
+        "outputs": ["b"]
+    }"#;
+
+    #[test]
+    fn test_chunks_round_trip() {
+        let array = NpyArray::new(vec![3, CHUNK_VALUES], vec![0.5; 3 * CHUNK_VALUES]).unwrap();
+        let chunks = tensor_chunks("x", &array);
+        assert_eq!(chunks.len(), 3);
+
+        let mut assembler = Assembler::default();
+        for chunk in chunks {
+            assembler.push(chunk).unwrap();
+        }
+        assert_eq!(assembler.finish().unwrap()["x"], array);
+
+        // Chunks of a tensor must be consecutive and complete
+        let mut assembler = Assembler::default();
+        let mut chunks = tensor_chunks("x", &array);
+        assembler.push(chunks.remove(0)).unwrap();
+        assembler
+            .push(tensor_chunks("y", &NpyArray::new(vec![1], vec![1.0]).unwrap())[0].clone())
+            .unwrap();
+        assert!(assembler.push(chunks.remove(0)).is_err());
+        let mut assembler = Assembler::default();
+        assembler
+            .push(tensor_chunks("x", &array)[0].clone())
+            .unwrap();
+        assert!(assembler.finish().is_err());
+    }
+
+    #[test]
+    fn test_input_size_limit() {
+        for shape in [vec![u64::MAX, 2], vec![MAX_INPUT_VALUES as u64 + 1]] {
+            let chunk = TensorChunk {
+                name: "x".to_string(),
+                shape,
+                data: Vec::new(),
+            };
+            assert!(matches!(
+                Assembler::default().push(chunk),
+                Err(LuminairError::InvalidGraph(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_verify_size_limit() {
+        let mut proof = vec![0; MAX_VERIFY_BYTES - 1];
+        append(&mut proof, vec![0], "proof").unwrap();
+        let error = append(&mut proof, vec![0], "proof").unwrap_err();
+        assert_eq!(error.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(proof.len(), MAX_VERIFY_BYTES);
+    }
+
+    #[tokio::test]
+    async fn test_prove_and_verify() {
+        let description = ModelDescription::from_json(MODEL).unwrap();
+        let service = LuminairService::new(description, None).unwrap();
+
+        let inputs = BTreeMap::from([(
+            "a".to_string(),
+            NpyArray::new(vec![2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap(),
+        )]);
+        let execution = service.execute(&inputs, true).unwrap();
+        assert_eq!(execution.outputs["b"].data, [1.0, 4.0, 9.0, 16.0]);
+
+        let (proof, settings) = execution.proof.unwrap();
+        verify(
+            LuminairProof::from_bincode(&proof).unwrap(),
+            CircuitSettings::from_bincode(&settings).unwrap(),
+        )
+        .unwrap();
+
+        let model = service
+            .get_model(Request::new(GetModelRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            ModelDescription::from_json(&model.description).unwrap(),
+            service.inner.description
+        );
+    }
+}
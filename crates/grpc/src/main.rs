@@ -0,0 +1,54 @@
+use std::process::ExitCode;
+
+use luminair_graph::{description::ModelDescription, weights::SafetensorsWeights};
+use luminair_grpc::{proto::luminair_server::LuminairServer, LuminairService};
+use tonic::transport::Server;
+
+const USAGE: &str = "\
+Usage: luminair-grpc <model.json> [options]
+
+Serves a model over gRPC, see proto/luminair.proto.
+
+Options:
+  --addr <host:port>    Listen address [default: 127.0.0.1:50051]
+  --weights <file>      Weights in .safetensors format
+";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match serve(std::env::args().skip(1).collect()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn serve(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut model, mut addr, mut weights) = (None, "127.0.0.1:50051".to_string(), None);
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = args.next().ok_or("Missing value for --addr")?,
+            "--weights" => weights = Some(args.next().ok_or("Missing value for --weights")?),
+            "--help" | "-h" => {
+                print!("{}", USAGE);
+                return Ok(());
+            }
+            _ if model.is_none() && !arg.starts_with("--") => model = Some(arg),
+            _ => return Err(format!("Unexpected argument '{}'\n\n{}", arg, USAGE).into()),
+        }
+    }
+    let model = model.ok_or(format!("Missing model description path\n\n{}", USAGE))?;
+
+    let weights = weights.map(SafetensorsWeights::from_file).transpose()?;
+    let service = LuminairService::new(ModelDescription::from_file(model)?, weights)?;
+
+    println!("Serving gRPC on {}", addr);
+    Server::builder()
+        .add_service(LuminairServer::new(service))
+        .serve(addr.parse()?)
+        .await?;
+    Ok(())
+}
@@ -3,12 +3,48 @@ pub use luminal::prelude::*;
 pub use luminal_nn::*;
 
 // --- luminair_graph ---
+pub use luminair_graph::batch::{run_batch, BatchRun};
+pub use luminair_graph::bench::{bench_graph, BenchResult, NodeBench};
+pub use luminair_graph::compress::CompressedData;
+pub use luminair_graph::config::ProjectConfig;
+pub use luminair_graph::cost::{ComponentCost, CostEstimate, OpCost};
+pub use luminair_graph::data::{
+    CustomData, CustomDtype, GetTensorData, HalfData, IntegerData, OutputData, OutputKind,
+    SetTensorData,
+};
+pub use luminair_graph::doctor::{diagnose, CheckStatus, Diagnostic, DoctorReport};
+pub use luminair_graph::export::{ExportedEdge, ExportedNode, GraphExport};
+pub use luminair_graph::gguf::{GgufValue, GgufWeights};
 pub use luminair_graph::graph::LuminairGraph;
-pub use luminair_graph::StwoCompiler;
+pub use luminair_graph::metrics::Metrics;
+pub use luminair_graph::models::{mlp_classifier, small_cnn, transformer_block, ExampleModel};
+pub use luminair_graph::npy::{read_npz, write_npz, NpyArray};
+pub use luminair_graph::onnx::{OnnxModel, SUPPORTED_ONNX_OPS};
+pub use luminair_graph::precision::{OpPrecision, PrecisionReport};
+pub use luminair_graph::profile::{profile_graph, Timeline, TimelineEvent};
+pub use luminair_graph::quant::{QuantGranularity, QuantizedData};
+pub use luminair_graph::report::{LoweredOp, LoweringReport};
+pub use luminair_graph::sparse::SparseData;
+pub use luminair_graph::weights::SafetensorsWeights;
+pub use luminair_graph::{
+    CpuPartitionCompiler, RematerializeCompiler, StwoCompiler, StwoFolding, StwoLowering,
+    StwoOptimization, ValidationCompiler, WeightsCompiler,
+};
 
 // --- luminair_prover ---
-pub use luminair_prover::{prover::prove, LuminairProof};
+pub use luminair_prover::{
+    prover::prove,
+    summary::{ComponentSummary, ProofSummary, SecuritySummary},
+    LuminairProof,
+};
 
 // --- luminair_verifier ---
-pub use luminair_air::{pie::LuminairPie, settings::CircuitSettings};
+pub use luminair_air::fixed::{
+    conformance_self_test, f32_to_fixed, fixed_to_f32, to_fixed, RoundingMode,
+};
+pub use luminair_air::hash::{hash_f32_tensor, hash_tensor, hash_tensors};
+pub use luminair_air::{
+    pie::{LuminairPie, NodeTiming},
+    settings::CircuitSettings,
+};
 pub use luminair_verifier::verifier::verify;
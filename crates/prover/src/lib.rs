@@ -9,7 +9,9 @@ use std::{
 use stwo_prover::core::vcs::blake2_merkle::Blake2sMerkleHasher;
 use stwo_prover::core::{prover::StarkProof, vcs::ops::MerkleHasher};
 
+#[cfg(feature = "prover")]
 pub mod prover;
+pub mod summary;
 
 /// Represents the complete proof for a LuminAIR computation.
 ///
@@ -34,6 +34,8 @@ pub fn prove(
     pie: LuminairPie,
     settings: CircuitSettings,
 ) -> Result<LuminairProof<Blake2sMerkleHasher>, LuminairError> {
+    settings.check_fp_scale()?;
+
     // ┌──────────────────────────┐
     // │     Protocol Setup       │
     // └──────────────────────────┘
@@ -68,11 +70,16 @@ pub fn prove(
     // └───────────────────────────────────────┘
 
     tracing::info!("Main Trace");
-    let mut main_claim = LuminairClaim::default();
+    let mut main_claim = LuminairClaim {
+        unproven_ops: pie.execution_resources.unproven_ops,
+        ..Default::default()
+    };
     let mut interaction_claim_gen = LuminairInteractionClaimGenerator::default();
     let mut tree_builder = commitment_scheme.tree_builder();
 
-    for table in pie.trace_tables.clone() {
+    // Consume the trace tables directly: each one is moved into its claim generator
+    // instead of being cloned, which would double peak memory for large traces.
+    for table in pie.trace_tables {
         match table {
             TraceTable::Add { table } => {
                 let claim_gen = add::witness::ClaimGenerator::new(table);
@@ -0,0 +1,248 @@
+use std::fmt::{self, Write};
+
+use luminair_air::{components::InteractionClaim, LuminairClaim, LuminairInteractionClaim};
+use luminair_utils::LuminairError;
+use serde::Serialize;
+use stwo_prover::{
+    constraint_framework::PREPROCESSED_TRACE_IDX,
+    core::{
+        pcs::PcsConfig,
+        vcs::{blake2_hash::Blake2sHasher, blake2_merkle::Blake2sMerkleHasher},
+    },
+};
+
+use crate::LuminairProof;
+
+/// Domain separator of trace layout hashes.
+const TRACE_LAYOUT_DOMAIN: &[u8] = b"luminair.trace_layout.v1";
+
+/// Public inputs of a single AIR component, as attested by a proof.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct ComponentSummary {
+    /// Name of the AIR component.
+    pub component: String,
+    /// Log2 size of the component's trace.
+    pub log_size: u32,
+    /// LogUp sum claimed by the component's interaction trace.
+    pub claimed_sum: Option<String>,
+}
+
+/// Security parameters of the polynomial commitment scheme.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct SecuritySummary {
+    pub pow_bits: u32,
+    pub log_blowup_factor: u32,
+    pub n_queries: usize,
+    /// Conjectured security, `pow_bits + log_blowup_factor * n_queries`.
+    pub security_bits: u32,
+}
+
+/// Decoded contents of a proof, to audit what it attests to.
+///
+/// Generated by `LuminairProof::summary`.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct ProofSummary {
+    /// Hash of the trace layout: the components used, their trace sizes and the
+    /// preprocessed commitment.
+    ///
+    /// Proofs of the same model on inputs of the same shapes share it. It doesn't identify
+    /// the model: another model using the same components with the same trace sizes has
+    /// the same layout.
+    pub trace_layout_hash: String,
+    /// Components proven, with their public claims.
+    pub components: Vec<ComponentSummary>,
+    /// Operators executed on the CPU, whose outputs the proof does not attest to.
+    pub unproven_ops: Vec<String>,
+    /// Merkle roots of the preprocessed, main and interaction traces.
+    pub commitments: Vec<String>,
+    pub security: SecuritySummary,
+    /// Serialized size of each section of the proof, in bytes.
+    pub sizes: Vec<(String, u64)>,
+}
+
+impl ProofSummary {
+    /// Total serialized size of the proof, in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.sizes.iter().map(|(_, size)| size).sum()
+    }
+
+    pub fn to_json(&self) -> Result<String, LuminairError> {
+        serde_json::to_string_pretty(self).map_err(|e| {
+            LuminairError::SerializationError(format!(
+                "Failed to serialize proof summary to JSON: {}",
+                e
+            ))
+        })
+    }
+}
+
+impl fmt::Display for ProofSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Trace layout hash: {}", self.trace_layout_hash)?;
+        writeln!(f, "Components:")?;
+        for c in &self.components {
+            write!(f, "  {}: log size {}", c.component, c.log_size)?;
+            if let Some(sum) = &c.claimed_sum {
+                write!(f, ", claimed sum {}", sum)?;
+            }
+            writeln!(f)?;
+        }
+        if !self.unproven_ops.is_empty() {
+            writeln!(f, "Unproven operators: {}", self.unproven_ops.join(", "))?;
+        }
+        writeln!(f, "Commitments:")?;
+        for (name, commitment) in ["preprocessed", "main", "interaction"]
+            .iter()
+            .zip(&self.commitments)
+        {
+            writeln!(f, "  {}: {}", name, commitment)?;
+        }
+        writeln!(
+            f,
+            "Security: {} bits ({} queries, log blowup {}, {} proof-of-work bits)",
+            self.security.security_bits,
+            self.security.n_queries,
+            self.security.log_blowup_factor,
+            self.security.pow_bits
+        )?;
+        writeln!(f, "Size: {} bytes", self.total_size())?;
+        for (section, size) in &self.sizes {
+            writeln!(f, "  {}: {}", section, size)?;
+        }
+        Ok(())
+    }
+}
+
+impl LuminairProof<Blake2sMerkleHasher> {
+    /// Decodes the public inputs, security parameters and size breakdown of the proof.
+    ///
+    /// Security parameters are the ones `prove` and `verify` use; nothing here is verified.
+    pub fn summary(&self) -> Result<ProofSummary, LuminairError> {
+        let components = components(&self.claim, &self.interaction_claim);
+
+        let mut hasher = Blake2sHasher::new();
+        hasher.update(TRACE_LAYOUT_DOMAIN);
+        for c in &components {
+            hasher.update(&(c.component.len() as u64).to_le_bytes());
+            hasher.update(c.component.as_bytes());
+            hasher.update(&c.log_size.to_le_bytes());
+        }
+        let preprocessed = self
+            .proof
+            .commitments
+            .get(PREPROCESSED_TRACE_IDX)
+            .ok_or_else(|| {
+                LuminairError::SerializationError(
+                    "Proof has no preprocessed trace commitment".to_string(),
+                )
+            })?;
+        hasher.update(&preprocessed.0);
+
+        let config = PcsConfig::default();
+        let fri = config.fri_config;
+        let security = SecuritySummary {
+            pow_bits: config.pow_bits,
+            log_blowup_factor: fri.log_blowup_factor,
+            n_queries: fri.n_queries,
+            security_bits: config.pow_bits + fri.log_blowup_factor * fri.n_queries as u32,
+        };
+
+        let proof = &self.proof;
+        let sizes = vec![
+            (
+                "claims",
+                serialized_size(&(&self.claim, &self.interaction_claim))?,
+            ),
+            ("commitments", serialized_size(&proof.commitments)?),
+            ("sampled values", serialized_size(&proof.sampled_values)?),
+            ("decommitments", serialized_size(&proof.decommitments)?),
+            ("queried values", serialized_size(&proof.queried_values)?),
+            ("proof of work", serialized_size(&proof.proof_of_work)?),
+            ("FRI proof", serialized_size(&proof.fri_proof)?),
+        ];
+
+        Ok(ProofSummary {
+            trace_layout_hash: hex(&hasher.finalize().0),
+            components,
+            unproven_ops: self.claim.unproven_ops.clone(),
+            commitments: proof.commitments.iter().map(|c| hex(&c.0)).collect(),
+            security,
+            sizes: sizes
+                .into_iter()
+                .map(|(section, size)| (section.to_string(), size))
+                .collect(),
+        })
+    }
+}
+
+/// Lists the components present in the claim, in proving order.
+fn components(
+    claim: &LuminairClaim,
+    interaction_claim: &LuminairInteractionClaim,
+) -> Vec<ComponentSummary> {
+    let entries: [(&str, Option<u32>, &Option<InteractionClaim>); 8] = [
+        (
+            "add",
+            claim.add.as_ref().map(|c| c.log_size),
+            &interaction_claim.add,
+        ),
+        (
+            "mul",
+            claim.mul.as_ref().map(|c| c.log_size),
+            &interaction_claim.mul,
+        ),
+        (
+            "recip",
+            claim.recip.as_ref().map(|c| c.log_size),
+            &interaction_claim.recip,
+        ),
+        (
+            "sin",
+            claim.sin.as_ref().map(|c| c.log_size),
+            &interaction_claim.sin,
+        ),
+        (
+            "sin_lookup",
+            claim.sin_lookup.as_ref().map(|c| c.log_size),
+            &interaction_claim.sin_lookup,
+        ),
+        (
+            "sum_reduce",
+            claim.sum_reduce.as_ref().map(|c| c.log_size),
+            &interaction_claim.sum_reduce,
+        ),
+        (
+            "max_reduce",
+            claim.max_reduce.as_ref().map(|c| c.log_size),
+            &interaction_claim.max_reduce,
+        ),
+        (
+            "sqrt",
+            claim.sqrt.as_ref().map(|c| c.log_size),
+            &interaction_claim.sqrt,
+        ),
+    ];
+    entries
+        .into_iter()
+        .filter_map(|(component, log_size, interaction)| {
+            Some(ComponentSummary {
+                component: component.to_string(),
+                log_size: log_size?,
+                claimed_sum: interaction.as_ref().map(|c| c.claimed_sum.to_string()),
+            })
+        })
+        .collect()
+}
+
+fn serialized_size<T: Serialize>(value: &T) -> Result<u64, LuminairError> {
+    bincode::serialized_size(value).map_err(|e| {
+        LuminairError::SerializationError(format!("Failed to measure proof section: {}", e))
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut s, b| {
+        write!(s, "{:02x}", b).unwrap();
+        s
+    })
+}
@@ -0,0 +1,386 @@
+use luminair_air::settings::CircuitSettings;
+use luminair_graph::{graph::LuminairGraph, StwoCompiler};
+use luminair_prover::{prover::prove, LuminairProof};
+use luminair_verifier::verifier::verify as verify_proof;
+use luminal::prelude::*;
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+use stwo_prover::core::vcs::blake2_merkle::Blake2sMerkleHasher;
+
+create_exception!(
+    luminair,
+    LuminairError,
+    PyException,
+    "Error raised by LuminAIR."
+);
+
+fn to_py(e: luminair_utils::LuminairError) -> PyErr {
+    LuminairError::new_err(e.to_string())
+}
+
+/// A computation graph, proven with the Stwo pipeline once compiled.
+///
+/// Tensors are created with `tensor` and combined with Python operators and
+/// methods. `compile` lowers the graph for the outputs given, which can then be
+/// executed with `execute` or proven with `prove`.
+#[pyclass(name = "Graph", unsendable)]
+struct PyGraph {
+    // Boxed so that tensors can keep pointing to the graph when the Python object moves
+    cx: Box<Graph>,
+    compiled: bool,
+}
+
+#[pymethods]
+impl PyGraph {
+    #[new]
+    fn new() -> Self {
+        Self {
+            cx: Box::new(Graph::new()),
+            compiled: false,
+        }
+    }
+
+    /// Adds an input tensor, set to `data` in row-major order if given.
+    #[pyo3(signature = (shape, data=None, name="input"))]
+    fn tensor(
+        slf: &Bound<'_, Self>,
+        shape: Vec<usize>,
+        data: Option<Vec<f32>>,
+        name: &str,
+    ) -> PyResult<PyTensor> {
+        let dims = shape
+            .iter()
+            .map(|&d| Expression::from(d))
+            .collect::<Vec<_>>();
+        let tensor = PyTensor {
+            tensor: slf.borrow_mut().cx.named_tensor(name, dims),
+            graph: slf.clone().unbind(),
+        };
+        if let Some(data) = data {
+            tensor.set(data)?;
+        }
+        Ok(tensor)
+    }
+
+    /// Compiles the graph with `StwoCompiler`, retrieving `outputs`.
+    ///
+    /// The output tensors are updated in place to point to the compiled graph.
+    fn compile(&mut self, mut outputs: Vec<PyRefMut<'_, PyTensor>>) -> PyResult<()> {
+        if self.compiled {
+            return Err(LuminairError::new_err("The graph is already compiled"));
+        }
+        let mut tensors = outputs
+            .iter()
+            .map(|t| t.tensor.retrieve())
+            .collect::<Vec<_>>();
+        self.cx
+            .compile(<(GenericCompiler, StwoCompiler)>::default(), &mut tensors);
+        for (output, compiled) in outputs.iter_mut().zip(tensors) {
+            output.tensor = compiled;
+        }
+        self.compiled = true;
+        Ok(())
+    }
+
+    /// Executes the graph without generating a trace.
+    fn execute(&mut self) -> PyResult<()> {
+        self.check_compiled()?;
+        self.cx.execute();
+        Ok(())
+    }
+
+    /// Executes the graph and proves its execution, returning `(proof, settings)`.
+    ///
+    /// The GIL is released while proving, so other Python threads keep running.
+    fn prove(&mut self, py: Python<'_>) -> PyResult<(Proof, Settings)> {
+        self.check_compiled()?;
+        // `gen_circuit_settings` would panic on unsupported ops rather than return the error
+        self.cx.check_supported_ops().map_err(to_py)?;
+        let mut settings = self.cx.gen_circuit_settings();
+        let trace = self.cx.gen_trace(&mut settings).map_err(to_py)?;
+        // The graph isn't `Send`, so only proving, which doesn't use it, runs without the GIL
+        let proof_settings = settings.clone();
+        let proof = py
+            .allow_threads(move || prove(trace, proof_settings))
+            .map_err(to_py)?;
+        Ok((
+            Proof {
+                bytes: proof.to_bincode().map_err(to_py)?,
+            },
+            Settings(settings),
+        ))
+    }
+
+    /// Operators of the compiled graph without an AIR component.
+    ///
+    /// `prove` fails on them with an `UnsupportedOps` error, unless `ops.cpu_fallback` is set in
+    /// `luminair.toml` to execute them on the CPU, unproven.
+    fn unsupported_ops(&self) -> Vec<String> {
+        self.cx
+            .unsupported_ops()
+            .into_iter()
+            .map(|(node, op)| format!("{} (node {})", op, node.index()))
+            .collect()
+    }
+}
+
+impl PyGraph {
+    fn check_compiled(&self) -> PyResult<()> {
+        if !self.compiled {
+            return Err(LuminairError::new_err(
+                "The graph must be compiled before it is executed",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A tensor of a `Graph`.
+#[pyclass(name = "Tensor", unsendable)]
+struct PyTensor {
+    tensor: GraphTensor,
+    // Keeps the graph alive as long as its tensors
+    graph: Py<PyGraph>,
+}
+
+impl PyTensor {
+    fn unary(&self, py: Python<'_>, f: impl FnOnce(GraphTensor) -> GraphTensor) -> Self {
+        Self {
+            tensor: f(self.tensor),
+            graph: self.graph.clone_ref(py),
+        }
+    }
+
+    fn binary(
+        &self,
+        py: Python<'_>,
+        other: &PyTensor,
+        f: impl FnOnce(GraphTensor, GraphTensor) -> GraphTensor,
+    ) -> PyResult<Self> {
+        if !self.graph.is(&other.graph) {
+            return Err(PyValueError::new_err("Tensors belong to different graphs"));
+        }
+        Ok(Self {
+            tensor: f(self.tensor, other.tensor),
+            graph: self.graph.clone_ref(py),
+        })
+    }
+
+    fn dims(&self) -> Vec<usize> {
+        self.tensor
+            .shape
+            .dims()
+            .iter()
+            .map(|d| d.to_usize().unwrap())
+            .collect()
+    }
+}
+
+#[pymethods]
+impl PyTensor {
+    #[getter]
+    fn shape(&self) -> Vec<usize> {
+        self.dims()
+    }
+
+    /// Sets the values of an input tensor, in row-major order.
+    fn set(&self, data: Vec<f32>) -> PyResult<()> {
+        let len = self.dims().iter().product::<usize>();
+        if data.len() != len {
+            return Err(PyValueError::new_err(format!(
+                "Tensor of shape {:?} takes {} values, got {}",
+                self.dims(),
+                len,
+                data.len()
+            )));
+        }
+        self.tensor.set(data);
+        Ok(())
+    }
+
+    /// Values of an output tensor, once the graph has been executed.
+    fn data(&self) -> Vec<f32> {
+        self.tensor.data()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Tensor(shape={:?})", self.dims())
+    }
+
+    fn __add__(&self, py: Python<'_>, other: PyRef<'_, PyTensor>) -> PyResult<Self> {
+        self.binary(py, &other, |a, b| a + b)
+    }
+
+    fn __sub__(&self, py: Python<'_>, other: PyRef<'_, PyTensor>) -> PyResult<Self> {
+        self.binary(py, &other, |a, b| a - b)
+    }
+
+    fn __mul__(&self, py: Python<'_>, other: PyRef<'_, PyTensor>) -> PyResult<Self> {
+        self.binary(py, &other, |a, b| a * b)
+    }
+
+    fn __truediv__(&self, py: Python<'_>, other: PyRef<'_, PyTensor>) -> PyResult<Self> {
+        self.binary(py, &other, |a, b| a / b)
+    }
+
+    fn __matmul__(&self, py: Python<'_>, other: PyRef<'_, PyTensor>) -> PyResult<Self> {
+        self.binary(py, &other, |a, b| a.matmul(b))
+    }
+
+    fn __neg__(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| -a)
+    }
+
+    fn abs(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.abs())
+    }
+
+    fn recip(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.recip())
+    }
+
+    fn sqrt(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.sqrt())
+    }
+
+    fn sin(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.sin())
+    }
+
+    fn cos(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.cos())
+    }
+
+    fn exp(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.exp())
+    }
+
+    fn ln(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.ln())
+    }
+
+    fn relu(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.relu())
+    }
+
+    fn sigmoid(&self, py: Python<'_>) -> Self {
+        self.unary(py, |a| a.sigmoid())
+    }
+
+    fn sum_reduce(&self, py: Python<'_>, axis: usize) -> PyResult<Self> {
+        self.check_axis(axis)?;
+        Ok(self.unary(py, |a| a.sum_reduce(axis)))
+    }
+
+    fn max_reduce(&self, py: Python<'_>, axis: usize) -> PyResult<Self> {
+        self.check_axis(axis)?;
+        Ok(self.unary(py, |a| a.max_reduce(axis)))
+    }
+
+    fn mean_reduce(&self, py: Python<'_>, axis: usize) -> PyResult<Self> {
+        self.check_axis(axis)?;
+        Ok(self.unary(py, |a| a.mean_reduce(axis)))
+    }
+}
+
+impl PyTensor {
+    fn check_axis(&self, axis: usize) -> PyResult<()> {
+        if axis >= self.tensor.shape.len() {
+            return Err(PyValueError::new_err(format!(
+                "Axis {} out of range for a {}-d tensor",
+                axis,
+                self.tensor.shape.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A serialized proof, checked with `verify`.
+#[pyclass]
+struct Proof {
+    bytes: Vec<u8>,
+}
+
+#[pymethods]
+impl Proof {
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.bytes)
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        decode(data).map_err(to_py)?;
+        Ok(Self {
+            bytes: data.to_vec(),
+        })
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        Ok(std::fs::write(path, &self.bytes)?)
+    }
+
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+/// Circuit settings a proof was generated with, needed to verify it.
+#[pyclass]
+struct Settings(CircuitSettings);
+
+#[pymethods]
+impl Settings {
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new_bound(py, &self.0.to_bincode().map_err(to_py)?))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        Ok(Self(CircuitSettings::from_bincode(data).map_err(to_py)?))
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        self.0.to_bincode_file(path).map_err(to_py)
+    }
+
+    #[staticmethod]
+    fn load(path: &str) -> PyResult<Self> {
+        Ok(Self(
+            CircuitSettings::from_bincode_file(path).map_err(to_py)?,
+        ))
+    }
+}
+
+fn decode(
+    data: &[u8],
+) -> Result<LuminairProof<Blake2sMerkleHasher>, luminair_utils::LuminairError> {
+    LuminairProof::from_bincode(data)
+}
+
+/// Verifies `proof` against the settings it was generated with.
+///
+/// Raises `LuminairError` if the proof is invalid.
+#[pyfunction]
+fn verify(py: Python<'_>, proof: &Proof, settings: &Settings) -> PyResult<()> {
+    let proof = decode(&proof.bytes).map_err(to_py)?;
+    let settings = settings.0.clone();
+    py.allow_threads(move || verify_proof(proof, settings))
+        .map_err(to_py)
+}
+
+#[pymodule]
+fn luminair(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGraph>()?;
+    m.add_class::<PyTensor>()?;
+    m.add_class::<Proof>()?;
+    m.add_class::<Settings>()?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    m.add("LuminairError", m.py().get_type_bound::<LuminairError>())?;
+    Ok(())
+}
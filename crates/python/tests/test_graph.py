@@ -0,0 +1,33 @@
+import math
+
+import pytest
+
+import luminair
+
+
+def test_prove_and_verify():
+    g = luminair.Graph()
+    a = g.tensor([2, 2], [1.0, 2.0, 3.0, 4.0])
+    b = (a * a + a).sin()
+    g.compile([b])
+
+    proof, settings = g.prove()
+    assert b.data() == pytest.approx([math.sin(x * x + x) for x in range(1, 5)], abs=1e-2)
+    luminair.verify(proof, settings)
+    luminair.verify(
+        luminair.Proof.from_bytes(proof.to_bytes()),
+        luminair.Settings.from_bytes(settings.to_bytes()),
+    )
+
+
+def test_errors():
+    g = luminair.Graph()
+    with pytest.raises(ValueError):
+        g.tensor([2, 2], [1.0])
+    a = g.tensor([2, 2], [1.0, 2.0, 3.0, 4.0])
+    with pytest.raises(ValueError):
+        a.sum_reduce(2)
+    with pytest.raises(luminair.LuminairError):
+        g.prove()
+    with pytest.raises(luminair.LuminairError):
+        luminair.Proof.from_bytes(b"not a proof")
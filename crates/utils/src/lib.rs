@@ -24,6 +24,42 @@ pub enum LuminairError {
 
     #[error("Serialization error: {0}")]
     SerializationError(String),
+
+    #[error(
+        "Fixed-point scale mismatch: built with {expected} fractional bits, settings use {found}."
+    )]
+    FixedPointScaleMismatch { expected: u32, found: u32 },
+
+    #[error(
+        "Fixed-point overflow in {op} (node {node}) at element {index}: {value} is out of range."
+    )]
+    FixedPointOverflow {
+        op: String,
+        node: u32,
+        index: usize,
+        value: f64,
+    },
+
+    #[error("Fixed-point conformance check failed: {0}")]
+    ConformanceError(String),
+
+    #[error("Unsupported operators: {}.", .0.join(", "))]
+    UnsupportedOps(Vec<String>),
+
+    #[error("Weights loading error: {0}")]
+    WeightsError(String),
+
+    #[error("Invalid graph: {0}")]
+    InvalidGraph(String),
+
+    #[error("Invalid tensor data: {0}")]
+    InvalidTensorData(String),
+
+    #[error("Configuration error: {0}")]
+    ConfigError(String),
+
+    #[error("Trace layout mismatch: expected trace layout {expected}, the proof has {found}.")]
+    TraceLayoutMismatch { expected: String, found: String },
 }
 
 /// Errors that can occur during AIR trace generation or processing.
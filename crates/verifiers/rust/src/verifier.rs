@@ -39,6 +39,8 @@ pub fn verify(
     let _span = span!(Level::INFO, "luminair_verification").entered();
     info!("🚀 Starting LuminAIR proof verification");
 
+    settings.check_fp_scale()?;
+
     // Convert lookups in circuit settings to preprocessed column.
     let lut_cols = lookups_to_preprocessed_column(&settings.lookups);
     let preprocessed_trace = PreProcessedTrace::new(lut_cols);
@@ -146,3 +148,24 @@ pub fn verify(
         }
     }
 }
+
+/// Verifies a STWO proof and checks it has the expected trace layout.
+///
+/// `trace_layout_hash` is the hex hash from `LuminairProof::summary`, shared by all proofs of a
+/// model on inputs of the same shapes. A proof with another layout is rejected with
+/// `LuminairError::TraceLayoutMismatch`. This doesn't bind the proof to a model: another
+/// model using the same components with the same trace sizes passes the check.
+pub fn verify_trace_layout(
+    proof: LuminairProof<Blake2sMerkleHasher>,
+    settings: CircuitSettings,
+    trace_layout_hash: &str,
+) -> Result<(), LuminairError> {
+    let found = proof.summary()?.trace_layout_hash;
+    if !found.eq_ignore_ascii_case(trace_layout_hash.trim()) {
+        return Err(LuminairError::TraceLayoutMismatch {
+            expected: trace_layout_hash.to_string(),
+            found,
+        });
+    }
+    verify(proof, settings)
+}
@@ -29,6 +29,26 @@ export function verify(
   settingsBytes: Uint8Array
 ): VerificationResult;
 
+/**
+ * Verifies a LuminAIR proof and checks it has the expected trace layout
+ * @param proofBytes - Binary proof data as Uint8Array
+ * @param settingsBytes - Binary settings data as Uint8Array
+ * @param traceLayoutHash - Expected trace layout hash, in hex, as reported by `proof_summary`
+ * @returns Verification result
+ */
+export function verify_trace_layout(
+  proofBytes: Uint8Array,
+  settingsBytes: Uint8Array,
+  traceLayoutHash: string
+): VerificationResult;
+
+/**
+ * Decodes the public inputs of a proof without verifying it
+ * @param proofBytes - Binary proof data as Uint8Array
+ * @returns JSON summary: trace layout hash, components, commitments, security and sizes
+ */
+export function proof_summary(proofBytes: Uint8Array): string;
+
 /**
  * Tests if the WASM module is working correctly
  * @returns Success message
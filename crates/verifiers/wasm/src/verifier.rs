@@ -1,6 +1,6 @@
 use luminair_air::settings::CircuitSettings;
 use luminair_prover::LuminairProof;
-use luminair_verifier::verifier::verify as verify_rust;
+use luminair_verifier::verifier::{verify as verify_rust, verify_trace_layout as verify_trace_layout_rust};
 use stwo_prover::core::vcs::blake2_merkle::Blake2sMerkleHasher;
 use wasm_bindgen::prelude::*;
 use tracing::{info, span, Level};
@@ -39,7 +39,7 @@ pub fn verify(proof_bytes: &[u8], settings_bytes: &[u8]) -> VerificationResult {
     info!("🌟 LuminAIR WASM Verifier: Beginning verification process");
 
     // Parse the proof from bincode
-    let proof: LuminairProof<Blake2sMerkleHasher> = match bincode::deserialize(proof_bytes) {
+    let proof: LuminairProof<Blake2sMerkleHasher> = match LuminairProof::from_bincode(proof_bytes) {
         Ok(proof) => {
             console_info("✅ Successfully parsed proof binary");
             info!("📦 Proof parsing: Success");
@@ -57,7 +57,7 @@ pub fn verify(proof_bytes: &[u8], settings_bytes: &[u8]) -> VerificationResult {
     };
 
     // Parse the settings from bincode
-    let settings: CircuitSettings = match bincode::deserialize(settings_bytes) {
+    let settings: CircuitSettings = match CircuitSettings::from_bincode(settings_bytes) {
         Ok(settings) => {
             console_info("✅ Successfully parsed settings binary");
             info!("⚙️  Settings parsing: Success");
@@ -99,6 +99,64 @@ pub fn verify(proof_bytes: &[u8], settings_bytes: &[u8]) -> VerificationResult {
     }
 }
 
+/// Verifies a LuminAIR proof and checks it has the expected trace layout.
+///
+/// `trace_layout_hash` is the hex hash reported by `proof_summary` or `luminair proof inspect`,
+/// shared by all proofs of a model on inputs of the same shapes. Proofs with another layout
+/// are rejected, but the hash doesn't identify the model: another model using the same
+/// components with the same trace sizes has the same layout.
+#[wasm_bindgen]
+pub fn verify_trace_layout(
+    proof_bytes: &[u8],
+    settings_bytes: &[u8],
+    trace_layout_hash: &str,
+) -> VerificationResult {
+    let _span = span!(Level::INFO, "wasm_trace_layout_verification").entered();
+    console_info("🌟 Starting WASM proof verification against the expected trace layout...");
+
+    let proof: LuminairProof<Blake2sMerkleHasher> = match LuminairProof::from_bincode(proof_bytes) {
+        Ok(proof) => proof,
+        Err(e) => return failure(format!("Failed to parse proof binary: {}", e)),
+    };
+    let settings: CircuitSettings = match CircuitSettings::from_bincode(settings_bytes) {
+        Ok(settings) => settings,
+        Err(e) => return failure(format!("Failed to parse settings binary: {}", e)),
+    };
+
+    match verify_trace_layout_rust(proof, settings, trace_layout_hash) {
+        Ok(()) => {
+            console_info("🎉 Proof verification successful, trace layout matches! ✅");
+            VerificationResult {
+                success: true,
+                error_message: None,
+            }
+        }
+        Err(e) => failure(format!("Proof verification failed: {}", e)),
+    }
+}
+
+/// Decodes the public inputs of a proof without verifying it.
+///
+/// Returns the JSON summary of `luminair proof inspect`: trace layout hash, components with
+/// their claims, commitments, security parameters and size breakdown.
+#[wasm_bindgen]
+pub fn proof_summary(proof_bytes: &[u8]) -> Result<String, JsValue> {
+    let proof: LuminairProof<Blake2sMerkleHasher> = LuminairProof::from_bincode(proof_bytes)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse proof binary: {}", e)))?;
+    proof
+        .summary()
+        .and_then(|summary| summary.to_json())
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn failure(error_msg: String) -> VerificationResult {
+    console_error(&error_msg);
+    VerificationResult {
+        success: false,
+        error_message: Some(error_msg),
+    }
+}
+
 /// Utility function to check if the WASM module is working correctly
 #[wasm_bindgen]
 pub fn test_wasm_module() -> String {